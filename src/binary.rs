@@ -2,7 +2,7 @@
 use colored::Colorize;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
-use std::{fmt, usize};
+use std::fmt;
 
 #[cfg(feature = "elf")]
 use checksec::elf;
@@ -10,6 +10,10 @@ use checksec::elf;
 use checksec::macho;
 #[cfg(feature = "pe")]
 use checksec::pe;
+#[cfg(feature = "pe")]
+use checksec::resource::VersionInfo;
+
+use crate::privilege::FilePrivileges;
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum BinType {
@@ -65,6 +69,7 @@ impl fmt::Display for BinType {
     }
 }
 
+#[allow(clippy::large_enum_variant)]
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum BinSpecificProperties {
     #[cfg(feature = "elf")]
@@ -91,6 +96,10 @@ impl fmt::Display for BinSpecificProperties {
 pub struct Blob {
     pub binarytype: BinType,
     pub properties: BinSpecificProperties,
+    /// PE `VS_VERSIONINFO` metadata
+    #[cfg(feature = "pe")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_info: Option<VersionInfo>,
 }
 
 impl Blob {
@@ -98,7 +107,22 @@ impl Blob {
         binarytype: BinType,
         properties: BinSpecificProperties,
     ) -> Self {
-        Self { binarytype, properties }
+        Self {
+            binarytype,
+            properties,
+            #[cfg(feature = "pe")]
+            version_info: None,
+        }
+    }
+}
+impl fmt::Display for Blob {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: | {} |", self.binarytype, self.properties)?;
+        #[cfg(feature = "pe")]
+        if let Some(version_info) = &self.version_info {
+            write!(f, " {version_info} |")?;
+        }
+        Ok(())
     }
 }
 
@@ -107,10 +131,13 @@ pub struct Binary {
     pub file: PathBuf,
     pub blobs: Vec<Blob>,
     pub libraries: Vec<Binary>,
+    /// file mode, ownership and capabilities
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub privileges: Option<FilePrivileges>,
 }
 
 impl Binary {
     pub fn new(file: PathBuf, blobs: Vec<Blob>) -> Self {
-        Self { file, blobs, libraries: vec![] }
+        Self { file, blobs, libraries: vec![], privileges: None }
     }
 }
@@ -0,0 +1,179 @@
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::binary::Binary;
+
+/// entries not used by any scan for this long are dropped when saving
+const MAX_UNUSED_SECS: u64 = 30 * 24 * 60 * 60;
+
+// features changing the results, a cache written by a build with other
+// features is discarded
+static RESULT_FEATURES: [(&str, bool); 5] = [
+    ("elf", cfg!(feature = "elf")),
+    ("macho", cfg!(feature = "macho")),
+    ("pe", cfg!(feature = "pe")),
+    ("disassembly", cfg!(feature = "disassembly")),
+    ("maps", cfg!(feature = "maps")),
+];
+
+#[derive(Deserialize, Serialize)]
+struct Entry {
+    /// seconds since the epoch the entry was last used
+    used: u64,
+    /// the file the results were parsed from
+    file: PathBuf,
+    binaries: Vec<Binary>,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+struct CacheFile {
+    /// checksec version, features and settings the results depend on
+    configuration: String,
+    /// results by SHA-256 of the file contents
+    entries: HashMap<String, Entry>,
+}
+
+/// Results of earlier runs, persisted in a single gzip compressed JSON
+/// file and keyed by content hash, so unchanged files are not parsed again
+/// wherever they are
+pub struct ResultCache {
+    path: PathBuf,
+    configuration: String,
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+impl ResultCache {
+    /// `$XDG_CACHE_HOME/checksec/results.json.gz`, or below `~/.cache`
+    pub fn default_path() -> Option<PathBuf> {
+        let base = env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .or_else(|| {
+                env::var_os("HOME").map(|home| Path::new(&home).join(".cache"))
+            })?;
+        Some(base.join("checksec").join("results.json.gz"))
+    }
+    /// Load the cache at `path`, starting empty if there is none yet or it
+    /// was written for another configuration. `settings` are further
+    /// inputs the results depend on, such as the dangerous imports list.
+    pub fn open(path: PathBuf, settings: &str) -> Self {
+        let features: Vec<&str> = RESULT_FEATURES
+            .iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(name, _)| *name)
+            .collect();
+        let configuration = format!(
+            "{} {} {:x}",
+            env!("CARGO_PKG_VERSION"),
+            features.join(","),
+            Sha256::digest(settings.as_bytes())
+        );
+        let entries = match Self::load(&path) {
+            Ok(file) if file.configuration == configuration => file.entries,
+            Ok(_) => HashMap::new(),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                HashMap::new()
+            }
+            Err(err) => {
+                eprintln!("Ignoring result cache {}: {}", path.display(), err);
+                HashMap::new()
+            }
+        };
+        Self { path, configuration, entries: Mutex::new(entries) }
+    }
+    fn load(path: &Path) -> io::Result<CacheFile> {
+        let reader = BufReader::new(GzDecoder::new(fs::File::open(path)?));
+        Ok(serde_json::from_reader(reader)?)
+    }
+    /// Cached results for `bytes`, read from `file`, or the results of
+    /// `parse` which are added to the cache
+    pub fn get_or_parse<E, F>(
+        &self,
+        bytes: &[u8],
+        file: &Path,
+        parse: F,
+    ) -> Result<Vec<Binary>, E>
+    where
+        F: FnOnce(&[u8], &Path) -> Result<Vec<Binary>, E>,
+    {
+        let hash = format!("{:x}", Sha256::digest(bytes));
+        let cached =
+            self.entries.lock().unwrap().get_mut(&hash).map(|entry| {
+                entry.used = now();
+                (entry.file.clone(), entry.binaries.clone())
+            });
+        if let Some((parsed, mut binaries)) = cached {
+            // identical copies share the entry, keep the archive member
+            // suffix of their paths
+            let parsed = parsed.display().to_string();
+            for binary in &mut binaries {
+                let member = binary
+                    .file
+                    .display()
+                    .to_string()
+                    .strip_prefix(&parsed)
+                    .unwrap_or_default()
+                    .to_string();
+                binary.file =
+                    PathBuf::from(format!("{}{}", file.display(), member));
+            }
+            return Ok(binaries);
+        }
+        let binaries = parse(bytes, file)?;
+        self.entries.lock().unwrap().insert(
+            hash,
+            Entry {
+                used: now(),
+                file: file.to_path_buf(),
+                binaries: binaries.clone(),
+            },
+        );
+        Ok(binaries)
+    }
+    /// Write the cache back, dropping entries unused for 30 days
+    pub fn save(&self) -> io::Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        let oldest = now().saturating_sub(MAX_UNUSED_SECS);
+        entries.retain(|_, entry| entry.used >= oldest);
+        let file = CacheFile {
+            configuration: self.configuration.clone(),
+            entries: std::mem::take(&mut *entries),
+        };
+        // replace the cache at once so an interrupted run cannot leave a
+        // truncated one behind
+        let partial = self.path.with_extension("partial");
+        let result = (|| {
+            if let Some(directory) = self.path.parent() {
+                fs::create_dir_all(directory)?;
+            }
+            let mut writer = BufWriter::new(GzEncoder::new(
+                fs::File::create(&partial)?,
+                Compression::fast(),
+            ));
+            serde_json::to_writer(&mut writer, &file)?;
+            writer.into_inner()?.finish()?.sync_all()?;
+            fs::rename(&partial, &self.path)
+        })();
+        *entries = file.entries;
+        result
+    }
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
@@ -6,6 +6,9 @@ use base64::Engine;
 use bincode; 
 
 
+/// # Errors
+/// Returns a message describing the failing serialization or compression
+/// step.
 pub fn compress<T: Serialize>(results: &T) -> Result<String, String> {
     let serialized = bincode::serialize(&results)
         .map_err(|_| "Result serialization to binary failed".to_string())?;
@@ -19,10 +22,12 @@ pub fn compress<T: Serialize>(results: &T) -> Result<String, String> {
         .finish()
         .map_err(|_| "IO error occurred during flush".to_string())?;
 
-    let encoded = BASE64_STANDARD.encode(compressed);
-    Ok(encoded) // original type -> serialized -> compressed -> B64
+    Ok(BASE64_STANDARD.encode(compressed)) // original type -> serialized -> compressed -> B64
 }
 
+/// # Errors
+/// Returns a message describing the failing decoding, decompression or
+/// deserialization step.
 pub fn decompress<T: DeserializeOwned>(encoded_bytes: &[u8]) -> Result<T, String> {
 
     let encoded = std::str::from_utf8(encoded_bytes)
@@ -0,0 +1,134 @@
+//! Implements the debug info and symbol stripping report shared by all
+//! formats
+#[cfg(feature = "color")]
+use colored::Colorize;
+use flate2::read::ZlibDecoder;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::Read;
+
+/// Stripping status: `Full` (no symbols or debug info), `Partial` (symbol
+/// table kept, debug info removed) or `None` (ships debug info)
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
+pub enum Stripped {
+    Full,
+    Partial,
+    None,
+}
+
+impl Stripped {
+    #[must_use]
+    pub fn new(symbols: bool, debug: bool) -> Self {
+        match (symbols, debug) {
+            (_, true) => Self::None,
+            (true, false) => Self::Partial,
+            (false, false) => Self::Full,
+        }
+    }
+}
+
+impl fmt::Display for Stripped {
+    #[cfg(not(feature = "color"))]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<7}",
+            match self {
+                Self::Full => "Full",
+                Self::Partial => "Partial",
+                Self::None => "None",
+            }
+        )
+    }
+    #[cfg(feature = "color")]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<7}",
+            match self {
+                Self::Full => "Full".green(),
+                Self::Partial => "Partial".yellow(),
+                Self::None => "None".red(),
+            }
+        )
+    }
+}
+
+/// Debug info and symbol table report
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
+pub struct DebugInfo {
+    /// combined stripping assessment
+    pub stripped: Stripped,
+    /// local symbol table present (`.symtab`, Mach-O local symbols, COFF
+    /// symbol table)
+    pub symbols: bool,
+    /// DWARF sections (`.debug_*`, `.zdebug_*`, `__DWARF,__debug_*`)
+    pub debug_sections: Vec<String>,
+    /// version of the first DWARF compilation unit
+    pub dwarf_version: Option<u16>,
+    /// Mach-O STABS debug map entries
+    pub stabs: bool,
+    /// separate debug file (`.gnu_debuglink`, PE `CodeView` PDB path)
+    pub debug_file: Option<String>,
+    /// shared alternate debug file (`.gnu_debugaltlink`)
+    pub alt_debug_file: Option<String>,
+    /// identifier matching the separate debug file (ELF build-id, Mach-O
+    /// `LC_UUID`, PE PDB GUID and age)
+    pub debug_id: Option<String>,
+}
+
+impl DebugInfo {
+    /// whether debug info can be found in a separate file
+    #[must_use]
+    pub fn has_separate_debug(&self) -> bool {
+        self.debug_file.is_some() || self.debug_id.is_some()
+    }
+}
+
+/// whether a section name is a DWARF section
+#[must_use]
+pub fn is_debug_section(name: &str) -> bool {
+    ["debug_", "zdebug_"]
+        .iter()
+        .any(|prefix| name.trim_start_matches(['.', '_']).starts_with(prefix))
+}
+
+/// `.zdebug_*` sections start with `ZLIB` and the big endian uncompressed
+/// size, followed by the zlib stream
+#[must_use]
+pub fn zdebug_data(data: &[u8]) -> Option<&[u8]> {
+    data.strip_prefix(b"ZLIB")?.get(8..)
+}
+
+/// inflate enough of a zlib stream to read a compilation unit header
+#[must_use]
+pub fn inflate_header(data: &[u8]) -> Option<Vec<u8>> {
+    let mut header = vec![0; 16];
+    let mut decoder = ZlibDecoder::new(data);
+    decoder.read_exact(&mut header).ok()?;
+    Some(header)
+}
+
+/// DWARF version from the first compilation unit header of `.debug_info`
+#[must_use]
+pub fn dwarf_version(debug_info: &[u8], little_endian: bool) -> Option<u16> {
+    let u32_at = |offset: usize| -> Option<u32> {
+        let bytes: [u8; 4] =
+            debug_info.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    };
+    // 64-bit DWARF escapes the unit length with 0xffffffff
+    let offset = if u32_at(0)? == 0xffff_ffff { 12 } else { 4 };
+    let bytes: [u8; 2] =
+        debug_info.get(offset..offset + 2)?.try_into().ok()?;
+    let version = if little_endian {
+        u16::from_le_bytes(bytes)
+    } else {
+        u16::from_be_bytes(bytes)
+    };
+    (2..=5).contains(&version).then_some(version)
+}
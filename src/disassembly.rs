@@ -1,5 +1,286 @@
+#[cfg(feature = "color")]
+use colored::Colorize;
 #[cfg(feature = "disassembly")]
-use iced_x86::{Decoder, DecoderOptions, Instruction, Mnemonic};
+use iced_x86::{
+    Decoder, DecoderOptions, FlowControl, Instruction, Mnemonic, OpKind,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::AddAssign;
+
+#[cfg(feature = "disassembly")]
+mod aarch64;
+#[cfg(feature = "disassembly")]
+mod riscv;
+
+#[cfg(feature = "disassembly")]
+const EM_386: u16 = 3;
+#[cfg(feature = "disassembly")]
+const EM_X86_64: u16 = 62;
+#[cfg(feature = "disassembly")]
+const EM_AARCH64: u16 = 183;
+#[cfg(feature = "disassembly")]
+const EM_RISCV: u16 = 243;
+
+#[cfg(feature = "disassembly")]
+const IMAGE_FILE_MACHINE_I386: u16 = 0x014c;
+#[cfg(feature = "disassembly")]
+const IMAGE_FILE_MACHINE_AMD64: u16 = 0x8664;
+#[cfg(feature = "disassembly")]
+const IMAGE_FILE_MACHINE_ARM64: u16 = 0xaa64;
+#[cfg(feature = "disassembly")]
+const IMAGE_FILE_MACHINE_RISCV32: u16 = 0x5032;
+#[cfg(feature = "disassembly")]
+const IMAGE_FILE_MACHINE_RISCV64: u16 = 0x5064;
+
+#[cfg(feature = "disassembly")]
+const CPU_TYPE_X86: u32 = 7;
+#[cfg(feature = "disassembly")]
+const CPU_TYPE_X86_64: u32 = 0x0100_0007;
+#[cfg(feature = "disassembly")]
+const CPU_TYPE_ARM64: u32 = 0x0100_000c;
+
+/// Instruction set of a binary, selects the decoder used for code checks
+#[cfg(feature = "disassembly")]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Architecture {
+    X86,
+    X86_64,
+    AArch64,
+    RiscV32,
+    RiscV64,
+}
+
+#[cfg(feature = "disassembly")]
+impl Architecture {
+    /// from the ELF `e_machine` and class, `None` for unsupported
+    /// architectures
+    #[must_use]
+    pub fn from_elf(e_machine: u16, is_64: bool) -> Option<Self> {
+        match e_machine {
+            EM_386 => Some(Self::X86),
+            EM_X86_64 => Some(Self::X86_64),
+            EM_AARCH64 => Some(Self::AArch64),
+            EM_RISCV if is_64 => Some(Self::RiscV64),
+            EM_RISCV => Some(Self::RiscV32),
+            _ => None,
+        }
+    }
+    /// from the PE COFF header `Machine`, `None` for unsupported
+    /// architectures
+    #[must_use]
+    pub fn from_pe(machine: u16) -> Option<Self> {
+        match machine {
+            IMAGE_FILE_MACHINE_I386 => Some(Self::X86),
+            IMAGE_FILE_MACHINE_AMD64 => Some(Self::X86_64),
+            IMAGE_FILE_MACHINE_ARM64 => Some(Self::AArch64),
+            IMAGE_FILE_MACHINE_RISCV32 => Some(Self::RiscV32),
+            IMAGE_FILE_MACHINE_RISCV64 => Some(Self::RiscV64),
+            _ => None,
+        }
+    }
+    /// from the Mach-O header `cputype`, `None` for unsupported
+    /// architectures
+    #[must_use]
+    pub fn from_macho(cputype: u32) -> Option<Self> {
+        match cputype {
+            CPU_TYPE_X86 => Some(Self::X86),
+            CPU_TYPE_X86_64 => Some(Self::X86_64),
+            CPU_TYPE_ARM64 => Some(Self::AArch64),
+            _ => None,
+        }
+    }
+    /// operating mode for the x86 decoder, `None` for other architectures
+    #[must_use]
+    pub fn bitness(self) -> Option<Bitness> {
+        match self {
+            Self::X86 => Some(Bitness::B32),
+            Self::X86_64 => Some(Bitness::B64),
+            _ => None,
+        }
+    }
+    /// Find how blocks of code, usually one per function, probe the stack.
+    /// `routines` holds addresses of known probe functions such as
+    /// `__chkstk`, x64 MSVC `__chkstk` is also found by its code.
+    #[must_use]
+    pub fn stack_probe(
+        self,
+        blocks: &[(u64, &[u8])],
+        mut routines: Vec<u64>,
+    ) -> StackProbe {
+        let loops = |found: bool| {
+            if found {
+                StackProbe::Loop
+            } else {
+                StackProbe::None
+            }
+        };
+        let bitness = match self {
+            Self::X86 => Bitness::B32,
+            Self::X86_64 => Bitness::B64,
+            Self::AArch64 => {
+                return loops(blocks.iter().any(|&(address, bytes)| {
+                    aarch64::has_stack_clash_protection(bytes, address)
+                }))
+            }
+            Self::RiscV32 | Self::RiscV64 => {
+                return loops(blocks.iter().any(|&(address, bytes)| {
+                    riscv::has_stack_clash_protection(
+                        bytes,
+                        address,
+                        self == Self::RiscV64,
+                    )
+                }))
+            }
+        };
+        // `__chkstk` may follow the calls to it, so call targets are only
+        // matched once all blocks are decoded
+        let mut calls = vec![];
+        let mut inline = StackProbe::None;
+        for &(rip, bytes) in blocks {
+            match x86_stack_probe(
+                bytes,
+                bitness,
+                rip,
+                &mut routines,
+                &mut calls,
+            ) {
+                StackProbe::Loop => return StackProbe::Loop,
+                found => inline = inline.max(found),
+            }
+        }
+        routines.sort_unstable();
+        if inline == StackProbe::None
+            && calls
+                .iter()
+                .any(|target| routines.binary_search(target).is_ok())
+        {
+            return StackProbe::Call;
+        }
+        inline
+    }
+}
+
+// instructions allowed between the stack allocation and the branch back to
+// it in a probe loop
+#[cfg(feature = "disassembly")]
+const PROBE_LOOP_WINDOW: usize = 6;
+
+/// x86 probe intervals, 4 KiB by default, other powers of two with
+/// `-mstack-probe-size`, `/Gs` or GCC's
+/// `--param stack-clash-protection-probe-interval`
+#[cfg(feature = "disassembly")]
+fn is_probe_interval(size: u64) -> bool {
+    size.is_power_of_two() && (0x400..=0x1_0000).contains(&size)
+}
+
+/// stack clash probes touch every page, so the stack grows in page sized
+/// steps
+#[cfg(feature = "disassembly")]
+fn is_page_multiple(size: u64) -> bool {
+    size >= 0x1000 && size.is_multiple_of(0x1000)
+}
+
+/// Tracks a stack allocation, a store to the new top of stack and the branch
+/// back to the allocation, for the fixed-width instruction sets
+#[cfg(feature = "disassembly")]
+#[derive(Default)]
+struct ProbeLoop {
+    allocation: Option<u64>,
+    probed: bool,
+    distance: usize,
+}
+
+#[cfg(feature = "disassembly")]
+impl ProbeLoop {
+    fn allocate(&mut self, address: u64) {
+        *self = Self { allocation: Some(address), ..Self::default() };
+    }
+    fn probe(&mut self) {
+        self.probed = self.allocation.is_some();
+    }
+    fn branch(&self, target: u64) -> bool {
+        self.probed && self.allocation == Some(target)
+    }
+    fn step(&mut self) {
+        self.distance += 1;
+        if self.distance > PROBE_LOOP_WINDOW {
+            *self = Self::default();
+        }
+    }
+}
+
+/// Stack probing mechanism, strongest evidence last
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Deserialize,
+    Serialize,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+)]
+pub enum StackProbe {
+    #[default]
+    None,
+    /// calls to a probe function (`__chkstk`, `__chkstk_ms`,
+    /// `_alloca_probe`, `__rust_probestack`)
+    Call,
+    /// inline probes after each page of a fixed size frame
+    Unrolled,
+    /// inline probe loop for large or dynamic (`alloca`) frames
+    Loop,
+}
+
+impl fmt::Display for StackProbe {
+    #[cfg(not(feature = "color"))]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<8}",
+            match self {
+                Self::None => "None",
+                Self::Call => "Call",
+                Self::Unrolled => "Unrolled",
+                Self::Loop => "Loop",
+            }
+        )
+    }
+    #[cfg(feature = "color")]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<8}",
+            match self {
+                Self::None => "None".red(),
+                Self::Call => "Call".green(),
+                Self::Unrolled => "Unrolled".green(),
+                Self::Loop => "Loop".green(),
+            }
+        )
+    }
+}
+
+// probe functions of the MSVC, MinGW, LLVM, Apple and Rust runtimes, with
+// the leading underscores stripped as their number differs between formats
+static PROBE_ROUTINES: [&str; 7] = [
+    "chkstk",
+    "chkstk_ms",
+    "chkstk_darwin",
+    "alloca_probe",
+    "alloca_probe_8",
+    "alloca_probe_16",
+    "rust_probestack",
+];
+
+/// whether a symbol names a stack probe function
+#[must_use]
+pub fn is_probe_routine(name: &str) -> bool {
+    PROBE_ROUTINES.contains(&name.trim_start_matches('_'))
+}
 
 #[cfg(feature = "disassembly")]
 #[derive(Clone, Copy, PartialEq)]
@@ -20,8 +301,9 @@ impl Bitness {
 }
 
 #[cfg(feature = "disassembly")]
-#[derive(PartialEq)]
+#[derive(Clone, Copy, Default, PartialEq)]
 enum SCPSteps {
+    #[default]
     Init,
     StartCmp,
     StartJump,
@@ -32,158 +314,296 @@ enum SCPSteps {
     EndCmp,
 }
 
+/// Matches the probe loops for dynamic frames one instruction at a time:
+///
+/// ```text
+/// GCC:
+/// 109e:       cmp    rsp,rcx
+/// 10a1:       je     10b8 <main+0x68>
+/// 10a3:       sub    rsp,0x1000
+/// 10aa:       or     QWORD PTR [rsp+0xff8],0x0
+/// 10b3:       cmp    rsp,rcx
+/// 10b6:       jne    10a3 <main+0x53>
+/// 10b8:
+///
+/// 109e:       cmp    rsp,rcx
+/// 10a1:       je     10b5 <main+0x65>
+/// 10a3:       sub    rsp,0x1000
+/// 10aa:       or     QWORD PTR [rsp+0xff8],0x0
+/// 10b3:       jmp    109e <main+0x4e>
+/// 10b5:
+///
+/// Clang:
+/// 118b:       cmp    rbx,rsp
+/// 118e:       jge    11a1 <main+0x61>
+/// 1190:       xor    QWORD PTR [rsp],0x0
+/// 1195:       sub    rsp,0x1000
+/// 119c:       cmp    rbx,rsp
+/// 119f:       jl     1190 <main+0x50>
+/// 11a1:
+///
+/// 1187:       cmp    rbx,rsp
+/// 118a:       jge    119a <main+0x5e>
+/// 118c:       xor    QWORD PTR [rsp],0x0
+/// 1191:       sub    rsp,0x1000
+/// 1198:       jmp    1187 <main+0x4b>
+/// 119a:
+/// ```
 #[cfg(feature = "disassembly")]
-#[allow(clippy::too_many_lines)]
-#[must_use]
-pub fn has_stack_clash_protection(
-    bytes: &[u8],
-    bitness: Bitness,
-    rip: u64,
-) -> bool {
-    let mut decoder =
-        Decoder::with_ip(bitness.as_u32(), bytes, rip, DecoderOptions::NONE);
-
-    let mut instr = Instruction::default();
-
-    let mut step = SCPSteps::Init;
-    let mut start_addr = 0;
-    let mut check_addr = 0;
-    let mut jump_addr = 0;
-
-    while decoder.can_decode() {
-        decoder.decode_out(&mut instr);
-
-        /*
-        GCC:
-        109e:       cmp    rsp,rcx
-        10a1:       je     10b8 <main+0x68>
-        10a3:       sub    rsp,0x1000
-        10aa:       or     QWORD PTR [rsp+0xff8],0x0
-        10b3:       cmp    rsp,rcx
-        10b6:       jne    10a3 <main+0x53>
-        10b8:
-
-        109e:	    cmp    rsp,rcx
-        10a1:   	je     10b5 <main+0x65>
-        10a3:	    sub    rsp,0x1000
-        10aa:	    or     QWORD PTR [rsp+0xff8],0x0
-        10b3:	    jmp    109e <main+0x4e>
-        10b5:
-
-        Clang:
-        118b:       cmp    rbx,rsp
-        118e:       jge    11a1 <main+0x61>
-        1190:       xor    QWORD PTR [rsp],0x0
-        1195:       sub    rsp,0x1000
-        119c:       cmp    rbx,rsp
-        119f:       jl     1190 <main+0x50>
-        11a1:
-
-        1187:	    cmp    rbx,rsp
-        118a:	    jge    119a <main+0x5e>
-        118c:	    xor    QWORD PTR [rsp],0x0
-        1191:	    sub    rsp,0x1000
-        1198:	    jmp    1187 <main+0x4b>
-        119a:
-        */
+#[derive(Default)]
+struct ClashLoop {
+    step: SCPSteps,
+    start_addr: u64,
+    check_addr: u64,
+    jump_addr: u64,
+}
 
+#[cfg(feature = "disassembly")]
+impl ClashLoop {
+    /// whether `instr` closes a probe loop
+    fn next(&mut self, instr: &Instruction, bitness: Bitness) -> bool {
         let mnemonic = instr.mnemonic();
+        let step = self.step;
 
         if step == SCPSteps::Init
             && mnemonic == Mnemonic::Cmp
             && (is_stack_pointer(instr.op0_register(), bitness)
                 || is_stack_pointer(instr.op1_register(), bitness))
         {
-            step = SCPSteps::StartCmp;
-            start_addr = instr.ip();
-            continue;
+            self.step = SCPSteps::StartCmp;
+            self.start_addr = instr.ip();
+            return false;
         } else if step == SCPSteps::StartCmp
             && (mnemonic == Mnemonic::Je || mnemonic == Mnemonic::Jge)
         {
-            step = SCPSteps::StartJump;
-            jump_addr = if bitness == Bitness::B64 {
-                instr.memory_displacement64()
-            } else {
-                u64::from(instr.memory_displacement32())
-            };
-            continue;
+            self.step = SCPSteps::StartJump;
+            self.jump_addr = instr.near_branch_target();
+            return false;
         } else if step == SCPSteps::StartJump
             && mnemonic == Mnemonic::Sub
             && is_stack_pointer(instr.op0_register(), bitness)
-            && instr.immediate(1) == 4096
+            && instr.try_immediate(1).is_ok_and(is_probe_interval)
         {
-            step = SCPSteps::CheckSubFirst;
-            if check_addr == 0 {
-                check_addr = instr.ip();
+            self.step = SCPSteps::CheckSubFirst;
+            if self.check_addr == 0 {
+                self.check_addr = instr.ip();
             }
-            continue;
+            return false;
         } else if step == SCPSteps::CheckSubFirst
             && mnemonic == Mnemonic::Or
             && is_stack_pointer(instr.memory_base(), bitness)
-            && instr.immediate(1) == 0
+            && instr.try_immediate(1).ok() == Some(0)
         {
-            step = SCPSteps::CheckOr;
-            if check_addr == 0 {
-                check_addr = instr.ip();
+            self.step = SCPSteps::CheckOr;
+            if self.check_addr == 0 {
+                self.check_addr = instr.ip();
             }
-            continue;
+            return false;
         } else if step == SCPSteps::StartJump
             && mnemonic == Mnemonic::Xor
             && is_stack_pointer(instr.memory_base(), bitness)
-            && instr.immediate(1) == 0
+            && instr.try_immediate(1).ok() == Some(0)
         {
-            step = SCPSteps::CheckXor;
-            if check_addr == 0 {
-                check_addr = instr.ip();
+            self.step = SCPSteps::CheckXor;
+            if self.check_addr == 0 {
+                self.check_addr = instr.ip();
             }
-            continue;
+            return false;
         } else if step == SCPSteps::CheckXor
             && mnemonic == Mnemonic::Sub
             && is_stack_pointer(instr.op0_register(), bitness)
-            && instr.immediate(1) == 4096
+            && instr.try_immediate(1).is_ok_and(is_probe_interval)
         {
-            step = SCPSteps::CheckSubLast;
-            continue;
+            self.step = SCPSteps::CheckSubLast;
+            return false;
         } else if (step == SCPSteps::CheckOr || step == SCPSteps::CheckSubLast)
             && mnemonic == Mnemonic::Jmp
+            && instr.near_branch_target() == self.start_addr
+            && self.jump_addr == instr.next_ip()
         {
-            let mem_disp = if bitness == Bitness::B64 {
-                instr.memory_displacement64()
-            } else {
-                u64::from(instr.memory_displacement32())
-            };
-            if mem_disp == start_addr && jump_addr == instr.next_ip() {
-                return true;
-            }
+            return true;
         } else if (step == SCPSteps::CheckOr || step == SCPSteps::CheckSubLast)
             && mnemonic == Mnemonic::Cmp
             && (is_stack_pointer(instr.op0_register(), bitness)
                 || is_stack_pointer(instr.op1_register(), bitness))
         {
-            step = SCPSteps::EndCmp;
-            continue;
+            self.step = SCPSteps::EndCmp;
+            return false;
         } else if step == SCPSteps::EndCmp
             && (mnemonic == Mnemonic::Jne || mnemonic == Mnemonic::Jl)
+            && instr.near_branch_target() == self.check_addr
+            && self.jump_addr == instr.next_ip()
         {
-            let mem_disp = if bitness == Bitness::B64 {
-                instr.memory_displacement64()
-            } else {
-                u64::from(instr.memory_displacement32())
-            };
-            if mem_disp == check_addr && jump_addr == instr.next_ip() {
-                return true;
-            }
+            return true;
         }
 
-        step = SCPSteps::Init;
-        start_addr = 0;
-        check_addr = 0;
-        jump_addr = 0;
+        *self = Self::default();
+        false
     }
+}
 
+#[cfg(feature = "disassembly")]
+#[must_use]
+pub fn has_stack_clash_protection(
+    bytes: &[u8],
+    bitness: Bitness,
+    rip: u64,
+) -> bool {
+    let mut decoder =
+        Decoder::with_ip(bitness.as_u32(), bytes, rip, DecoderOptions::NONE);
+    let mut instr = Instruction::default();
+    let mut clash_loop = ClashLoop::default();
+
+    while decoder.can_decode() {
+        decoder.decode_out(&mut instr);
+        if clash_loop.next(&instr, bitness) {
+            return true;
+        }
+    }
     false
 }
 
+/// Matches MSVC `__chkstk` one instruction at a time, it compares against
+/// `StackLimit` from the TEB (`gs:[0x10]` on x64, `fs:[0x8]` on x86) and
+/// touches the pages below it
+#[cfg(feature = "disassembly")]
+#[derive(Default)]
+struct Chkstk {
+    reads_limit: bool,
+    steps_page: bool,
+}
+
+#[cfg(feature = "disassembly")]
+impl Chkstk {
+    fn next(&mut self, instr: &Instruction, bitness: Bitness) {
+        let (segment, stack_limit) = match bitness {
+            Bitness::B64 => (iced_x86::Register::GS, 0x10),
+            Bitness::B32 => (iced_x86::Register::FS, 0x8),
+        };
+        if instr.segment_prefix() == segment
+            && instr.memory_displacement64() == stack_limit
+        {
+            self.reads_limit = true;
+        } else if self.reads_limit
+            && (instr.memory_displacement64() == 0x1000u64.wrapping_neg()
+                || instr.mnemonic() == Mnemonic::Sub
+                    && instr.try_immediate(1).ok() == Some(0x1000))
+        {
+            self.steps_page = true;
+        }
+    }
+    fn found(&self) -> bool {
+        self.reads_limit && self.steps_page
+    }
+}
+
+/// `or [sp+disp], 0`, `xor [sp+disp], 0` or `mov [sp+disp], 0`
+#[cfg(feature = "disassembly")]
+fn is_probe(instr: &Instruction, bitness: Bitness) -> bool {
+    matches!(instr.mnemonic(), Mnemonic::Or | Mnemonic::Xor | Mnemonic::Mov)
+        && instr.op0_kind() == OpKind::Memory
+        && is_stack_pointer(instr.memory_base(), bitness)
+        && instr.try_immediate(1).ok() == Some(0)
+}
+
+/// Find probe loops and unrolled probes in a block of x86/x64 code, in a
+/// single pass that also adds the block to `routines` if it is MSVC
+/// `__chkstk` and the targets of its direct calls to `calls`:
+///
+/// ```text
+/// GCC, fixed frame:                    GCC, unrolled:
+///     lea    r11,[rsp-0x4000]              sub    rsp,0x2000
+///     sub    rsp,0x1000                    or     QWORD PTR [rsp],0x0
+///     or     QWORD PTR [rsp],0x0           sub    rsp,0x2000
+///     cmp    rsp,r11                       or     QWORD PTR [rsp],0x0
+///     jne    <sub>                         sub    rsp,0xe20
+///
+/// MSVC:
+///     mov    eax,0x2010
+///     call   __chkstk
+///     sub    rsp,rax
+/// ```
+///
+/// A single page allocated and then stored to is just as likely an ordinary
+/// frame, probes only count as unrolled when another allocation follows.
+#[cfg(feature = "disassembly")]
+fn x86_stack_probe(
+    bytes: &[u8],
+    bitness: Bitness,
+    rip: u64,
+    routines: &mut Vec<u64>,
+    calls: &mut Vec<u64>,
+) -> StackProbe {
+    let mut decoder =
+        Decoder::with_ip(bitness.as_u32(), bytes, rip, DecoderOptions::NONE);
+    let mut instr = Instruction::default();
+    let mut clash_loop = ClashLoop::default();
+    let mut chkstk = Chkstk::default();
+    let mut result = StackProbe::None;
+    // previous instruction if it allocated a probe interval, with the
+    // interval, or probed
+    let mut last_sub: Option<(u64, u64)> = None;
+    let mut last_probe = None;
+    // first instruction and interval of the last allocate and probe pair,
+    // and the number of instructions since
+    let mut pair: Option<(u64, u64, usize)> = None;
+
+    while decoder.can_decode() {
+        decoder.decode_out(&mut instr);
+        if clash_loop.next(&instr, bitness) {
+            return StackProbe::Loop;
+        }
+        chkstk.next(&instr, bitness);
+        let allocation = if instr.mnemonic() == Mnemonic::Sub
+            && is_stack_pointer(instr.op0_register(), bitness)
+        {
+            instr.try_immediate(1).ok()
+        } else {
+            None
+        };
+        let interval = allocation.filter(|&size| is_probe_interval(size));
+        let probes = is_probe(&instr, bitness);
+        match instr.flow_control() {
+            FlowControl::Call => calls.push(instr.near_branch_target()),
+            FlowControl::ConditionalBranch
+            | FlowControl::UnconditionalBranch => {
+                let target = instr.near_branch_target();
+                if pair.is_some_and(|(start, _, _)| start == target) {
+                    return StackProbe::Loop;
+                }
+            }
+            _ => {}
+        }
+        if let Some((start, pair_interval, distance)) = pair {
+            // the next pair or the residual allocation, of a frame spanning
+            // more than one page
+            if allocation.is_some() && pair_interval >= 0x1000 {
+                result = StackProbe::Unrolled;
+            }
+            pair = (distance < PROBE_LOOP_WINDOW).then_some((
+                start,
+                pair_interval,
+                distance + 1,
+            ));
+        }
+        match (last_sub, last_probe, interval) {
+            (Some((start, interval)), _, _) if probes => {
+                pair = Some((start, interval, 0));
+            }
+            (_, Some(start), Some(interval)) => {
+                pair = Some((start, interval, 0));
+            }
+            _ => {}
+        }
+        last_sub = interval.map(|interval| (instr.ip(), interval));
+        last_probe = probes.then(|| instr.ip());
+    }
+    if chkstk.found() {
+        routines.push(rip);
+    }
+    result
+}
+
 #[cfg(feature = "disassembly")]
 fn is_stack_pointer(reg: iced_x86::Register, bitness: Bitness) -> bool {
     reg == match bitness {
@@ -191,3 +611,343 @@ fn is_stack_pointer(reg: iced_x86::Register, bitness: Bitness) -> bool {
         Bitness::B32 => iced_x86::Register::ESP,
     }
 }
+
+// instructions between the guard compare and the branch to the fail call
+#[cfg(feature = "disassembly")]
+const CANARY_BRANCH_WINDOW: usize = 3;
+
+/// whether an operand reads the stack guard from the TCB, `fs:[0x28]` on
+/// x64 and `gs:[0x14]` on x86
+#[cfg(feature = "disassembly")]
+fn is_stack_guard(instr: &Instruction, bitness: Bitness) -> bool {
+    let (segment, offset) = match bitness {
+        Bitness::B64 => (iced_x86::Register::FS, 0x28),
+        Bitness::B32 => (iced_x86::Register::GS, 0x14),
+    };
+    instr.segment_prefix() == segment
+        && instr.memory_base() == iced_x86::Register::None
+        && instr.memory_displacement64() == offset
+}
+
+/// Find `-fstack-protector` canary checks in blocks of x86/x64 code, for
+/// binaries that do not name `__stack_chk_fail`:
+///
+/// ```text
+///     mov    rax,QWORD PTR fs:0x28
+///     mov    QWORD PTR [rsp+0x18],rax
+///     ...
+///     mov    rax,QWORD PTR [rsp+0x18]
+///     sub    rax,QWORD PTR fs:0x28        (or xor, cmp)
+///     jne    <fail>
+///     ...
+/// fail:
+///     call   __stack_chk_fail
+/// ```
+#[cfg(feature = "disassembly")]
+#[must_use]
+pub fn has_canary_check(blocks: &[(u64, &[u8])], bitness: Bitness) -> bool {
+    let mut instr = Instruction::default();
+    let mut calls = vec![];
+    // addresses the guard compare branches to and falls through to
+    let mut fail_paths = vec![];
+    for &(rip, bytes) in blocks {
+        let mut decoder = Decoder::with_ip(
+            bitness.as_u32(),
+            bytes,
+            rip,
+            DecoderOptions::NONE,
+        );
+        let mut loaded = false;
+        let mut compared: Option<usize> = None;
+        while decoder.can_decode() {
+            decoder.decode_out(&mut instr);
+            let guard = is_stack_guard(&instr, bitness);
+            match instr.mnemonic() {
+                Mnemonic::Mov if guard => loaded = true,
+                Mnemonic::Xor | Mnemonic::Sub | Mnemonic::Cmp
+                    if guard && loaded =>
+                {
+                    compared = Some(0);
+                    continue;
+                }
+                _ => {}
+            }
+            match instr.flow_control() {
+                FlowControl::Call => calls.push(instr.ip()),
+                FlowControl::ConditionalBranch if compared.is_some() => {
+                    fail_paths.push(instr.near_branch_target());
+                    fail_paths.push(instr.next_ip());
+                    compared = None;
+                }
+                _ => {}
+            }
+            compared = compared
+                .map(|distance| distance + 1)
+                .filter(|&distance| distance < CANARY_BRANCH_WINDOW);
+        }
+    }
+    calls.sort_unstable();
+    fail_paths.iter().any(|target| calls.binary_search(target).is_ok())
+}
+
+/// Spectre and straight-line speculation mitigations found in x86/x64 code
+#[derive(Clone, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
+pub struct SpeculationMitigations {
+    /// retpoline thunks (`__x86_indirect_thunk_*`, `__llvm_retpoline_*` or
+    /// their inlined `call; pause; lfence` body)
+    pub retpoline_thunks: usize,
+    /// calls and jumps through retpoline thunks
+    pub retpoline_branches: usize,
+    /// indirect calls and jumps bypassing the thunks, including PLT stubs
+    pub indirect_branches: usize,
+    /// jumps to return thunks (`__x86_return_thunk`)
+    pub return_thunk_branches: usize,
+    /// `lfence` speculation barriers (`/Qspectre`, `-mlvi-hardening`,
+    /// manual speculative load hardening)
+    pub lfences: usize,
+    /// `ret` and indirect `jmp` followed by `int3` (`-mharden-sls`)
+    pub sls_hardened: usize,
+    /// `ret` and indirect `jmp` instructions
+    pub sls_candidates: usize,
+}
+
+impl AddAssign for SpeculationMitigations {
+    fn add_assign(&mut self, other: Self) {
+        self.retpoline_thunks += other.retpoline_thunks;
+        self.retpoline_branches += other.retpoline_branches;
+        self.indirect_branches += other.indirect_branches;
+        self.return_thunk_branches += other.return_thunk_branches;
+        self.lfences += other.lfences;
+        self.sls_hardened += other.sls_hardened;
+        self.sls_candidates += other.sls_candidates;
+    }
+}
+
+impl fmt::Display for SpeculationMitigations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Retpoline: {} Indirect Branches: {} Return Thunks: {} \
+            LFENCE: {} SLS: {}/{}",
+            self.retpoline_branches,
+            self.indirect_branches,
+            self.return_thunk_branches,
+            self.lfences,
+            self.sls_hardened,
+            self.sls_candidates
+        )
+    }
+}
+
+/// Known thunk entry points, usually from the symbol table
+#[derive(Default)]
+pub struct Thunks {
+    /// indirect branch thunks
+    pub indirect: Vec<u64>,
+    /// return thunks
+    pub ret: Vec<u64>,
+}
+
+impl Thunks {
+    /// Sort symbol `(name, address)` pairs into indirect branch and return
+    /// thunks
+    #[must_use]
+    pub fn from_symbols<'a>(
+        symbols: impl IntoIterator<Item = (&'a str, u64)>,
+    ) -> Self {
+        let mut thunks = Self::default();
+        for (name, address) in symbols {
+            if name.starts_with("__x86_return_thunk") {
+                thunks.ret.push(address);
+            } else if name.starts_with("__x86_indirect_thunk")
+                || name.starts_with("__x86_indirect_call_thunk")
+                || name.starts_with("__x86_indirect_jump_thunk")
+                || name.starts_with("__llvm_retpoline_")
+            {
+                thunks.indirect.push(address);
+            }
+        }
+        thunks
+    }
+}
+
+#[cfg(feature = "disassembly")]
+#[derive(Clone, Copy, Default, PartialEq)]
+enum ThunkSteps {
+    #[default]
+    Init,
+    Call,
+    Pause,
+    Lfence,
+    Loop,
+    Capture,
+}
+
+/// Finds retpoline bodies one instruction at a time:
+///
+/// ```text
+///     call  2f
+/// 1:  pause
+///     lfence
+///     jmp   1b
+/// 2:  mov   [rsp], reg      (indirect branch thunk)
+///     lea   rsp, [rsp+8]    (return thunk)
+///     ret
+/// ```
+#[cfg(feature = "disassembly")]
+#[derive(Default)]
+struct ThunkBody {
+    step: ThunkSteps,
+    call_addr: u64,
+    capture_addr: u64,
+    pause_addr: u64,
+    is_return: bool,
+}
+
+#[cfg(feature = "disassembly")]
+impl ThunkBody {
+    /// adds the thunk to `thunks` when `instr` completes its body
+    fn next(
+        &mut self,
+        instr: &Instruction,
+        bitness: Bitness,
+        thunks: &mut Thunks,
+    ) {
+        let mnemonic = instr.mnemonic();
+        self.step = match self.step {
+            _ if mnemonic == Mnemonic::Call
+                && matches!(
+                    instr.op0_kind(),
+                    OpKind::NearBranch32 | OpKind::NearBranch64
+                ) =>
+            {
+                self.call_addr = instr.ip();
+                self.capture_addr = instr.near_branch_target();
+                ThunkSteps::Call
+            }
+            ThunkSteps::Call if mnemonic == Mnemonic::Pause => {
+                self.pause_addr = instr.ip();
+                ThunkSteps::Pause
+            }
+            ThunkSteps::Pause if mnemonic == Mnemonic::Lfence => {
+                ThunkSteps::Lfence
+            }
+            ThunkSteps::Lfence
+                if mnemonic == Mnemonic::Jmp
+                    && instr.near_branch_target() == self.pause_addr =>
+            {
+                ThunkSteps::Loop
+            }
+            // GCC pads the loop before the capture
+            ThunkSteps::Loop if instr.ip() < self.capture_addr => {
+                ThunkSteps::Loop
+            }
+            ThunkSteps::Loop
+                if instr.ip() == self.capture_addr
+                    && is_stack_pointer(instr.memory_base(), bitness)
+                    && (mnemonic == Mnemonic::Mov
+                        && instr.op0_kind() == OpKind::Memory
+                        || mnemonic == Mnemonic::Lea
+                            && is_stack_pointer(
+                                instr.op0_register(),
+                                bitness,
+                            )) =>
+            {
+                self.is_return = mnemonic == Mnemonic::Lea;
+                ThunkSteps::Capture
+            }
+            ThunkSteps::Capture if mnemonic == Mnemonic::Ret => {
+                if self.is_return {
+                    thunks.ret.push(self.call_addr);
+                } else {
+                    thunks.indirect.push(self.call_addr);
+                }
+                ThunkSteps::Init
+            }
+            _ => ThunkSteps::Init,
+        };
+    }
+}
+
+/// Count Spectre v1/v2 and straight-line speculation mitigations in blocks
+/// of x86/x64 code, usually one per function. `thunks` holds thunk
+/// addresses known from symbols, thunk bodies are also found by pattern.
+#[cfg(feature = "disassembly")]
+#[must_use]
+pub fn speculation_mitigations(
+    blocks: &[(u64, &[u8])],
+    bitness: Bitness,
+    mut thunks: Thunks,
+) -> SpeculationMitigations {
+    let mut result = SpeculationMitigations::default();
+    // a thunk body may follow the branches to it, so direct branch targets
+    // are only matched once all blocks are decoded
+    let mut targets = vec![];
+    for &(rip, bytes) in blocks {
+        result += count_speculation_mitigations(
+            bytes,
+            bitness,
+            rip,
+            &mut thunks,
+            &mut targets,
+        );
+    }
+    thunks.indirect.sort_unstable();
+    thunks.indirect.dedup();
+    thunks.ret.sort_unstable();
+    thunks.ret.dedup();
+    result.retpoline_thunks = thunks.indirect.len();
+    for target in targets {
+        if thunks.indirect.binary_search(&target).is_ok() {
+            result.retpoline_branches += 1;
+        } else if thunks.ret.binary_search(&target).is_ok() {
+            result.return_thunk_branches += 1;
+        }
+    }
+    result
+}
+
+/// Count the mitigations of one block, adding the thunk bodies found to
+/// `thunks` and the targets of direct calls and jumps to `targets`
+#[cfg(feature = "disassembly")]
+fn count_speculation_mitigations(
+    bytes: &[u8],
+    bitness: Bitness,
+    rip: u64,
+    thunks: &mut Thunks,
+    targets: &mut Vec<u64>,
+) -> SpeculationMitigations {
+    let mut result = SpeculationMitigations::default();
+    let mut decoder =
+        Decoder::with_ip(bitness.as_u32(), bytes, rip, DecoderOptions::NONE);
+    let mut instr = Instruction::default();
+    let mut body = ThunkBody::default();
+    let mut after_sls_candidate = false;
+
+    while decoder.can_decode() {
+        decoder.decode_out(&mut instr);
+        body.next(&instr, bitness, thunks);
+        if after_sls_candidate && instr.mnemonic() == Mnemonic::Int3 {
+            result.sls_hardened += 1;
+        }
+        after_sls_candidate = false;
+        match instr.flow_control() {
+            FlowControl::Call | FlowControl::UnconditionalBranch => {
+                targets.push(instr.near_branch_target());
+            }
+            FlowControl::IndirectCall => result.indirect_branches += 1,
+            FlowControl::IndirectBranch => {
+                result.indirect_branches += 1;
+                result.sls_candidates += 1;
+                after_sls_candidate = true;
+            }
+            FlowControl::Return => {
+                result.sls_candidates += 1;
+                after_sls_candidate = true;
+            }
+            _ if instr.mnemonic() == Mnemonic::Lfence => result.lfences += 1,
+            _ => {}
+        }
+    }
+    result
+}
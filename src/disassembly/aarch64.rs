@@ -0,0 +1,89 @@
+//! `AArch64` stack probe detection
+//!
+//! Instructions are a fixed 32 bits wide, so the few instructions of a probe
+//! loop are matched on their encoding instead of fully decoding the code.
+
+use super::{is_page_multiple, ProbeLoop};
+
+const SP: u32 = 31;
+
+/// `sub sp, sp, #imm{, lsl #12}` or `sub sp, sp, xN{, uxtx}`, returns the
+/// immediate or 0 for a register operand
+fn stack_allocation(insn: u32) -> Option<u64> {
+    let (rd, rn) = (insn & 0x1f, (insn >> 5) & 0x1f);
+    if rd != SP || rn != SP {
+        return None;
+    }
+    if insn & 0xff80_0000 == 0xd100_0000 {
+        let imm = u64::from((insn >> 10) & 0xfff);
+        return Some(if insn & (1 << 22) == 0 { imm } else { imm << 12 });
+    }
+    // extended register form, the only one that may name sp
+    (insn & 0xffe0_0000 == 0xcb20_0000).then_some(0)
+}
+
+/// `str xzr, [sp{, #imm}]` or `str wzr, [sp{, #imm}]`
+fn is_zero_store_to_sp(insn: u32) -> bool {
+    let (rt, rn) = (insn & 0x1f, (insn >> 5) & 0x1f);
+    rt == 31
+        && rn == SP
+        && matches!(insn & 0xffc0_0000, 0xf900_0000 | 0xb900_0000)
+}
+
+/// target of `b`, `b.cond`, `cbz` and `cbnz`
+fn branch_target(insn: u32, address: u64) -> Option<u64> {
+    let offset = if insn & 0xfc00_0000 == 0x1400_0000 {
+        sign_extend(insn & 0x03ff_ffff, 26)
+    } else if insn & 0xff00_0010 == 0x5400_0000
+        || insn & 0x7e00_0000 == 0x3400_0000
+    {
+        sign_extend((insn >> 5) & 0x7_ffff, 19)
+    } else {
+        return None;
+    };
+    Some(address.wrapping_add_signed(offset * 4))
+}
+
+fn sign_extend(value: u32, bits: u32) -> i64 {
+    let shift = 64 - bits;
+    (i64::from(value) << shift) >> shift
+}
+
+/// Look for a stack probe loop as emitted by GCC and Clang:
+///
+/// ```text
+/// GCC:
+///     sub     sp, sp, #0x10, lsl #12
+///     str     xzr, [sp, #1024]
+///     cmp     sp, x16
+///     b.ne    <loop>
+///
+/// Clang:
+///     sub     sp, sp, #0x1, lsl #12
+///     cmp     sp, x9
+///     b.le    <done>
+///     str     xzr, [sp]
+///     b       <loop>
+/// ```
+pub(super) fn has_stack_clash_protection(bytes: &[u8], address: u64) -> bool {
+    let mut probe_loop = ProbeLoop::default();
+    for (index, word) in bytes.chunks_exact(4).enumerate() {
+        let insn = u32::from_le_bytes([word[0], word[1], word[2], word[3]]);
+        let address = address + 4 * index as u64;
+        if let Some(size) = stack_allocation(insn) {
+            if size == 0 || is_page_multiple(size) {
+                probe_loop.allocate(address);
+                continue;
+            }
+        }
+        if is_zero_store_to_sp(insn) {
+            probe_loop.probe();
+        } else if let Some(target) = branch_target(insn, address) {
+            if probe_loop.branch(target) {
+                return true;
+            }
+        }
+        probe_loop.step();
+    }
+    false
+}
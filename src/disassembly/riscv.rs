@@ -0,0 +1,156 @@
+//! RISC-V stack probe detection
+//!
+//! Instructions are 32 bits wide, or 16 bits with the compressed extension,
+//! so the few instructions of a probe loop are matched on their encoding
+//! instead of fully decoding the code.
+
+use super::{is_page_multiple, ProbeLoop};
+
+const SP: u32 = 2;
+
+enum Insn {
+    /// `sub sp, sp, rs2`, `add sp, sp, rs2` or `addi sp, sp, -imm`, with
+    /// the immediate or 0 for a register operand
+    StackAllocation(u64),
+    /// `sd zero, imm(sp)`, `sw zero, imm(sp)` or their compressed forms
+    ZeroStoreToSp,
+    /// conditional branch or `j`
+    Branch(u64),
+    Other,
+}
+
+fn sign_extend(value: u32, bits: u32) -> i64 {
+    let shift = 64 - bits;
+    (i64::from(value) << shift) >> shift
+}
+
+fn decode32(insn: u32, address: u64) -> Insn {
+    let opcode = insn & 0x7f;
+    let rd = (insn >> 7) & 0x1f;
+    let funct3 = (insn >> 12) & 0x7;
+    let rs1 = (insn >> 15) & 0x1f;
+    let rs2 = (insn >> 20) & 0x1f;
+    let funct7 = insn >> 25;
+    match opcode {
+        // OP: add/sub
+        0x33 if rd == SP
+            && rs1 == SP
+            && funct3 == 0
+            && matches!(funct7, 0 | 0x20) =>
+        {
+            Insn::StackAllocation(0)
+        }
+        // OP-IMM: addi
+        0x13 if rd == SP && rs1 == SP && funct3 == 0 => {
+            let imm = sign_extend(insn >> 20, 12);
+            if imm < 0 {
+                Insn::StackAllocation(imm.unsigned_abs())
+            } else {
+                Insn::Other
+            }
+        }
+        // STORE: sw/sd
+        0x23 if rs1 == SP && rs2 == 0 && matches!(funct3, 2 | 3) => {
+            Insn::ZeroStoreToSp
+        }
+        // BRANCH
+        0x63 => {
+            let imm = ((insn >> 31) & 1) << 12
+                | ((insn >> 7) & 1) << 11
+                | ((insn >> 25) & 0x3f) << 5
+                | ((insn >> 8) & 0xf) << 1;
+            Insn::Branch(address.wrapping_add_signed(sign_extend(imm, 13)))
+        }
+        // JAL x0, i.e. `j`
+        0x6f if rd == 0 => {
+            let imm = ((insn >> 31) & 1) << 20
+                | ((insn >> 12) & 0xff) << 12
+                | ((insn >> 20) & 1) << 11
+                | ((insn >> 21) & 0x3ff) << 1;
+            Insn::Branch(address.wrapping_add_signed(sign_extend(imm, 21)))
+        }
+        _ => Insn::Other,
+    }
+}
+
+fn decode16(insn: u16, address: u64, is_64: bool) -> Insn {
+    let insn = u32::from(insn);
+    let quadrant = insn & 0x3;
+    let funct3 = insn >> 13;
+    let rs2 = (insn >> 2) & 0x1f;
+    match (quadrant, funct3) {
+        // c.sdsp (RV64) and c.swsp with rs2 = zero
+        (2, 7) if is_64 && rs2 == 0 => Insn::ZeroStoreToSp,
+        (2, 6) if rs2 == 0 => Insn::ZeroStoreToSp,
+        // c.addi16sp
+        (1, 3) if (insn >> 7) & 0x1f == SP => {
+            let imm = ((insn >> 12) & 1) << 9
+                | ((insn >> 6) & 1) << 4
+                | ((insn >> 5) & 1) << 6
+                | ((insn >> 3) & 3) << 7
+                | ((insn >> 2) & 1) << 5;
+            let imm = sign_extend(imm, 10);
+            if imm < 0 {
+                Insn::StackAllocation(imm.unsigned_abs())
+            } else {
+                Insn::Other
+            }
+        }
+        // c.j
+        (1, 5) => {
+            let imm = ((insn >> 12) & 1) << 11
+                | ((insn >> 11) & 1) << 4
+                | ((insn >> 9) & 3) << 8
+                | ((insn >> 8) & 1) << 10
+                | ((insn >> 7) & 1) << 6
+                | ((insn >> 6) & 1) << 7
+                | ((insn >> 3) & 7) << 1
+                | ((insn >> 2) & 1) << 5;
+            Insn::Branch(address.wrapping_add_signed(sign_extend(imm, 12)))
+        }
+        _ => Insn::Other,
+    }
+}
+
+/// Look for a stack probe loop as emitted by GCC:
+///
+/// ```text
+///     sub     sp, sp, t0
+///     sd      zero, 0(sp)
+///     bne     sp, t1, <loop>
+/// ```
+pub(super) fn has_stack_clash_protection(
+    bytes: &[u8],
+    address: u64,
+    is_64: bool,
+) -> bool {
+    let mut probe_loop = ProbeLoop::default();
+    let mut offset = 0;
+    while let Some(&[low, high]) = bytes.get(offset..offset + 2) {
+        let address = address + offset as u64;
+        let half = u16::from_le_bytes([low, high]);
+        let insn = if half & 0x3 == 0x3 {
+            let Some(&[b2, b3]) = bytes.get(offset + 2..offset + 4) else {
+                break;
+            };
+            offset += 4;
+            decode32(u32::from_le_bytes([low, high, b2, b3]), address)
+        } else {
+            offset += 2;
+            decode16(half, address, is_64)
+        };
+        match insn {
+            Insn::StackAllocation(size)
+                if size == 0 || is_page_multiple(size) =>
+            {
+                probe_loop.allocate(address);
+                continue;
+            }
+            Insn::ZeroStoreToSp => probe_loop.probe(),
+            Insn::Branch(target) if probe_loop.branch(target) => return true,
+            _ => {}
+        }
+        probe_loop.step();
+    }
+    false
+}
@@ -0,0 +1,645 @@
+//! Implements checksec for .NET assemblies hosted in PE32/32+ binaries
+#[cfg(feature = "color")]
+use colored::Colorize;
+use goblin::pe::utils::find_offset;
+use goblin::pe::{options::ParseOptions, PE};
+use scroll::Pread;
+use scroll_derive::Pread;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[cfg(feature = "color")]
+use crate::colorize_bool;
+use crate::pe::Properties;
+
+const COMIMAGE_FLAGS_ILONLY: u32 = 0x0000_0001;
+const COMIMAGE_FLAGS_32BITREQUIRED: u32 = 0x0000_0002;
+const COMIMAGE_FLAGS_STRONGNAMESIGNED: u32 = 0x0000_0008;
+const COMIMAGE_FLAGS_32BITPREFERRED: u32 = 0x0002_0000;
+
+// "BSJB"
+const METADATA_SIGNATURE: u32 = 0x424A_5342;
+// "RTR"
+const READYTORUN_SIGNATURE: u32 = 0x0052_5452;
+
+// `#~` heap size flags
+const HEAP_STRING_4: u8 = 0x01;
+const HEAP_GUID_4: u8 = 0x02;
+const HEAP_BLOB_4: u8 = 0x04;
+
+// metadata table ids (ECMA-335 II.22)
+const TABLE_TYPE_REF: usize = 0x01;
+const TABLE_MEMBER_REF: usize = 0x0A;
+const TABLE_CUSTOM_ATTRIBUTE: usize = 0x0C;
+const TABLE_ASSEMBLY: usize = 0x20;
+// placeholder for unused coded index tags
+const TABLE_UNUSED: usize = usize::MAX;
+
+// `HasCustomAttribute` tag for the `Assembly` table
+const HAS_CUSTOM_ATTRIBUTE_ASSEMBLY: u32 = 14;
+// `CustomAttributeType` tag for the `MemberRef` table
+const CUSTOM_ATTRIBUTE_TYPE_MEMBER_REF: u32 = 3;
+// `MemberRefParent` tag for the `TypeRef` table
+const MEMBER_REF_PARENT_TYPE_REF: u32 = 1;
+
+/// `IMAGE_DATA_DIRECTORY`
+#[derive(Debug, Copy, Clone, Default, Pread)]
+struct ImageDataDirectory {
+    virtual_address: u32,
+    size: u32,
+}
+
+/// [`IMAGE_COR20_HEADER`](https://learn.microsoft.com/en-us/dotnet/framework/unmanaged-api/metadata/image-cor20-header-structure)
+#[allow(dead_code)]
+#[derive(Debug, Copy, Clone, Default, Pread)]
+struct ImageCor20Header {
+    cb: u32,
+    major_runtime_version: u16,
+    minor_runtime_version: u16,
+    metadata: ImageDataDirectory,
+    flags: u32,
+    entry_point_token: u32,
+    resources: ImageDataDirectory,
+    strong_name_signature: ImageDataDirectory,
+    code_manager_table: ImageDataDirectory,
+    vtable_fixups: ImageDataDirectory,
+    export_address_table_jumps: ImageDataDirectory,
+    managed_native_header: ImageDataDirectory,
+}
+
+/// Strong name signature state: `None`, `DelaySigned`, `Present` or
+/// `Invalid`
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
+pub enum StrongName {
+    None,
+    DelaySigned,
+    /// a well-formed signature, which is not cryptographically verified
+    Present,
+    Invalid,
+}
+impl fmt::Display for StrongName {
+    #[cfg(not(feature = "color"))]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<11}",
+            match *self {
+                Self::None => "None",
+                Self::DelaySigned => "DelaySigned",
+                Self::Present => "Present",
+                Self::Invalid => "Invalid",
+            }
+        )
+    }
+    #[cfg(feature = "color")]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<11}",
+            match *self {
+                Self::None => "None".red(),
+                Self::DelaySigned => "DelaySigned".yellow(),
+                Self::Present => "Present".yellow(),
+                Self::Invalid => "Invalid".red(),
+            }
+        )
+    }
+}
+
+/// Checksec result struct for the managed part of .NET assemblies
+///
+/// **Example**
+///
+/// ```rust
+/// use checksec::dotnet::CheckSecResults;
+/// use goblin::pe::PE;
+/// use std::fs;
+///
+/// pub fn print_results(binary: &String) {
+///     if let Ok(buf) = fs::read(binary) {
+///         if let Ok(pe) = PE::parse(&buf) {
+///             println!("{:#?}", CheckSecResults::parse(&pe, &buf));
+///         }
+///     }
+/// }
+/// ```
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
+pub struct CheckSecResults {
+    /// Metadata runtime version string (e.g. `v4.0.30319`)
+    pub runtime_version: String,
+    /// Target framework from the assembly `TargetFrameworkAttribute`
+    pub target_framework: Option<String>,
+    /// IL only (`COMIMAGE_FLAGS_ILONLY`), otherwise mixed-mode
+    pub il_only: bool,
+    /// 32-bit required (`COMIMAGE_FLAGS_32BITREQUIRED`)
+    pub required_32bit: bool,
+    /// 32-bit preferred (`COMIMAGE_FLAGS_32BITPREFERRED`)
+    pub preferred_32bit: bool,
+    /// Strong name signature
+    pub strong_name: StrongName,
+    /// `ReadyToRun` precompiled native code
+    pub ready_to_run: bool,
+    /// Native code of mixed-mode assemblies is built with NX and ASLR
+    /// *(always true for IL only assemblies)*
+    pub native_code_hardened: bool,
+}
+impl CheckSecResults {
+    /// Parse the CLI header and metadata of a .NET assembly, returns `None`
+    /// if the PE has no (valid) CLR runtime header
+    #[must_use]
+    pub fn parse(pe: &PE, bytes: &[u8]) -> Option<Self> {
+        let optional_header = pe.header.optional_header?;
+        let file_alignment = optional_header.windows_fields.file_alignment;
+        let to_offset = |rva: u32| {
+            find_offset(
+                rva as usize,
+                &pe.sections,
+                file_alignment,
+                &ParseOptions::default(),
+            )
+        };
+        let clr_dir =
+            optional_header.data_directories.get_clr_runtime_header()?;
+        let cor20: ImageCor20Header =
+            bytes.pread(to_offset(clr_dir.virtual_address)?).ok()?;
+        let metadata = bytes
+            .get(to_offset(cor20.metadata.virtual_address)?..)
+            .and_then(|md| md.get(..cor20.metadata.size as usize))
+            .and_then(Metadata::parse)?;
+
+        let il_only = cor20.flags & COMIMAGE_FLAGS_ILONLY != 0;
+        let ready_to_run = cor20.managed_native_header.virtual_address != 0
+            && to_offset(cor20.managed_native_header.virtual_address)
+                .and_then(|offset| bytes.pread::<u32>(offset).ok())
+                == Some(READYTORUN_SIGNATURE);
+        let signature = if cor20.strong_name_signature.size == 0 {
+            None
+        } else {
+            to_offset(cor20.strong_name_signature.virtual_address).and_then(
+                |offset| {
+                    bytes.get(
+                        offset
+                            ..offset
+                                + cor20.strong_name_signature.size as usize,
+                    )
+                },
+            )
+        };
+        let strong_name = strong_name(
+            metadata.assembly_public_key(),
+            signature,
+            cor20.flags & COMIMAGE_FLAGS_STRONGNAMESIGNED != 0,
+        );
+        let native_code_hardened = il_only
+            || (pe.has_nx()
+                && pe.has_dynamic_base()
+                && (!pe.is_64 || pe.has_high_entropy_va()));
+
+        Some(Self {
+            target_framework: metadata.target_framework(),
+            runtime_version: metadata.version,
+            il_only,
+            required_32bit: cor20.flags & COMIMAGE_FLAGS_32BITREQUIRED != 0,
+            preferred_32bit: cor20.flags & COMIMAGE_FLAGS_32BITPREFERRED != 0,
+            strong_name,
+            ready_to_run,
+            native_code_hardened,
+        })
+    }
+}
+
+impl fmt::Display for CheckSecResults {
+    #[cfg(not(feature = "color"))]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Runtime: {} Framework: {} IL Only: {} 32BIT Required: {} \
+            32BIT Preferred: {} Strong Name: {} ReadyToRun: {} \
+            Native Hardened: {}",
+            self.runtime_version,
+            self.target_framework.as_deref().unwrap_or("None"),
+            self.il_only,
+            self.required_32bit,
+            self.preferred_32bit,
+            self.strong_name,
+            self.ready_to_run,
+            self.native_code_hardened
+        )
+    }
+    #[cfg(feature = "color")]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {}",
+            "Runtime:".bold(),
+            self.runtime_version,
+            "Framework:".bold(),
+            self.target_framework.as_deref().unwrap_or("None"),
+            "IL Only:".bold(),
+            colorize_bool!(self.il_only),
+            "32BIT Required:".bold(),
+            self.required_32bit,
+            "32BIT Preferred:".bold(),
+            self.preferred_32bit,
+            "Strong Name:".bold(),
+            self.strong_name,
+            "ReadyToRun:".bold(),
+            self.ready_to_run,
+            "Native Hardened:".bold(),
+            colorize_bool!(self.native_code_hardened)
+        )
+    }
+}
+
+/// Structural strong name check: the signature has to be present, non-zero
+/// and sized for the RSA modulus of the assembly public key. The signature
+/// itself is not cryptographically verified.
+fn strong_name(
+    public_key: Option<&[u8]>,
+    signature: Option<&[u8]>,
+    signed_flag: bool,
+) -> StrongName {
+    let public_key = match public_key {
+        Some(key) if !key.is_empty() => key,
+        _ if signature.is_some() => return StrongName::Invalid,
+        _ => return StrongName::None,
+    };
+    let Some(signature) = signature else {
+        return StrongName::Invalid;
+    };
+    if !signed_flag || signature.iter().all(|b| *b == 0) {
+        return StrongName::DelaySigned;
+    }
+    // PublicKeyBlob: SigAlgID, HashAlgID, cbPublicKey, followed by a
+    // PUBLICKEYBLOB (BLOBHEADER, RSAPUBKEY { magic, bitlen, pubexp }, ...)
+    match public_key.pread::<u32>(12 + 8 + 4) {
+        Ok(bitlen) if bitlen as usize / 8 == signature.len() => {
+            StrongName::Present
+        }
+        // the ECMA standard key carries no modulus
+        _ if public_key.len() == 16 => StrongName::Present,
+        _ => StrongName::Invalid,
+    }
+}
+
+/// Read an ECMA-335 compressed unsigned integer, returns the value and the
+/// number of bytes consumed
+fn compressed_u32(bytes: &[u8]) -> Option<(u32, usize)> {
+    let b0 = u32::from(*bytes.first()?);
+    if b0 & 0x80 == 0 {
+        Some((b0, 1))
+    } else if b0 & 0xC0 == 0x80 {
+        Some((((b0 & 0x3F) << 8) | u32::from(*bytes.get(1)?), 2))
+    } else if b0 & 0xE0 == 0xC0 {
+        let rest = bytes.get(1..4)?;
+        Some((
+            ((b0 & 0x1F) << 24)
+                | (u32::from(rest[0]) << 16)
+                | (u32::from(rest[1]) << 8)
+                | u32::from(rest[2]),
+            4,
+        ))
+    } else {
+        None
+    }
+}
+
+/// Metadata table column types
+#[derive(Clone, Copy)]
+enum Col {
+    U16,
+    U32,
+    Str,
+    Guid,
+    Blob,
+    Table(usize),
+    Coded(&'static [usize]),
+}
+
+const TYPE_DEF_OR_REF: &[usize] = &[0x02, 0x01, 0x1B];
+const HAS_CONSTANT: &[usize] = &[0x04, 0x08, 0x17];
+const HAS_CUSTOM_ATTRIBUTE: &[usize] = &[
+    0x06, 0x04, 0x01, 0x02, 0x08, 0x09, 0x0A, 0x00, 0x0E, 0x17, 0x14, 0x11,
+    0x1A, 0x1B, 0x20, 0x23, 0x26, 0x27, 0x28, 0x2A, 0x2C, 0x2B,
+];
+const HAS_FIELD_MARSHAL: &[usize] = &[0x04, 0x08];
+const HAS_DECL_SECURITY: &[usize] = &[0x02, 0x06, 0x20];
+const MEMBER_REF_PARENT: &[usize] = &[0x02, 0x01, 0x1A, 0x06, 0x1B];
+const HAS_SEMANTICS: &[usize] = &[0x14, 0x17];
+const METHOD_DEF_OR_REF: &[usize] = &[0x06, 0x0A];
+const MEMBER_FORWARDED: &[usize] = &[0x04, 0x06];
+const IMPLEMENTATION: &[usize] = &[0x26, 0x23, 0x27];
+const CUSTOM_ATTRIBUTE_TYPE: &[usize] =
+    &[TABLE_UNUSED, TABLE_UNUSED, 0x06, 0x0A, TABLE_UNUSED];
+const RESOLUTION_SCOPE: &[usize] = &[0x00, 0x1A, 0x23, 0x01];
+const TYPE_OR_METHOD_DEF: &[usize] = &[0x02, 0x06];
+
+/// Column layout of the metadata tables `0x00..=0x2C` (ECMA-335 II.22)
+const SCHEMA: [&[Col]; 0x2D] = [
+    // Module
+    &[Col::U16, Col::Str, Col::Guid, Col::Guid, Col::Guid],
+    // TypeRef
+    &[Col::Coded(RESOLUTION_SCOPE), Col::Str, Col::Str],
+    // TypeDef
+    &[
+        Col::U32,
+        Col::Str,
+        Col::Str,
+        Col::Coded(TYPE_DEF_OR_REF),
+        Col::Table(0x04),
+        Col::Table(0x06),
+    ],
+    // FieldPtr
+    &[Col::Table(0x04)],
+    // Field
+    &[Col::U16, Col::Str, Col::Blob],
+    // MethodPtr
+    &[Col::Table(0x06)],
+    // MethodDef
+    &[Col::U32, Col::U16, Col::U16, Col::Str, Col::Blob, Col::Table(0x08)],
+    // ParamPtr
+    &[Col::Table(0x08)],
+    // Param
+    &[Col::U16, Col::U16, Col::Str],
+    // InterfaceImpl
+    &[Col::Table(0x02), Col::Coded(TYPE_DEF_OR_REF)],
+    // MemberRef
+    &[Col::Coded(MEMBER_REF_PARENT), Col::Str, Col::Blob],
+    // Constant
+    &[Col::U16, Col::Coded(HAS_CONSTANT), Col::Blob],
+    // CustomAttribute
+    &[
+        Col::Coded(HAS_CUSTOM_ATTRIBUTE),
+        Col::Coded(CUSTOM_ATTRIBUTE_TYPE),
+        Col::Blob,
+    ],
+    // FieldMarshal
+    &[Col::Coded(HAS_FIELD_MARSHAL), Col::Blob],
+    // DeclSecurity
+    &[Col::U16, Col::Coded(HAS_DECL_SECURITY), Col::Blob],
+    // ClassLayout
+    &[Col::U16, Col::U32, Col::Table(0x02)],
+    // FieldLayout
+    &[Col::U32, Col::Table(0x04)],
+    // StandAloneSig
+    &[Col::Blob],
+    // EventMap
+    &[Col::Table(0x02), Col::Table(0x14)],
+    // EventPtr
+    &[Col::Table(0x14)],
+    // Event
+    &[Col::U16, Col::Str, Col::Coded(TYPE_DEF_OR_REF)],
+    // PropertyMap
+    &[Col::Table(0x02), Col::Table(0x17)],
+    // PropertyPtr
+    &[Col::Table(0x17)],
+    // Property
+    &[Col::U16, Col::Str, Col::Blob],
+    // MethodSemantics
+    &[Col::U16, Col::Table(0x06), Col::Coded(HAS_SEMANTICS)],
+    // MethodImpl
+    &[
+        Col::Table(0x02),
+        Col::Coded(METHOD_DEF_OR_REF),
+        Col::Coded(METHOD_DEF_OR_REF),
+    ],
+    // ModuleRef
+    &[Col::Str],
+    // TypeSpec
+    &[Col::Blob],
+    // ImplMap
+    &[Col::U16, Col::Coded(MEMBER_FORWARDED), Col::Str, Col::Table(0x1A)],
+    // FieldRVA
+    &[Col::U32, Col::Table(0x04)],
+    // EncLog
+    &[Col::U32, Col::U32],
+    // EncMap
+    &[Col::U32],
+    // Assembly
+    &[
+        Col::U32,
+        Col::U16,
+        Col::U16,
+        Col::U16,
+        Col::U16,
+        Col::U32,
+        Col::Blob,
+        Col::Str,
+        Col::Str,
+    ],
+    // AssemblyProcessor
+    &[Col::U32],
+    // AssemblyOS
+    &[Col::U32, Col::U32, Col::U32],
+    // AssemblyRef
+    &[
+        Col::U16,
+        Col::U16,
+        Col::U16,
+        Col::U16,
+        Col::U32,
+        Col::Blob,
+        Col::Str,
+        Col::Str,
+        Col::Blob,
+    ],
+    // AssemblyRefProcessor
+    &[Col::U32, Col::Table(0x23)],
+    // AssemblyRefOS
+    &[Col::U32, Col::U32, Col::U32, Col::Table(0x23)],
+    // File
+    &[Col::U32, Col::Str, Col::Blob],
+    // ExportedType
+    &[Col::U32, Col::U32, Col::Str, Col::Str, Col::Coded(IMPLEMENTATION)],
+    // ManifestResource
+    &[Col::U32, Col::U32, Col::Str, Col::Coded(IMPLEMENTATION)],
+    // NestedClass
+    &[Col::Table(0x02), Col::Table(0x02)],
+    // GenericParam
+    &[Col::U16, Col::U16, Col::Coded(TYPE_OR_METHOD_DEF), Col::Str],
+    // MethodSpec
+    &[Col::Coded(METHOD_DEF_OR_REF), Col::Blob],
+    // GenericParamConstraint
+    &[Col::Table(0x2A), Col::Coded(TYPE_DEF_OR_REF)],
+];
+
+/// Parsed metadata root with the heaps and tables needed for the checks
+struct Metadata<'a> {
+    version: String,
+    strings: &'a [u8],
+    blobs: &'a [u8],
+    tables: Option<Tables<'a>>,
+}
+
+impl<'a> Metadata<'a> {
+    fn parse(bytes: &'a [u8]) -> Option<Self> {
+        if bytes.pread::<u32>(0).ok()? != METADATA_SIGNATURE {
+            return None;
+        }
+        let version_len = bytes.pread::<u32>(12).ok()? as usize;
+        let version = bytes.get(16..16 + version_len)?;
+        let version = String::from_utf8_lossy(
+            version.split(|b| *b == 0).next().unwrap_or_default(),
+        )
+        .into_owned();
+        let mut offset = 16 + version_len + 2; // flags
+        let streams: u16 = bytes.gread(&mut offset).ok()?;
+
+        let mut metadata =
+            Self { version, strings: &[], blobs: &[], tables: None };
+        let mut table_stream = None;
+        for _ in 0..streams {
+            let stream_offset = bytes.gread::<u32>(&mut offset).ok()? as usize;
+            let stream_size = bytes.gread::<u32>(&mut offset).ok()? as usize;
+            let name_len =
+                bytes.get(offset..)?.iter().position(|b| *b == 0)?;
+            let name = bytes.get(offset..offset + name_len)?;
+            // name is null terminated and padded to 4 bytes
+            offset += (name_len + 4) & !3;
+            let data =
+                bytes.get(stream_offset..stream_offset + stream_size)?;
+            match name {
+                b"#~" | b"#-" => table_stream = Some(data),
+                b"#Strings" => metadata.strings = data,
+                b"#Blob" => metadata.blobs = data,
+                _ => (),
+            }
+        }
+        metadata.tables = table_stream.and_then(Tables::parse);
+        Some(metadata)
+    }
+    fn string(&self, idx: u32) -> Option<&'a str> {
+        let bytes = self.strings.get(idx as usize..)?;
+        let len = bytes.iter().position(|b| *b == 0)?;
+        std::str::from_utf8(&bytes[..len]).ok()
+    }
+    fn blob(&self, idx: u32) -> Option<&'a [u8]> {
+        let bytes = self.blobs.get(idx as usize..)?;
+        let (len, consumed) = compressed_u32(bytes)?;
+        bytes.get(consumed..consumed + len as usize)
+    }
+    /// `PublicKey` blob of the `Assembly` table row
+    fn assembly_public_key(&self) -> Option<&'a [u8]> {
+        let row = self.tables.as_ref()?.row(TABLE_ASSEMBLY, 1)?;
+        self.blob(*row.get(6)?)
+    }
+    /// decode the `TargetFrameworkAttribute` applied to the assembly
+    fn target_framework(&self) -> Option<String> {
+        let tables = self.tables.as_ref()?;
+        let tag_bits = |tables: &[usize]| coded_tag_bits(tables.len());
+        for idx in 1..=tables.rows(TABLE_CUSTOM_ATTRIBUTE) {
+            let row = tables.row(TABLE_CUSTOM_ATTRIBUTE, idx)?;
+            let parent_bits = tag_bits(HAS_CUSTOM_ATTRIBUTE);
+            let type_bits = tag_bits(CUSTOM_ATTRIBUTE_TYPE);
+            if row[0] & ((1 << parent_bits) - 1)
+                != HAS_CUSTOM_ATTRIBUTE_ASSEMBLY
+                || row[1] & ((1 << type_bits) - 1)
+                    != CUSTOM_ATTRIBUTE_TYPE_MEMBER_REF
+            {
+                continue;
+            }
+            let member_ref =
+                tables.row(TABLE_MEMBER_REF, row[1] >> type_bits)?;
+            let class_bits = tag_bits(MEMBER_REF_PARENT);
+            if member_ref[0] & ((1 << class_bits) - 1)
+                != MEMBER_REF_PARENT_TYPE_REF
+            {
+                continue;
+            }
+            let type_ref =
+                tables.row(TABLE_TYPE_REF, member_ref[0] >> class_bits)?;
+            if self.string(type_ref[1]) != Some("TargetFrameworkAttribute")
+                || self.string(type_ref[2])
+                    != Some("System.Runtime.Versioning")
+            {
+                continue;
+            }
+            // prolog 0x0001 followed by a SerString
+            let value = self.blob(row[2])?;
+            let (len, consumed) = compressed_u32(value.get(2..)?)?;
+            let name = value.get(2 + consumed..2 + consumed + len as usize)?;
+            return Some(String::from_utf8_lossy(name).into_owned());
+        }
+        None
+    }
+}
+
+/// number of tag bits used by a coded index over `tables` tables
+fn coded_tag_bits(tables: usize) -> u32 {
+    usize::BITS - (tables - 1).leading_zeros()
+}
+
+/// `#~` metadata tables stream
+struct Tables<'a> {
+    data: &'a [u8],
+    heap_sizes: u8,
+    rows: [u32; 64],
+    offsets: [usize; 64],
+}
+
+impl<'a> Tables<'a> {
+    fn parse(bytes: &'a [u8]) -> Option<Self> {
+        let heap_sizes: u8 = bytes.pread(6).ok()?;
+        let valid: u64 = bytes.pread(8).ok()?;
+        let mut offset = 24;
+        let mut tables =
+            Self { data: bytes, heap_sizes, rows: [0; 64], offsets: [0; 64] };
+        for table in 0..64 {
+            if valid & (1 << table) != 0 {
+                tables.rows[table] = bytes.gread(&mut offset).ok()?;
+            }
+        }
+        for table in 0..SCHEMA.len() {
+            tables.offsets[table] = offset;
+            offset += tables.row_size(table) * tables.rows[table] as usize;
+        }
+        Some(tables)
+    }
+    fn rows(&self, table: usize) -> u32 {
+        self.rows.get(table).copied().unwrap_or(0)
+    }
+    fn col_size(&self, col: Col) -> usize {
+        match col {
+            Col::U32 => 4,
+            Col::Str if self.heap_sizes & HEAP_STRING_4 != 0 => 4,
+            Col::Guid if self.heap_sizes & HEAP_GUID_4 != 0 => 4,
+            Col::Blob if self.heap_sizes & HEAP_BLOB_4 != 0 => 4,
+            Col::U16 | Col::Str | Col::Guid | Col::Blob => 2,
+            Col::Table(table) => {
+                if self.rows(table) < 1 << 16 {
+                    2
+                } else {
+                    4
+                }
+            }
+            Col::Coded(tables) => {
+                let max_rows =
+                    tables.iter().map(|t| self.rows(*t)).max().unwrap_or(0);
+                if max_rows < 1 << (16 - coded_tag_bits(tables.len())) {
+                    2
+                } else {
+                    4
+                }
+            }
+        }
+    }
+    fn row_size(&self, table: usize) -> usize {
+        SCHEMA[table].iter().map(|col| self.col_size(*col)).sum()
+    }
+    /// read all columns of the 1-based row `idx` of `table`
+    fn row(&self, table: usize, idx: u32) -> Option<Vec<u32>> {
+        if idx == 0 || idx > self.rows(table) {
+            return None;
+        }
+        let mut offset =
+            self.offsets[table] + self.row_size(table) * (idx as usize - 1);
+        SCHEMA[table]
+            .iter()
+            .map(|col| match self.col_size(*col) {
+                2 => self.data.gread::<u16>(&mut offset).ok().map(u32::from),
+                _ => self.data.gread::<u32>(&mut offset).ok(),
+            })
+            .collect()
+    }
+}
@@ -4,27 +4,42 @@ use colored::Colorize;
 #[cfg(target_os = "linux")]
 use either::Either;
 use std::ops::Deref;
+use goblin::container::Ctx;
 use goblin::elf::dynamic::{
-    DF_1_NOW, DF_1_PIE, DF_BIND_NOW, DT_RPATH, DT_RUNPATH,
+    DF_1_NOW, DF_1_PIE, DF_BIND_NOW, DT_GNU_HASH, DT_HASH, DT_JMPREL,
+    DT_PLTGOT, DT_REL, DT_RELA, DT_RPATH, DT_RUNPATH, DT_STRTAB, DT_SYMTAB,
+    DT_VERSYM,
 };
 use goblin::elf::header::{ET_DYN, ET_REL};
-use goblin::elf::program_header::{PT_GNU_RELRO, PT_GNU_STACK, PF_X};
+use goblin::elf::program_header::{
+    ProgramHeader, PT_DYNAMIC, PT_GNU_RELRO, PT_GNU_STACK, PT_LOAD, PF_X,
+};
+use goblin::elf::section_header::SHF_COMPRESSED;
 #[cfg(feature = "disassembly")]
 use goblin::elf::section_header::{SHF_ALLOC, SHF_EXECINSTR, SHT_PROGBITS};
 use goblin::elf::Elf;
+use scroll::{Endian, Pread, Pwrite};
 use serde_derive::{Deserialize, Serialize};
-#[cfg(feature = "disassembly")]
 use std::convert::TryFrom;
 use std::fmt;
+use std::io;
 #[cfg(target_os = "linux")]
 use std::path::{Path, PathBuf};
 
 #[cfg(feature = "color")]
 use crate::colorize_bool;
 #[cfg(feature = "disassembly")]
-use crate::disassembly::{has_stack_clash_protection, Bitness};
+use crate::disassembly::{speculation_mitigations, Architecture, Thunks};
+use crate::disassembly::{self, SpeculationMitigations, StackProbe};
+#[cfg(feature = "disassembly")]
+use crate::functions::Functions;
 #[cfg(target_os = "linux")]
 use crate::ldso::{LdSoError, LdSoLookup};
+use crate::debuginfo::{self, DebugInfo, Stripped};
+use crate::kmod::KernelModule;
+use crate::language::{self, GoBuildInfo, Language};
+use crate::provenance::{self, Annobin, PackageNote, StackProtector};
+use crate::sanitizer::VecSanitizer;
 use crate::shared::{Rpath, VecRpath};
 
 static STC_CANARY_KWDS: [&str; 3] = ["__stack_chk_fail", "__stack_chk_guard", "__intel_security_cookie"];
@@ -204,6 +219,29 @@ impl fmt::Display for Fortify {
     }
 }
 
+/// Where the stack canary was found: annobin notes, symbols such as
+/// `__stack_chk_fail`, or the guard check in the code
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq)]
+pub enum CanarySource {
+    Annobin,
+    Symbol,
+    Code,
+}
+
+impl fmt::Display for CanarySource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                Self::Annobin => "Annobin",
+                Self::Symbol => "Symbol",
+                Self::Code => "Code",
+            }
+        )
+    }
+}
+
 /// Checksec result struct for ELF32/64 binaries
 ///
 /// **Example**
@@ -226,12 +264,19 @@ impl fmt::Display for Fortify {
 pub struct CheckSecResults {
     /// Stack Canary (*CFLAGS=*`-fstack-protector*`)
     pub canary: bool,
+    /// How the stack canary was detected, `None` if there is none
+    pub canary_source: Option<CanarySource>,
     /// Clang Control Flow Integrity (*CFLAGS=*`-fsanitize=cfi-*`)
     pub clang_cfi: bool,
-    /// Clang SafeStack (*CFLAGS=*`-fsanitize=safe-stack`)
+    /// Clang `SafeStack` (*CFLAGS=*`-fsanitize=safe-stack`)
     pub clang_safestack: bool,
+    /// Sanitizer, coverage and fuzzing instrumentation
+    /// (*CFLAGS=*`-fsanitize=address,undefined,...`, `--coverage`)
+    pub sanitizers: VecSanitizer,
     /// Stack Clash Protection (*CFLAGS=*`-fstack-clash-protection`)
     pub stack_clash_protection: bool,
+    /// Stack probing mechanism found in the code
+    pub stack_probe: StackProbe,
     /// Fortify (*CFLAGS=*`-D_FORTIFY_SOURCE`)
     pub fortify: Fortify,
     /// Fortified functions
@@ -252,26 +297,95 @@ pub struct CheckSecResults {
     pub dynlibs: Vec<String>,
     // number of symbols
     pub symbol_count: SymbolCount,
+    /// Compiler and linker identification strings (`.comment`)
+    pub comment: Vec<String>,
+    /// GNU build-id (`.note.gnu.build-id`)
+    pub build_id: Option<String>,
+    /// Package metadata (`.note.package`)
+    pub package: Option<PackageNote>,
+    /// Recorded compiler switches (*CFLAGS=*`-frecord-gcc-switches`)
+    pub command_line: Vec<String>,
+    /// Hardening flags recorded by annobin (`.gnu.build.attributes`)
+    pub annobin: Option<Annobin>,
+    /// Source language
+    pub language: Language,
+    /// Go build information (`.go.buildinfo`)
+    pub go_buildinfo: Option<GoBuildInfo>,
+    /// Contains C/C++ code, such as cgo code in a Go binary, which the C
+    /// oriented checks (canary, fortify, stack clash) apply to. Always true
+    /// for C/C++ binaries.
+    pub native_code: bool,
+    /// Debug info and symbol stripping
+    pub debug_info: DebugInfo,
+    /// Retpoline, speculative load hardening and straight-line speculation
+    /// mitigations in x86/x64 code (*CFLAGS=*`-mindirect-branch=thunk
+    /// -mfunction-return=thunk -mharden-sls=all`)
+    pub speculation: Option<SpeculationMitigations>,
+    /// Linux kernel module properties (`.ko`)
+    pub kernel_module: Option<KernelModule>,
 }
 impl CheckSecResults {
     #[must_use]
     pub fn parse(elf: &Elf, bytes: &[u8]) -> Self {
+        // split into functions once for all the code checks
+        let blocks = get_function_code(elf, bytes);
         let (fortified, fortifiable) = elf.has_fortified();
-        let fortify = match (fortified, fortifiable) {
-            (0, 0) => Fortify::Undecidable, 
-            (f, v) if f == v => Fortify::Full,
-            (f, _) if f == 0 => Fortify::None,
-            (f, v) if f < v => Fortify::Partial,
+        let annobin = Annobin::parse(elf, bytes);
+        // annobin records the flags each object was compiled with, so it
+        // takes precedence over guessing from symbols and code
+        let fortify = match (annobin.as_ref().and_then(|a| a.fortify), fortified, fortifiable) {
+            (Some(0), 0, _) => Fortify::None,
+            (Some(0), _, _) => Fortify::Partial,
+            (Some(_), _, _) => Fortify::Full,
+            (None, 0, 0) => Fortify::Undecidable,
+            (None, f, v) if f == v => Fortify::Full,
+            (None, 0, _) => Fortify::None,
+            (None, f, v) if f < v => Fortify::Partial,
             _ => Fortify::Undecidable, // This case should never happen
         };
+        let canary_source =
+            match annobin.as_ref().and_then(|a| a.stack_protector) {
+                Some(level) => (level >= StackProtector::Basic)
+                    .then_some(CanarySource::Annobin),
+                None if elf.has_canary() => Some(CanarySource::Symbol),
+                // static and stripped binaries only give it away in the code
+                None if elf.has_canary_check(&blocks) => {
+                    Some(CanarySource::Code)
+                }
+                None => None,
+            };
+        let canary = canary_source.is_some();
+        let comment = provenance::section_strings(elf, bytes, ".comment");
+        let build_id = provenance::build_id(elf, bytes);
+        let language = Language::detect(elf, bytes, &comment);
+        let go_buildinfo = GoBuildInfo::parse(elf, bytes);
+        let native_code = match language {
+            Language::C => true,
+            Language::Go => {
+                go_buildinfo.as_ref().is_some_and(GoBuildInfo::cgo_enabled)
+                    || language::has_cgo_symbols(elf)
+            }
+            // Rust links libc and the C runtime anyway, only hardened C
+            // objects give away C code of its own
+            Language::Rust => canary || fortified > 0,
+        };
+        let stack_probe = elf.has_stack_probe(&blocks);
+        let stack_clash_protection =
+            match annobin.as_ref().and_then(|a| a.stack_clash) {
+                Some(enabled) => enabled,
+                None => stack_probe != StackProbe::None,
+            };
         Self {
-            canary: elf.has_canary(),
+            canary,
+            canary_source,
             clang_cfi: elf.has_clang_cfi(),
             clang_safestack: elf.has_clang_safestack(),
+            sanitizers: elf.has_sanitizers(),
             fortify,
             fortified,
             fortifiable,
-            stack_clash_protection: elf.has_stack_clash_protection(bytes),
+            stack_clash_protection,
+            stack_probe,
             nx: elf.has_nx(),
             pie: elf.has_pie(),
             relro: elf.has_relro(),
@@ -283,8 +397,283 @@ impl CheckSecResults {
                 .map(std::string::ToString::to_string)
                 .collect(),
             symbol_count: elf.symbol_count(),
+            comment,
+            build_id: build_id.clone(),
+            package: provenance::package(elf, bytes),
+            command_line: provenance::section_strings(
+                elf,
+                bytes,
+                ".GCC.command.line",
+            ),
+            annobin,
+            language,
+            go_buildinfo,
+            native_code,
+            debug_info: get_debug_info(elf, bytes, build_id),
+            speculation: elf.speculation_mitigations(&blocks),
+            kernel_module: KernelModule::parse(elf, bytes),
+        }
+    }
+}
+
+impl CheckSecResults {
+    /// Rust emits stack probes itself, Go checks for stack growth on
+    /// function entry instead
+    #[must_use]
+    pub fn stack_clash_applies(&self) -> bool {
+        self.native_code || self.language == Language::Rust
+    }
+    /// Compare with the results for the loaded image of the same binary,
+    /// limited to the checks that only need program headers, the dynamic
+    /// section and code, as the loaded image has no section headers
+    #[must_use]
+    pub fn differences(&self, loaded: &Self) -> Vec<Difference> {
+        fn paths(rpath: &VecRpath) -> String {
+            rpath
+                .iter()
+                .map(|path| match path {
+                    Rpath::Yes(p) | Rpath::YesRW(p) => p.as_str(),
+                    Rpath::None => "None",
+                })
+                .collect::<Vec<_>>()
+                .join(":")
+        }
+        let checks = [
+            ("canary", self.canary.to_string(), loaded.canary.to_string()),
+            (
+                "clang_cfi",
+                self.clang_cfi.to_string(),
+                loaded.clang_cfi.to_string(),
+            ),
+            (
+                "clang_safestack",
+                self.clang_safestack.to_string(),
+                loaded.clang_safestack.to_string(),
+            ),
+            (
+                "stack_probe",
+                format!("{:?}", self.stack_probe),
+                format!("{:?}", loaded.stack_probe),
+            ),
+            (
+                "fortify",
+                format!("{:?}", self.fortify),
+                format!("{:?}", loaded.fortify),
+            ),
+            (
+                "fortified",
+                self.fortified.to_string(),
+                loaded.fortified.to_string(),
+            ),
+            ("nx", format!("{:?}", self.nx), format!("{:?}", loaded.nx)),
+            ("pie", format!("{:?}", self.pie), format!("{:?}", loaded.pie)),
+            (
+                "relro",
+                format!("{:?}", self.relro),
+                format!("{:?}", loaded.relro),
+            ),
+            ("rpath", paths(&self.rpath), paths(&loaded.rpath)),
+            ("runpath", paths(&self.runpath), paths(&loaded.runpath)),
+            ("dynlibs", self.dynlibs.join(","), loaded.dynlibs.join(",")),
+        ];
+        checks
+            .into_iter()
+            .filter(|(_, file, memory)| file != memory)
+            .map(|(check, file, memory)| Difference {
+                check: check.to_string(),
+                file,
+                memory,
+            })
+            .collect()
+    }
+}
+
+/// Check result that differs between a binary on disk and its loaded image
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
+pub struct Difference {
+    pub check: String,
+    pub file: String,
+    pub memory: String,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} (file) {} (memory)", self.check, self.file, self.memory)
+    }
+}
+
+// `DT_RELR`, not yet known to goblin
+const DT_RELR: u64 = 36;
+// dynamic entries glibc relocates in place when loading a PIE or library
+const RELOCATED_DYNAMIC_TAGS: [u64; 10] = [
+    DT_PLTGOT,
+    DT_HASH,
+    DT_STRTAB,
+    DT_SYMTAB,
+    DT_RELA,
+    DT_REL,
+    DT_JMPREL,
+    DT_VERSYM,
+    DT_GNU_HASH,
+    DT_RELR,
+];
+// refuse to rebuild images claiming to be larger than this
+const MAX_IMAGE_SIZE: u64 = 1 << 30;
+
+/// Rebuild the file image of an ELF binary loaded at `base` from its
+/// `PT_LOAD` segments, `read` fills a buffer from the given address of the
+/// process memory. Section headers are not loaded and dropped from the
+/// image, dynamic entries relocated by the loader are turned back into
+/// virtual addresses.
+///
+/// # Errors
+/// Returns read errors and malformed ELF or program headers.
+pub fn image_from_memory<F>(
+    base: u64,
+    mut read: F,
+) -> Result<Vec<u8>, goblin::error::Error>
+where
+    F: FnMut(u64, &mut [u8]) -> io::Result<()>,
+{
+    let malformed =
+        |msg: &str| goblin::error::Error::Malformed(msg.to_string());
+    let mut ehdr = [0u8; goblin::elf::header::header64::SIZEOF_EHDR];
+    read(base, &mut ehdr)?;
+    let header = Elf::parse_header(&ehdr)?;
+    let ctx = Ctx::new(header.container()?, header.endianness()?);
+
+    // the header comes from the target process, check it before allocating
+    let phentsize = if ctx.is_big() {
+        goblin::elf::program_header::program_header64::SIZEOF_PHDR
+    } else {
+        goblin::elf::program_header::program_header32::SIZEOF_PHDR
+    };
+    if usize::from(header.e_phentsize) != phentsize {
+        return Err(malformed("bad e_phentsize"));
+    }
+    let phnum = usize::from(header.e_phnum);
+    if header.e_phoff.saturating_add((phnum * phentsize) as u64)
+        > MAX_IMAGE_SIZE
+    {
+        return Err(malformed("program headers outside of the image"));
+    }
+    let mut phdrs = vec![0u8; phnum * phentsize];
+    read(base.wrapping_add(header.e_phoff), &mut phdrs)?;
+    let program_headers = ProgramHeader::parse(&phdrs, 0, phnum, ctx)?;
+    let loads: Vec<&ProgramHeader> =
+        program_headers.iter().filter(|ph| ph.p_type == PT_LOAD).collect();
+    let first = loads
+        .iter()
+        .map(|ph| ph.p_vaddr & !(ph.p_align.max(1) - 1))
+        .min()
+        .ok_or_else(|| malformed("no PT_LOAD segments"))?;
+    let bias =
+        if header.e_type == ET_DYN { base.wrapping_sub(first) } else { 0 };
+
+    let size = loads
+        .iter()
+        .map(|ph| ph.p_offset.saturating_add(ph.p_filesz))
+        .chain([header.e_phoff.saturating_add(phdrs.len() as u64)])
+        .max()
+        .unwrap_or_default()
+        .max(ehdr.len() as u64);
+    if size > MAX_IMAGE_SIZE {
+        return Err(malformed("loaded image too large"));
+    }
+    // all offsets are within MAX_IMAGE_SIZE from here on
+    #[allow(clippy::cast_possible_truncation)]
+    let mut image = vec![0u8; size as usize];
+    for ph in &loads {
+        #[allow(clippy::cast_possible_truncation)]
+        let range = ph.p_offset as usize..(ph.p_offset + ph.p_filesz) as usize;
+        read(bias.wrapping_add(ph.p_vaddr), &mut image[range])?;
+    }
+    if let Some(dynamic) =
+        program_headers.iter().find(|ph| ph.p_type == PT_DYNAMIC)
+    {
+        if bias != 0 {
+            unrelocate_dynamic(&mut image, dynamic, &loads, bias, ctx.is_big())
+                .map_err(|_| malformed("bad PT_DYNAMIC"))?;
+        }
+    }
+    // e_shoff, e_shnum and e_shstrndx
+    let (shoff, shnum) = if ctx.is_big() {
+        (0x28..0x30, 0x3c..0x40)
+    } else {
+        (0x20..0x24, 0x30..0x34)
+    };
+    image[shoff].fill(0);
+    image[shnum].fill(0);
+    Ok(image)
+}
+
+/// subtract the load bias from the dynamic entries the loader relocated
+fn unrelocate_dynamic(
+    image: &mut [u8],
+    dynamic: &ProgramHeader,
+    loads: &[&ProgramHeader],
+    bias: u64,
+    is_64: bool,
+) -> Result<(), scroll::Error> {
+    let endian = if image[5] == goblin::elf::header::ELFDATA2MSB {
+        Endian::Big
+    } else {
+        Endian::Little
+    };
+    let in_segment = |vaddr: u64| {
+        loads.iter().any(|ph| {
+            (ph.p_vaddr..ph.p_vaddr.saturating_add(ph.p_memsz))
+                .contains(&vaddr)
+        })
+    };
+    let word = if is_64 { 8 } else { 4 };
+    let start = usize::try_from(dynamic.p_offset).unwrap_or(usize::MAX);
+    let count = usize::try_from(dynamic.p_filesz).unwrap_or(0) / (2 * word);
+    for entry in (0..count).map(|i| start.saturating_add(i * 2 * word)) {
+        let (tag, value) = if is_64 {
+            (
+                image.pread_with::<u64>(entry, endian)?,
+                image.pread_with::<u64>(entry + word, endian)?,
+            )
+        } else {
+            (
+                u64::from(image.pread_with::<u32>(entry, endian)?),
+                u64::from(image.pread_with::<u32>(entry + word, endian)?),
+            )
+        };
+        let value = value.wrapping_sub(bias);
+        if RELOCATED_DYNAMIC_TAGS.contains(&tag)
+            && !in_segment(value.wrapping_add(bias))
+            && in_segment(value)
+        {
+            if is_64 {
+                image.pwrite_with(value, entry + word, endian)?;
+            } else {
+                #[allow(clippy::cast_possible_truncation)]
+                image.pwrite_with(value as u32, entry + word, endian)?;
+            }
         }
     }
+    Ok(())
+}
+
+/// boolean check result, or N/A if the check does not apply
+#[cfg(not(feature = "color"))]
+fn not_applicable(value: bool, applies: bool) -> String {
+    if applies {
+        value.to_string()
+    } else {
+        "N/A".to_string()
+    }
+}
+/// colorized boolean check result, or N/A if the check does not apply
+#[cfg(feature = "color")]
+fn not_applicable(value: bool, applies: bool) -> String {
+    if applies {
+        colorize_bool!(value)
+    } else {
+        format!("{:<5}", "N/A").yellow().to_string()
+    }
 }
 
 impl fmt::Display for CheckSecResults {
@@ -293,13 +682,21 @@ impl fmt::Display for CheckSecResults {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "Canary: {} CFI: {} SafeStack: {} StackClash: {} Fortify: {} Fortified: {:2} \
+            "Canary: {} CFI: {} SafeStack: {} Sanitizers: {} StackClash: {} Fortify: {} Fortified: {:2} \
             Fortifiable: {:2} NX: {} PIE: {} Relro: {} RPATH: {} RUNPATH: {} Symbols: {}",
-            self.canary,
+            not_applicable(self.canary, self.native_code),
             self.clang_cfi,
             self.clang_safestack,
-            self.stack_clash_protection,
-            self.fortify,
+            self.sanitizers,
+            not_applicable(
+                self.stack_clash_protection,
+                self.stack_clash_applies()
+            ),
+            if self.native_code {
+                self.fortify.to_string()
+            } else {
+                format!("{:<11}", "N/A")
+            },
             self.fortified,
             self.fortifiable,
             self.nx,
@@ -307,25 +704,66 @@ impl fmt::Display for CheckSecResults {
             self.relro,
             self.rpath,
             self.runpath,
-            self.symbols
-        )
+            self.symbol_count
+        )?;
+        if let Some(compiler) = self.comment.first() {
+            write!(f, " Compiler: {compiler}")?;
+        }
+        if let Some(build_id) = &self.build_id {
+            write!(f, " Build-ID: {build_id}")?;
+        }
+        if let Some(package) = &self.package {
+            write!(f, " Package: {package}")?;
+        }
+        write!(f, " Stripped: {}", self.debug_info.stripped)?;
+        if let Some(debug_file) = &self.debug_info.debug_file {
+            write!(f, " Debug File: {debug_file}")?;
+        }
+        if self.language != Language::C {
+            write!(f, " Language: {}", self.language)?;
+        }
+        if let Some(go) = &self.go_buildinfo {
+            write!(f, " {go}")?;
+        }
+        if self.canary_source == Some(CanarySource::Code) {
+            write!(f, " Canary Source: {}", CanarySource::Code)?;
+        }
+        if self.stack_probe != StackProbe::None {
+            write!(f, " Stack Probe: {}", self.stack_probe)?;
+        }
+        if let Some(speculation) = &self.speculation {
+            write!(f, " {speculation}")?;
+        }
+        if let Some(kernel_module) = &self.kernel_module {
+            write!(f, " {kernel_module}")?;
+        }
+        Ok(())
     }
     #[cfg(feature = "color")]
     /// Colorized human readable format output
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "{} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {}",
+            "{} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {}",
             "Canary:".bold(),
-            colorize_bool!(self.canary),
+            not_applicable(self.canary, self.native_code),
             "CFI:".bold(),
             colorize_bool!(self.clang_cfi),
             "SafeStack:".bold(),
             colorize_bool!(self.clang_safestack),
+            "Sanitizers:".bold(),
+            self.sanitizers,
             "StackClash:".bold(),
-            colorize_bool!(self.stack_clash_protection),
+            not_applicable(
+                self.stack_clash_protection,
+                self.stack_clash_applies()
+            ),
             "Fortify:".bold(),
-            self.fortify,
+            if self.native_code {
+                self.fortify.to_string()
+            } else {
+                format!("{:<11}", "N/A").yellow().to_string()
+            },
             "Fortified:".bold(),
             self.fortified,
             "Fortifiable:".bold(),
@@ -342,7 +780,142 @@ impl fmt::Display for CheckSecResults {
             self.runpath,
             "Symbols".bold(),
             self.symbol_count
+        )?;
+        if let Some(compiler) = self.comment.first() {
+            write!(f, " {} {}", "Compiler:".bold(), compiler)?;
+        }
+        if let Some(build_id) = &self.build_id {
+            write!(f, " {} {}", "Build-ID:".bold(), build_id)?;
+        }
+        if let Some(package) = &self.package {
+            write!(f, " {} {}", "Package:".bold(), package)?;
+        }
+        write!(f, " {} {}", "Stripped:".bold(), self.debug_info.stripped)?;
+        if let Some(debug_file) = &self.debug_info.debug_file {
+            write!(f, " {} {}", "Debug File:".bold(), debug_file)?;
+        }
+        if self.language != Language::C {
+            write!(f, " {} {}", "Language:".bold(), self.language)?;
+        }
+        if let Some(go) = &self.go_buildinfo {
+            write!(f, " {go}")?;
+        }
+        if self.canary_source == Some(CanarySource::Code) {
+            write!(f, " {} {}", "Canary Source:".bold(), CanarySource::Code)?;
+        }
+        if self.stack_probe != StackProbe::None {
+            write!(f, " {} {}", "Stack Probe:".bold(), self.stack_probe)?;
+        }
+        if let Some(speculation) = &self.speculation {
+            write!(f, " {speculation}")?;
+        }
+        if let Some(kernel_module) = &self.kernel_module {
+            write!(f, " {kernel_module}")?;
+        }
+        Ok(())
+    }
+}
+
+/// code of executable sections, or executable segments if the section
+/// headers are gone, split into functions where symbols or unwind
+/// information tell where they are, empty without the `disassembly` feature
+#[allow(unused_variables)]
+fn get_function_code<'a>(elf: &Elf, bytes: &'a [u8]) -> Vec<(u64, &'a [u8])> {
+    #[cfg(not(feature = "disassembly"))]
+    return Vec::new();
+    #[cfg(feature = "disassembly")]
+    {
+        let functions = Functions::from_elf(elf, bytes);
+        let code = |offset: u64, size: u64, address: u64| {
+            let start = usize::try_from(offset).ok()?;
+            let end = start.checked_add(usize::try_from(size).ok()?)?;
+            Some(functions.code(bytes.get(start..end)?, address))
+        };
+        if elf.section_headers.is_empty() {
+            return elf
+                .program_headers
+                .iter()
+                .filter(|ph| ph.p_type == PT_LOAD && ph.p_flags & PF_X != 0)
+                .filter_map(|ph| code(ph.p_offset, ph.p_filesz, ph.p_vaddr))
+                .flatten()
+                .collect();
+        }
+        elf.section_headers
+            .iter()
+            .filter(|sh| {
+                sh.sh_type == SHT_PROGBITS
+                    && sh.sh_flags & u64::from(SHF_EXECINSTR | SHF_ALLOC)
+                        == u64::from(SHF_EXECINSTR | SHF_ALLOC)
+            })
+            .filter_map(|sh| code(sh.sh_offset, sh.sh_size, sh.sh_addr))
+            .flatten()
+            .collect()
+    }
+}
+
+// `Elf32_Chdr`/`Elf64_Chdr` sizes and `ELFCOMPRESS_ZLIB`
+const CHDR_SIZE_32: usize = 12;
+const CHDR_SIZE_64: usize = 24;
+const ELFCOMPRESS_ZLIB: u32 = 1;
+
+/// contents of a `.gnu_debuglink` or `.gnu_debugaltlink` section up to the
+/// first NUL, the file name
+fn get_link_name(elf: &Elf, bytes: &[u8], name: &str) -> Option<String> {
+    let data = provenance::section_data(elf, bytes, name)?;
+    let name = data.split(|&b| b == 0).next()?;
+    (!name.is_empty()).then(|| String::from_utf8_lossy(name).into_owned())
+}
+
+/// DWARF version of `.debug_info`, compressed with `SHF_COMPRESSED` or as
+/// `.zdebug_info`
+fn get_dwarf_version(elf: &Elf, bytes: &[u8]) -> Option<u16> {
+    let sh = elf.section_headers.iter().find(|sh| {
+        matches!(
+            elf.shdr_strtab.get_at(sh.sh_name),
+            Some(".debug_info" | ".zdebug_info")
         )
+    })?;
+    let name = elf.shdr_strtab.get_at(sh.sh_name)?;
+    let data = provenance::section_data(elf, bytes, name)?;
+    if name == ".zdebug_info" {
+        let header = debuginfo::inflate_header(debuginfo::zdebug_data(data)?)?;
+        return debuginfo::dwarf_version(&header, elf.little_endian);
+    }
+    if sh.sh_flags & u64::from(SHF_COMPRESSED) == 0 {
+        return debuginfo::dwarf_version(data, elf.little_endian);
+    }
+    let ch_type: [u8; 4] = data.get(..4)?.try_into().ok()?;
+    let ch_type = if elf.little_endian {
+        u32::from_le_bytes(ch_type)
+    } else {
+        u32::from_be_bytes(ch_type)
+    };
+    if ch_type != ELFCOMPRESS_ZLIB {
+        return None;
+    }
+    let chdr_size = if elf.is_64 { CHDR_SIZE_64 } else { CHDR_SIZE_32 };
+    let header = debuginfo::inflate_header(data.get(chdr_size..)?)?;
+    debuginfo::dwarf_version(&header, elf.little_endian)
+}
+
+fn get_debug_info(elf: &Elf, bytes: &[u8], build_id: Option<String>) -> DebugInfo {
+    let debug_sections: Vec<String> = elf
+        .section_headers
+        .iter()
+        .filter_map(|sh| elf.shdr_strtab.get_at(sh.sh_name))
+        .filter(|name| debuginfo::is_debug_section(name))
+        .map(ToString::to_string)
+        .collect();
+    let symbols = !elf.syms.is_empty();
+    DebugInfo {
+        stripped: Stripped::new(symbols, !debug_sections.is_empty()),
+        symbols,
+        dwarf_version: get_dwarf_version(elf, bytes),
+        debug_sections,
+        stabs: false,
+        debug_file: get_link_name(elf, bytes, ".gnu_debuglink"),
+        alt_debug_file: get_link_name(elf, bytes, ".gnu_debugaltlink"),
+        debug_id: build_id,
     }
 }
 
@@ -367,12 +940,29 @@ impl fmt::Display for CheckSecResults {
 pub trait Properties {
     /// check for `__stack_chk_fail` or `__intel_security_cookie` in dynstrtab
     fn has_canary(&self) -> bool;
+    /// find the stack guard load and compare of `-fstack-protector` in
+    /// x86/x64 `(address, code)` blocks, for static or stripped binaries
+    /// without canary symbols
+    fn has_canary_check(&self, blocks: &[(u64, &[u8])]) -> bool;
     /// check for symbols containing `.cfi` in dynstrtab
     fn has_clang_cfi(&self) -> bool;
     /// check for `__safestack_init` in dynstrtab
     fn has_clang_safestack(&self) -> bool;
+    /// check symbols and linked libraries for sanitizer, coverage and
+    /// fuzzing runtimes
+    fn has_sanitizers(&self) -> VecSanitizer;
     /// checks for Stack Clash Protection
     fn has_stack_clash_protection(&self, bytes: &[u8]) -> bool;
+    /// find stack probe loops, unrolled probes and probe function calls in
+    /// `(address, code)` blocks, usually one per function
+    fn has_stack_probe(&self, blocks: &[(u64, &[u8])]) -> StackProbe;
+    /// count retpoline thunks, indirect branches, `lfence` barriers and
+    /// `int3` after `ret`/`jmp` in `(address, code)` blocks, `None` for
+    /// non-x86 binaries or without the `disassembly` feature
+    fn speculation_mitigations(
+        &self,
+        blocks: &[(u64, &[u8])],
+    ) -> Option<SpeculationMitigations>;
     /// check for symbols ending in `_chk` from dynstrtab
     fn has_fortify(&self) -> bool;
     /// counts fortified and fortifiable symbols from dynstrtab
@@ -500,6 +1090,21 @@ impl Properties for Elf<'_> {
         }
         false
     }
+    #[allow(unused_variables)]
+    fn has_canary_check(&self, blocks: &[(u64, &[u8])]) -> bool {
+        #[cfg(not(feature = "disassembly"))]
+        return false;
+        #[cfg(feature = "disassembly")]
+        {
+            let Some(bitness) =
+                Architecture::from_elf(self.header.e_machine, self.is_64)
+                    .and_then(Architecture::bitness)
+            else {
+                return false;
+            };
+            disassembly::has_canary_check(blocks, bitness)
+        }
+    }
     #[allow(clippy::case_sensitive_file_extension_comparisons)]
     fn has_clang_cfi(&self) -> bool {
         for sym in &self.syms {
@@ -528,38 +1133,79 @@ impl Properties for Elf<'_> {
         }
         false
     }
-    #[allow(unused_variables)]
+    fn has_sanitizers(&self) -> VecSanitizer {
+        VecSanitizer::detect(
+            self.syms
+                .iter()
+                .filter_map(|sym| self.strtab.get_at(sym.st_name))
+                .chain(
+                    self.dynsyms
+                        .iter()
+                        .filter_map(|sym| self.dynstrtab.get_at(sym.st_name)),
+                ),
+            self.libraries.iter().copied(),
+        )
+    }
     fn has_stack_clash_protection(&self, bytes: &[u8]) -> bool {
-        for sym in &self.syms {
-            if let Some(name) = self.strtab.get_at(sym.st_name) {
-                if name == "__rust_probestack" {
-                    return true;
-                }
-            }
+        self.has_stack_probe(&get_function_code(self, bytes))
+            != StackProbe::None
+    }
+    #[allow(unused_variables)]
+    fn has_stack_probe(&self, blocks: &[(u64, &[u8])]) -> StackProbe {
+        let routines: Vec<u64> = self
+            .syms
+            .iter()
+            .filter(|sym| {
+                sym.st_value != 0
+                    && self
+                        .strtab
+                        .get_at(sym.st_name)
+                        .is_some_and(disassembly::is_probe_routine)
+            })
+            .map(|sym| sym.st_value)
+            .collect();
+        // a linked in probe function is there to be called
+        let linked = if routines.is_empty() {
+            StackProbe::None
+        } else {
+            StackProbe::Call
+        };
+        #[cfg(not(feature = "disassembly"))]
+        return linked;
+        #[cfg(feature = "disassembly")]
+        {
+            let Some(arch) =
+                Architecture::from_elf(self.header.e_machine, self.is_64)
+            else {
+                return linked;
+            };
+            arch.stack_probe(blocks, routines).max(linked)
         }
+    }
+    #[allow(unused_variables)]
+    fn speculation_mitigations(
+        &self,
+        blocks: &[(u64, &[u8])],
+    ) -> Option<SpeculationMitigations> {
         #[cfg(not(feature = "disassembly"))]
-        return false;
+        return None;
         #[cfg(feature = "disassembly")]
-        self.section_headers
-            .iter()
-            .filter(|sh| {
-                sh.sh_type == SHT_PROGBITS
-                    && sh.sh_flags & u64::from(SHF_EXECINSTR | SHF_ALLOC)
-                        == u64::from(SHF_EXECINSTR | SHF_ALLOC)
-            })
-            .any(|sh| {
-                if let Some(execbytes) = bytes.get(
-                    usize::try_from(sh.sh_offset).unwrap()
-                        ..usize::try_from(sh.sh_offset + sh.sh_size).unwrap(),
-                ) {
-                    let bitness =
-                        if self.is_64 { Bitness::B64 } else { Bitness::B32 };
-
-                    has_stack_clash_protection(execbytes, bitness, sh.sh_addr)
-                } else {
-                    false
-                }
-            })
+        {
+            let bitness =
+                Architecture::from_elf(self.header.e_machine, self.is_64)?
+                    .bitness()?;
+            let thunks = Thunks::from_symbols(
+                self.syms
+                    .iter()
+                    .filter_map(|sym| {
+                        Some((self.strtab.get_at(sym.st_name)?, sym.st_value))
+                    })
+                    .chain(self.dynsyms.iter().filter_map(|sym| {
+                        Some((self.dynstrtab.get_at(sym.st_name)?, sym.st_value))
+                    })),
+            );
+            Some(speculation_mitigations(blocks, bitness, thunks))
+        }
     }
     fn has_fortify(&self) -> bool {
         for sym in &self.dynsyms {
@@ -615,7 +1261,7 @@ impl Properties for Elf<'_> {
                 break;
             }
         }
-        return Nx::Disabled;
+        Nx::Disabled
     }
     fn has_pie(&self) -> PIE {
         if self.header.e_type == ET_DYN {
@@ -659,7 +1305,7 @@ impl Properties for Elf<'_> {
         VecRpath::new(vec![Rpath::None])
     }
     fn symbol_count(&self) -> SymbolCount {
-        return SymbolCount { count: self.syms.len() };
+        SymbolCount { count: self.syms.len() }
     }
     fn has_runpath(&self) -> VecRpath {
         if self.dynamic.is_some() {
@@ -0,0 +1,188 @@
+use glob::Pattern;
+use ignore::{Walk, WalkBuilder};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+use crate::privilege;
+
+/// enough bytes to tell the formats apart
+const MAGIC_LEN: usize = 8;
+
+const ARCHIVE_MAGIC: &[u8] = b"!<arch>\n";
+#[cfg(feature = "elf")]
+const ELF_MAGIC: &[u8] = b"\x7fELF";
+#[cfg(feature = "pe")]
+const PE_MAGIC: &[u8] = b"MZ";
+// thin Mach-O in both byte orders, fat and 64-bit fat
+#[cfg(feature = "macho")]
+static MACHO_MAGICS: [&[u8]; 6] = [
+    b"\xfe\xed\xfa\xce",
+    b"\xfe\xed\xfa\xcf",
+    b"\xce\xfa\xed\xfe",
+    b"\xcf\xfa\xed\xfe",
+    b"\xca\xfe\xba\xbe",
+    b"\xca\xfe\xba\xbf",
+];
+
+/// Why a file found in a directory walk was not checked
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Skip {
+    /// matches none of the `--include` globs, or an `--exclude` glob
+    Excluded,
+    /// larger than `--max-size`
+    TooLarge,
+    /// not setuid/setgid and without capabilities (`--privileged`)
+    Unprivileged,
+    /// no magic bytes of a supported format
+    NotBinary,
+}
+
+impl fmt::Display for Skip {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Excluded => "excluded",
+                Self::TooLarge => "too large",
+                Self::Unprivileged => "not privileged",
+                Self::NotBinary => "not a binary",
+            }
+        )
+    }
+}
+
+/// Which files of a directory to check
+#[allow(clippy::struct_excessive_bools)]
+pub struct WalkOptions {
+    pub include: Vec<Pattern>,
+    pub exclude: Vec<Pattern>,
+    /// in bytes
+    pub max_size: Option<u64>,
+    pub follow_symlinks: bool,
+    /// also walk hidden files and directories
+    pub hidden: bool,
+    /// honor `.gitignore`, `.ignore` and git exclude files
+    pub ignore_files: bool,
+    /// only setuid/setgid binaries and binaries with capabilities
+    pub privileged: bool,
+}
+
+impl WalkOptions {
+    pub fn walk(&self, basepath: &Path) -> Walk {
+        WalkBuilder::new(basepath)
+            .follow_links(self.follow_symlinks)
+            .hidden(!self.hidden)
+            .parents(self.ignore_files)
+            .ignore(self.ignore_files)
+            .git_ignore(self.ignore_files)
+            .git_global(self.ignore_files)
+            .git_exclude(self.ignore_files)
+            .build()
+    }
+    /// Why `file` below `basepath` is not checked, cheapest checks first and
+    /// only reading the first bytes of the file
+    pub fn skip(
+        &self,
+        file: &Path,
+        basepath: &Path,
+    ) -> io::Result<Option<Skip>> {
+        let relative = file.strip_prefix(basepath).unwrap_or(file);
+        if (!self.include.is_empty()
+            && !self.include.iter().any(|glob| glob.matches_path(relative)))
+            || self.exclude.iter().any(|glob| glob.matches_path(relative))
+        {
+            return Ok(Some(Skip::Excluded));
+        }
+        let mut fp = fs::File::open(file)?;
+        if self.max_size.is_some_and(|max| {
+            fp.metadata().is_ok_and(|metadata| metadata.len() > max)
+        }) {
+            return Ok(Some(Skip::TooLarge));
+        }
+        if self.privileged && !privilege::is_privileged(file) {
+            return Ok(Some(Skip::Unprivileged));
+        }
+        let mut magic = Vec::with_capacity(MAGIC_LEN);
+        fp.by_ref().take(MAGIC_LEN as u64).read_to_end(&mut magic)?;
+        if !has_binary_magic(&magic) {
+            return Ok(Some(Skip::NotBinary));
+        }
+        Ok(None)
+    }
+}
+
+fn has_binary_magic(magic: &[u8]) -> bool {
+    #[cfg(feature = "elf")]
+    if magic.starts_with(ELF_MAGIC) {
+        return true;
+    }
+    #[cfg(feature = "pe")]
+    if magic.starts_with(PE_MAGIC) {
+        return true;
+    }
+    #[cfg(feature = "macho")]
+    if MACHO_MAGICS.iter().any(|macho| magic.starts_with(macho)) {
+        return true;
+    }
+    magic.starts_with(ARCHIVE_MAGIC)
+}
+
+/// `--max-size` value, bytes with an optional `K`, `M` or `G` suffix
+pub fn parse_size(size: &str) -> Result<u64, String> {
+    let upper = size.trim().to_ascii_uppercase();
+    let (number, shift) = [("K", 10), ("M", 20), ("G", 30)]
+        .iter()
+        .find_map(|(suffix, shift)| {
+            upper.strip_suffix(suffix).map(|number| (number, *shift))
+        })
+        .unwrap_or((upper.as_str(), 0));
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|number| number.checked_mul(1 << shift))
+        .ok_or_else(|| format!("invalid size {size}"))
+}
+
+/// Files skipped and failed in a directory scan
+#[derive(Default)]
+pub struct ScanSummary {
+    pub checked: usize,
+    pub skipped: BTreeMap<Skip, usize>,
+    /// file, or walk error, and reason
+    pub failed: Vec<String>,
+}
+
+impl ScanSummary {
+    /// worth reporting, a scan without either only found binaries
+    pub fn is_notable(&self) -> bool {
+        !self.skipped.is_empty() || !self.failed.is_empty()
+    }
+}
+
+impl fmt::Display for ScanSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Checked {} files, skipped {}",
+            self.checked,
+            self.skipped.values().sum::<usize>()
+        )?;
+        if !self.skipped.is_empty() {
+            let reasons: Vec<String> = self
+                .skipped
+                .iter()
+                .map(|(reason, count)| format!("{count} {reason}"))
+                .collect();
+            write!(f, " ({})", reasons.join(", "))?;
+        }
+        write!(f, ", failed {}", self.failed.len())?;
+        for failure in &self.failed {
+            write!(f, "\n \u{21aa} {failure}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,412 @@
+//! Implements function boundary discovery from symbol and unwind information,
+//! so code checks can decode one function at a time instead of whole
+//! sections with data, padding and jump tables mixed in
+#[cfg(feature = "elf")]
+use goblin::elf::program_header::PT_GNU_EH_FRAME;
+#[cfg(feature = "elf")]
+use goblin::elf::sym::STT_FUNC;
+#[cfg(feature = "elf")]
+use goblin::elf::Elf;
+#[cfg(feature = "macho")]
+use goblin::mach::load_command::CommandVariant;
+#[cfg(feature = "macho")]
+use goblin::mach::MachO;
+#[cfg(feature = "pe")]
+use goblin::pe::PE;
+use scroll::{Endian, Pread, Sleb128, Uleb128};
+use std::ops::Deref;
+
+// DWARF exception header pointer encodings
+const DW_EH_PE_OMIT: u8 = 0xff;
+const DW_EH_PE_ABSPTR: u8 = 0x00;
+const DW_EH_PE_ULEB128: u8 = 0x01;
+const DW_EH_PE_UDATA2: u8 = 0x02;
+const DW_EH_PE_UDATA4: u8 = 0x03;
+const DW_EH_PE_UDATA8: u8 = 0x04;
+const DW_EH_PE_SLEB128: u8 = 0x09;
+const DW_EH_PE_SDATA2: u8 = 0x0a;
+const DW_EH_PE_SDATA4: u8 = 0x0b;
+const DW_EH_PE_SDATA8: u8 = 0x0c;
+const DW_EH_PE_PCREL: u8 = 0x10;
+const DW_EH_PE_DATAREL: u8 = 0x30;
+
+/// Address range of a function, `end` is exclusive
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Function {
+    pub start: u64,
+    pub end: u64,
+}
+
+/// Sorted, non-overlapping function ranges of a binary
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Functions {
+    functions: Vec<Function>,
+}
+
+impl Functions {
+    /// Build the ranges from function start addresses and, where the source
+    /// records it, their sizes. Functions without a size end at the next
+    /// start, overlapping ranges are merged.
+    #[must_use]
+    pub fn new(mut entries: Vec<(u64, Option<u64>)>) -> Self {
+        entries.retain(|&(start, _)| start != 0);
+        // sizes first, so dedup keeps them
+        entries.sort_by_key(|&(start, size)| (start, size.is_none()));
+        entries.dedup_by_key(|&mut (start, _)| start);
+        let mut functions: Vec<Function> = Vec::with_capacity(entries.len());
+        for (index, &(start, size)) in entries.iter().enumerate() {
+            let next = entries.get(index + 1).map_or(u64::MAX, |e| e.0);
+            let end = size
+                .filter(|&size| size != 0)
+                .map_or(next, |size| start.saturating_add(size));
+            match functions.last_mut() {
+                Some(last) if start < last.end => last.end = last.end.max(end),
+                _ => functions.push(Function { start, end }),
+            }
+        }
+        Self { functions }
+    }
+    /// Functions from `.symtab`/`.dynsym` `STT_FUNC` symbols and the FDEs of
+    /// `.eh_frame`, or the `.eh_frame_hdr` search table if the section
+    /// headers are gone
+    #[cfg(feature = "elf")]
+    #[must_use]
+    pub fn from_elf(elf: &Elf, bytes: &[u8]) -> Self {
+        let mut entries: Vec<(u64, Option<u64>)> = elf
+            .syms
+            .iter()
+            .chain(elf.dynsyms.iter())
+            .filter(|sym| sym.st_type() == STT_FUNC && sym.st_shndx != 0)
+            .map(|sym| (sym.st_value, Some(sym.st_size)))
+            .collect();
+        let endian =
+            if elf.little_endian { Endian::Little } else { Endian::Big };
+        let address_size = if elf.is_64 { 8 } else { 4 };
+        let eh_frame = elf.section_headers.iter().find(|sh| {
+            elf.shdr_strtab.get_at(sh.sh_name) == Some(".eh_frame")
+        });
+        if let Some(sh) = eh_frame {
+            if let Some(data) = file_range(bytes, sh.sh_offset, sh.sh_size) {
+                entries.extend(
+                    parse_eh_frame(data, sh.sh_addr, endian, address_size)
+                        .into_iter()
+                        .map(|(start, size)| (start, Some(size))),
+                );
+            }
+        } else if let Some(ph) =
+            elf.program_headers.iter().find(|ph| ph.p_type == PT_GNU_EH_FRAME)
+        {
+            if let Some(data) = file_range(bytes, ph.p_offset, ph.p_filesz) {
+                entries.extend(
+                    parse_eh_frame_hdr(data, ph.p_vaddr, endian, address_size)
+                        .into_iter()
+                        .map(|start| (start, None)),
+                );
+            }
+        }
+        Self::new(entries)
+    }
+    /// Functions from the x64 `.pdata` runtime function table
+    #[cfg(feature = "pe")]
+    #[must_use]
+    pub fn from_pe(pe: &PE) -> Self {
+        let Some(exception_data) = &pe.exception_data else {
+            return Self::default();
+        };
+        // goblin does not check the directory size against the file, so
+        // read entry by entry until one is out of bounds
+        Self::new(
+            (0..exception_data.len())
+                .map_while(|index| exception_data.get_function(index).ok())
+                .filter(|function| {
+                    function.end_address > function.begin_address
+                })
+                .map(|function| {
+                    (
+                        pe.image_base + u64::from(function.begin_address),
+                        Some(u64::from(
+                            function.end_address - function.begin_address,
+                        )),
+                    )
+                })
+                .collect(),
+        )
+    }
+    /// Functions from `LC_FUNCTION_STARTS`, ULEB128 deltas starting at the
+    /// `__TEXT` segment
+    #[cfg(feature = "macho")]
+    #[must_use]
+    pub fn from_macho(macho: &MachO, bytes: &[u8]) -> Self {
+        let Some(text) = macho
+            .segments
+            .iter()
+            .find(|segment| segment.name().is_ok_and(|name| name == "__TEXT"))
+        else {
+            return Self::default();
+        };
+        let Some(data) = macho.load_commands.iter().find_map(|loadcmd| {
+            let CommandVariant::FunctionStarts(cmd) = loadcmd.command else {
+                return None;
+            };
+            file_range(bytes, u64::from(cmd.dataoff), u64::from(cmd.datasize))
+        }) else {
+            return Self::default();
+        };
+        let mut entries = Vec::new();
+        let mut address = text.vmaddr;
+        let mut offset = 0;
+        while let Ok(delta) = Uleb128::read(data, &mut offset) {
+            if delta == 0 {
+                break;
+            }
+            address = address.wrapping_add(delta);
+            entries.push((address, None));
+        }
+        Self::new(entries)
+    }
+    /// Split a block of code loaded at `address` into the functions it
+    /// contains. Gaps between functions are skipped, a block without any
+    /// known function is returned whole.
+    #[must_use]
+    pub fn code<'a>(
+        &self,
+        bytes: &'a [u8],
+        address: u64,
+    ) -> Vec<(u64, &'a [u8])> {
+        let block_end = address.saturating_add(bytes.len() as u64);
+        let first = self.functions.partition_point(|f| f.end <= address);
+        let blocks: Vec<(u64, &[u8])> = self.functions[first..]
+            .iter()
+            .take_while(|function| function.start < block_end)
+            .filter_map(|function| {
+                let start = function.start.max(address);
+                let end = function.end.min(block_end);
+                let offset = usize::try_from(start - address).ok()?;
+                let len = usize::try_from(end - start).ok()?;
+                Some((start, bytes.get(offset..offset + len)?))
+            })
+            .collect();
+        if blocks.is_empty() {
+            vec![(address, bytes)]
+        } else {
+            blocks
+        }
+    }
+}
+
+impl Deref for Functions {
+    type Target = Vec<Function>;
+    fn deref(&self) -> &Self::Target {
+        &self.functions
+    }
+}
+
+fn file_range(bytes: &[u8], offset: u64, size: u64) -> Option<&[u8]> {
+    let start = usize::try_from(offset).ok()?;
+    let size = usize::try_from(size).ok()?;
+    bytes.get(start..start.checked_add(size)?)
+}
+
+/// Read a pointer encoded with `encoding`, `address` is the address of the
+/// section start for `DW_EH_PE_pcrel` and `DW_EH_PE_datarel`
+fn read_encoded(
+    data: &[u8],
+    offset: &mut usize,
+    encoding: u8,
+    address: u64,
+    endian: Endian,
+    address_size: usize,
+) -> Option<u64> {
+    let field = address.wrapping_add(*offset as u64);
+    let value = match encoding & 0x0f {
+        DW_EH_PE_ABSPTR if address_size == 8 => {
+            data.gread_with::<u64>(offset, endian).ok()?
+        }
+        DW_EH_PE_ULEB128 => Uleb128::read(data, offset).ok()?,
+        DW_EH_PE_UDATA2 => {
+            u64::from(data.gread_with::<u16>(offset, endian).ok()?)
+        }
+        DW_EH_PE_ABSPTR | DW_EH_PE_UDATA4 => {
+            u64::from(data.gread_with::<u32>(offset, endian).ok()?)
+        }
+        DW_EH_PE_UDATA8 => data.gread_with::<u64>(offset, endian).ok()?,
+        DW_EH_PE_SLEB128 => Sleb128::read(data, offset).ok()?.cast_unsigned(),
+        DW_EH_PE_SDATA2 => {
+            i64::from(data.gread_with::<i16>(offset, endian).ok()?)
+                .cast_unsigned()
+        }
+        DW_EH_PE_SDATA4 => {
+            i64::from(data.gread_with::<i32>(offset, endian).ok()?)
+                .cast_unsigned()
+        }
+        DW_EH_PE_SDATA8 => {
+            data.gread_with::<i64>(offset, endian).ok()?.cast_unsigned()
+        }
+        _ => return None,
+    };
+    Some(match encoding & 0x70 {
+        DW_EH_PE_PCREL => field.wrapping_add(value),
+        DW_EH_PE_DATAREL => address.wrapping_add(value),
+        _ => value,
+    })
+}
+
+/// `(pc_begin, pc_range)` of every FDE in an `.eh_frame` section loaded at
+/// `address`
+fn parse_eh_frame(
+    data: &[u8],
+    address: u64,
+    endian: Endian,
+    address_size: usize,
+) -> Vec<(u64, u64)> {
+    let mut fdes = Vec::new();
+    // FDE pointer encoding of each CIE, by offset
+    let mut cies: Vec<(usize, u8)> = Vec::new();
+    let mut offset = 0;
+    while let Ok(length) = data.pread_with::<u32>(offset, endian) {
+        let (length, header) = match length {
+            0 => break,
+            0xffff_ffff => match data.pread_with::<u64>(offset + 4, endian) {
+                Ok(length) => (length, 12),
+                Err(_) => break,
+            },
+            length => (u64::from(length), 4),
+        };
+        let Some(end) = usize::try_from(length)
+            .ok()
+            .and_then(|length| (offset + header).checked_add(length))
+            .filter(|&end| end <= data.len())
+        else {
+            break;
+        };
+        let id_offset = offset + header;
+        let entry = &data[..end];
+        let Ok(id) = entry.pread_with::<u32>(id_offset, endian) else {
+            break;
+        };
+        if id == 0 {
+            if let Some(encoding) =
+                parse_cie(entry, id_offset + 4, endian, address_size)
+            {
+                cies.push((offset, encoding));
+            }
+        } else if let Some(&(_, encoding)) = id_offset
+            .checked_sub(id as usize)
+            .and_then(|cie| cies.iter().find(|&&(offset, _)| offset == cie))
+        {
+            let mut cursor = id_offset + 4;
+            let pc_begin = read_encoded(
+                entry,
+                &mut cursor,
+                encoding,
+                address,
+                endian,
+                address_size,
+            );
+            // the range is an unsigned size in the same format
+            let pc_range = read_encoded(
+                entry,
+                &mut cursor,
+                encoding & 0x0f,
+                0,
+                endian,
+                address_size,
+            );
+            if let (Some(pc_begin), Some(pc_range)) = (pc_begin, pc_range) {
+                fdes.push((pc_begin, pc_range));
+            }
+        }
+        offset = end;
+    }
+    fdes
+}
+
+/// FDE pointer encoding from the augmentation data of a CIE
+fn parse_cie(
+    data: &[u8],
+    mut offset: usize,
+    endian: Endian,
+    address_size: usize,
+) -> Option<u8> {
+    let version: u8 = data.gread(&mut offset).ok()?;
+    let augmentation_end =
+        offset + data.get(offset..)?.iter().position(|&b| b == 0)?;
+    let augmentation = data.get(offset..augmentation_end)?.to_vec();
+    offset = augmentation_end + 1;
+    if augmentation.contains(&b'h') {
+        // eh pointer of the ancient "eh" augmentation
+        return None;
+    }
+    Uleb128::read(data, &mut offset).ok()?; // code alignment
+    Sleb128::read(data, &mut offset).ok()?; // data alignment
+    if version == 1 {
+        offset += 1;
+    } else {
+        Uleb128::read(data, &mut offset).ok()?;
+    }
+    let mut encoding = DW_EH_PE_ABSPTR;
+    if augmentation.first() == Some(&b'z') {
+        Uleb128::read(data, &mut offset).ok()?;
+        for &c in &augmentation[1..] {
+            match c {
+                b'R' => encoding = data.gread(&mut offset).ok()?,
+                b'L' => offset += 1,
+                b'P' => {
+                    let personality: u8 = data.gread(&mut offset).ok()?;
+                    read_encoded(
+                        data,
+                        &mut offset,
+                        personality & 0x0f,
+                        0,
+                        endian,
+                        address_size,
+                    )?;
+                }
+                _ => {}
+            }
+        }
+    }
+    Some(encoding)
+}
+
+/// function start addresses from the binary search table of an
+/// `.eh_frame_hdr` section loaded at `address`
+fn parse_eh_frame_hdr(
+    data: &[u8],
+    address: u64,
+    endian: Endian,
+    address_size: usize,
+) -> Vec<u64> {
+    let (
+        Some(&1),
+        Some(&eh_frame_ptr_enc),
+        Some(&fde_count_enc),
+        Some(&table_enc),
+    ) = (data.first(), data.get(1), data.get(2), data.get(3))
+    else {
+        return Vec::new();
+    };
+    if fde_count_enc == DW_EH_PE_OMIT || table_enc == DW_EH_PE_OMIT {
+        return Vec::new();
+    }
+    let mut offset = 4;
+    let read = |offset: &mut usize, encoding| {
+        read_encoded(data, offset, encoding, address, endian, address_size)
+    };
+    let (Some(_), Some(count)) = (
+        read(&mut offset, eh_frame_ptr_enc),
+        read(&mut offset, fde_count_enc),
+    ) else {
+        return Vec::new();
+    };
+    let mut starts = Vec::new();
+    for _ in 0..count {
+        let (Some(start), Some(_)) =
+            (read(&mut offset, table_enc), read(&mut offset, table_enc))
+        else {
+            break;
+        };
+        starts.push(start);
+    }
+    starts
+}
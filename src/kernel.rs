@@ -0,0 +1,443 @@
+//! Implements the hardening report of a Linux kernel from its build
+//! configuration and runtime settings
+#[cfg(feature = "color")]
+use colored::Colorize;
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "color")]
+use crate::colorize_bool;
+use crate::provenance::StackProtector;
+
+const GZIP_MAGIC: &[u8] = b"\x1f\x8b";
+
+/// Kernel build configuration (`.config`, `/proc/config.gz`)
+#[derive(Clone, Debug, Default)]
+pub struct KernelConfig {
+    options: HashMap<String, String>,
+    /// release from the `# Linux/x86 6.1.0 Kernel Configuration` header
+    pub version: Option<String>,
+}
+
+impl KernelConfig {
+    /// Parse `CONFIG_*=value` lines, `# CONFIG_* is not set` lines are
+    /// the same as missing ones
+    #[must_use]
+    pub fn parse(text: &str) -> Self {
+        let mut config = Self::default();
+        for line in text.lines() {
+            if let Some(header) = line
+                .strip_prefix("# Linux/")
+                .and_then(|h| h.strip_suffix(" Kernel Configuration"))
+            {
+                config.version =
+                    header.split_whitespace().nth(1).map(ToString::to_string);
+            } else if let Some((name, value)) = line
+                .strip_prefix("CONFIG_")
+                .and_then(|option| option.split_once('='))
+            {
+                config.options.insert(name.to_string(), value.to_string());
+            }
+        }
+        config
+    }
+    /// Read a config file, gzip compressed or not
+    ///
+    /// # Errors
+    /// Returns read and decompression errors.
+    pub fn from_file(path: &Path) -> io::Result<Self> {
+        let data = fs::read(path)?;
+        let mut text = String::new();
+        if data.starts_with(GZIP_MAGIC) {
+            GzDecoder::new(data.as_slice()).read_to_string(&mut text)?;
+        } else {
+            text = String::from_utf8_lossy(&data).into_owned();
+        }
+        Ok(Self::parse(&text))
+    }
+    /// value of an option, without the `CONFIG_` prefix
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+    /// whether any of the options, old and new names of the same one, is
+    /// built in or a module
+    #[must_use]
+    pub fn enabled(&self, names: &[&str]) -> bool {
+        names.iter().any(|name| matches!(self.get(name), Some("y" | "m")))
+    }
+    fn stack_protector(&self) -> StackProtector {
+        if self.enabled(&["STACKPROTECTOR_STRONG", "CC_STACKPROTECTOR_STRONG"])
+        {
+            StackProtector::Strong
+        } else if self.enabled(&[
+            "STACKPROTECTOR",
+            "CC_STACKPROTECTOR",
+            "CC_STACKPROTECTOR_REGULAR",
+        ]) {
+            StackProtector::Basic
+        } else {
+            StackProtector::None
+        }
+    }
+}
+
+/// Hardening relevant `/proc/sys` settings
+#[derive(Clone, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
+pub struct Sysctls {
+    /// `kernel.kptr_restrict`, hide kernel pointers
+    pub kptr_restrict: Option<i64>,
+    /// `kernel.dmesg_restrict`, restrict the kernel log to `CAP_SYSLOG`
+    pub dmesg_restrict: Option<i64>,
+    /// `kernel.yama.ptrace_scope`
+    pub ptrace_scope: Option<i64>,
+    /// `kernel.unprivileged_bpf_disabled`
+    pub unprivileged_bpf_disabled: Option<i64>,
+    /// `kernel.kexec_load_disabled`
+    pub kexec_load_disabled: Option<i64>,
+    /// `kernel.perf_event_paranoid`
+    pub perf_event_paranoid: Option<i64>,
+    /// `kernel.randomize_va_space`
+    pub randomize_va_space: Option<i64>,
+    /// `fs.protected_symlinks`
+    pub protected_symlinks: Option<i64>,
+    /// `fs.protected_hardlinks`
+    pub protected_hardlinks: Option<i64>,
+    /// `vm.mmap_min_addr`
+    pub mmap_min_addr: Option<i64>,
+}
+
+impl Sysctls {
+    /// Read the settings below a `/proc/sys` directory
+    #[must_use]
+    pub fn read(proc_sys: &Path) -> Self {
+        let read = |name: &str| {
+            fs::read_to_string(proc_sys.join(name))
+                .ok()
+                .and_then(|value| value.trim().parse().ok())
+        };
+        Self {
+            kptr_restrict: read("kernel/kptr_restrict"),
+            dmesg_restrict: read("kernel/dmesg_restrict"),
+            ptrace_scope: read("kernel/yama/ptrace_scope"),
+            unprivileged_bpf_disabled: read(
+                "kernel/unprivileged_bpf_disabled",
+            ),
+            kexec_load_disabled: read("kernel/kexec_load_disabled"),
+            perf_event_paranoid: read("kernel/perf_event_paranoid"),
+            randomize_va_space: read("kernel/randomize_va_space"),
+            protected_symlinks: read("fs/protected_symlinks"),
+            protected_hardlinks: read("fs/protected_hardlinks"),
+            mmap_min_addr: read("vm/mmap_min_addr"),
+        }
+    }
+    /// name, value and the lowest hardened value of each setting
+    fn entries(&self) -> [(&'static str, Option<i64>, i64); 10] {
+        [
+            ("kptr_restrict", self.kptr_restrict, 1),
+            ("dmesg_restrict", self.dmesg_restrict, 1),
+            ("ptrace_scope", self.ptrace_scope, 1),
+            ("unprivileged_bpf_disabled", self.unprivileged_bpf_disabled, 1),
+            ("kexec_load_disabled", self.kexec_load_disabled, 1),
+            ("perf_event_paranoid", self.perf_event_paranoid, 2),
+            ("randomize_va_space", self.randomize_va_space, 2),
+            ("protected_symlinks", self.protected_symlinks, 1),
+            ("protected_hardlinks", self.protected_hardlinks, 1),
+            ("mmap_min_addr", self.mmap_min_addr, 4096),
+        ]
+    }
+}
+
+/// Checksec result struct for a Linux kernel. Build options are `None`
+/// without a config, runtime state is `None` when checking an offline
+/// config.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
+pub struct CheckSecResults {
+    /// kernel release
+    pub version: Option<String>,
+    /// build configuration the results are based on
+    pub config: Option<PathBuf>,
+    /// Kernel address space layout randomization (`RANDOMIZE_BASE`, not
+    /// disabled by `nokaslr`)
+    pub kaslr: Option<bool>,
+    /// `STACKPROTECTOR`, `STACKPROTECTOR_STRONG`
+    pub stack_protector: Option<StackProtector>,
+    /// read-only kernel text and rodata, non-executable data
+    /// (`STRICT_KERNEL_RWX`)
+    pub strict_kernel_rwx: Option<bool>,
+    /// the same for modules (`STRICT_MODULE_RWX`)
+    pub strict_module_rwx: Option<bool>,
+    /// bounds checked `copy_{to,from}_user` (`HARDENED_USERCOPY`)
+    pub hardened_usercopy: Option<bool>,
+    /// checked string and memory functions (`FORTIFY_SOURCE`)
+    pub fortify_source: Option<bool>,
+    /// guard pages around kernel stacks (`VMAP_STACK`)
+    pub vmap_stack: Option<bool>,
+    /// randomized slab freelists (`SLAB_FREELIST_RANDOM`)
+    pub slab_freelist_random: Option<bool>,
+    /// obfuscated slab freelist pointers (`SLAB_FREELIST_HARDENED`)
+    pub slab_freelist_hardened: Option<bool>,
+    /// restricted `/dev/mem` (`STRICT_DEVMEM`)
+    pub strict_devmem: Option<bool>,
+    /// only signed modules can be loaded (`MODULE_SIG_FORCE`)
+    pub module_sig_force: Option<bool>,
+    /// Supervisor Mode Access Prevention in use (runtime only)
+    pub smap: Option<bool>,
+    /// Supervisor Mode Execution Prevention in use (runtime only)
+    pub smep: Option<bool>,
+    /// Kernel page table isolation (`PAGE_TABLE_ISOLATION`,
+    /// `UNMAP_KERNEL_AT_EL0`), at runtime also true when the CPU is not
+    /// affected by Meltdown
+    pub pti: Option<bool>,
+    /// `/proc/sys` settings (runtime only)
+    pub sysctls: Sysctls,
+}
+
+// where distributions keep the config of the running kernel
+fn config_candidates(release: &str) -> [PathBuf; 4] {
+    [
+        PathBuf::from("/proc/config.gz"),
+        PathBuf::from(format!("/boot/config-{release}")),
+        PathBuf::from(format!("/lib/modules/{release}/config")),
+        PathBuf::from(format!("/lib/modules/{release}/build/.config")),
+    ]
+}
+
+impl CheckSecResults {
+    /// Checks of the build configuration alone, such as the config of an
+    /// offline kernel image
+    ///
+    /// # Errors
+    /// Returns errors reading the config.
+    pub fn from_config(path: &Path) -> io::Result<Self> {
+        let config = KernelConfig::from_file(path)?;
+        Ok(Self::parse(Some((path, &config)), None))
+    }
+    /// Checks of the running kernel, with the config from `/proc/config.gz`
+    /// or `/boot/config-<release>` if there is one
+    #[must_use]
+    pub fn running() -> Self {
+        let release = fs::read_to_string("/proc/sys/kernel/osrelease")
+            .map(|release| release.trim().to_string())
+            .unwrap_or_default();
+        let config =
+            config_candidates(&release).into_iter().find_map(|path| {
+                KernelConfig::from_file(&path)
+                    .ok()
+                    .map(|config| (path, config))
+            });
+        let runtime = Runtime {
+            release,
+            cmdline: fs::read_to_string("/proc/cmdline").unwrap_or_default(),
+            cpu_flags: fs::read_to_string("/proc/cpuinfo")
+                .unwrap_or_default()
+                .lines()
+                .find_map(|line| {
+                    let (key, value) = line.split_once(':')?;
+                    (key.trim() == "flags").then(|| value.to_string())
+                }),
+            meltdown: fs::read_to_string(
+                "/sys/devices/system/cpu/vulnerabilities/meltdown",
+            )
+            .ok(),
+            sysctls: Sysctls::read(Path::new("/proc/sys")),
+        };
+        Self::parse(
+            config.as_ref().map(|(path, config)| (path.as_path(), config)),
+            Some(&runtime),
+        )
+    }
+    fn parse(
+        config: Option<(&Path, &KernelConfig)>,
+        runtime: Option<&Runtime>,
+    ) -> Self {
+        let option =
+            |names: &[&str]| config.map(|(_, config)| config.enabled(names));
+        let cpu_flag = |flag: &str| {
+            runtime.and_then(|runtime| {
+                Some(
+                    runtime
+                        .cpu_flags
+                        .as_ref()?
+                        .split_whitespace()
+                        .any(|f| f == flag),
+                )
+            })
+        };
+        let kaslr = match (option(&["RANDOMIZE_BASE"]), runtime) {
+            (Some(true), Some(runtime)) => Some(
+                !runtime
+                    .cmdline
+                    .split_whitespace()
+                    .any(|arg| arg == "nokaslr"),
+            ),
+            (kaslr, _) => kaslr,
+        };
+        let pti = match runtime.and_then(|runtime| runtime.meltdown.as_deref())
+        {
+            Some(state) => Some(
+                state.starts_with("Not affected")
+                    || state.starts_with("Mitigation"),
+            ),
+            None => option(&[
+                "PAGE_TABLE_ISOLATION",
+                "MITIGATION_PAGE_TABLE_ISOLATION",
+                "UNMAP_KERNEL_AT_EL0",
+            ]),
+        };
+        Self {
+            version: runtime
+                .map(|runtime| runtime.release.clone())
+                .filter(|release| !release.is_empty())
+                .or_else(|| config.and_then(|(_, c)| c.version.clone())),
+            config: config.map(|(path, _)| path.to_path_buf()),
+            kaslr,
+            stack_protector: config
+                .map(|(_, config)| config.stack_protector()),
+            strict_kernel_rwx: option(&["STRICT_KERNEL_RWX", "DEBUG_RODATA"]),
+            strict_module_rwx: option(&[
+                "STRICT_MODULE_RWX",
+                "DEBUG_SET_MODULE_RONX",
+            ]),
+            hardened_usercopy: option(&["HARDENED_USERCOPY"]),
+            fortify_source: option(&["FORTIFY_SOURCE"]),
+            vmap_stack: option(&["VMAP_STACK"]),
+            slab_freelist_random: option(&["SLAB_FREELIST_RANDOM"]),
+            slab_freelist_hardened: option(&["SLAB_FREELIST_HARDENED"]),
+            strict_devmem: option(&["STRICT_DEVMEM"]),
+            module_sig_force: option(&["MODULE_SIG_FORCE"]),
+            smap: cpu_flag("smap"),
+            smep: cpu_flag("smep"),
+            pti,
+            sysctls: runtime
+                .map(|runtime| runtime.sysctls.clone())
+                .unwrap_or_default(),
+        }
+    }
+    /// name and value of the build options, for the report
+    fn options(&self) -> [(&'static str, Option<bool>); 14] {
+        [
+            ("KASLR", self.kaslr),
+            (
+                "Stack Protector",
+                self.stack_protector.map(|sp| sp >= StackProtector::Basic),
+            ),
+            ("Strict RWX", self.strict_kernel_rwx),
+            ("Module RWX", self.strict_module_rwx),
+            ("Hardened Usercopy", self.hardened_usercopy),
+            ("Fortify", self.fortify_source),
+            ("VMAP Stack", self.vmap_stack),
+            ("Freelist Random", self.slab_freelist_random),
+            ("Freelist Hardened", self.slab_freelist_hardened),
+            ("Strict devmem", self.strict_devmem),
+            ("Module Sig Force", self.module_sig_force),
+            ("SMAP", self.smap),
+            ("SMEP", self.smep),
+            ("PTI", self.pti),
+        ]
+    }
+}
+
+/// state of the running kernel next to its config
+struct Runtime {
+    release: String,
+    cmdline: String,
+    cpu_flags: Option<String>,
+    meltdown: Option<String>,
+    sysctls: Sysctls,
+}
+
+impl fmt::Display for CheckSecResults {
+    #[cfg(not(feature = "color"))]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Kernel: {} |",
+            self.version.as_deref().unwrap_or("unknown")
+        )?;
+        for (name, value) in self.options() {
+            match (name, value) {
+                ("Stack Protector", Some(_)) => write!(
+                    f,
+                    " {name}: {:?}",
+                    self.stack_protector.unwrap_or(StackProtector::None)
+                )?,
+                (_, Some(value)) => write!(f, " {name}: {value}")?,
+                (_, None) => write!(f, " {name}: N/A")?,
+            }
+        }
+        if self.sysctls != Sysctls::default() {
+            write!(f, " |")?;
+        }
+        for (name, value, _) in self.sysctls.entries() {
+            if let Some(value) = value {
+                write!(f, " {name}: {value}")?;
+            }
+        }
+        if let Some(config) = &self.config {
+            write!(f, " | Config: {}", config.display())?;
+        }
+        Ok(())
+    }
+    #[cfg(feature = "color")]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} |",
+            "Kernel:".bold(),
+            self.version.as_deref().unwrap_or("unknown")
+        )?;
+        for (name, value) in self.options() {
+            let label = format!("{name}:");
+            match (name, value) {
+                ("Stack Protector", Some(enabled)) => {
+                    let level = format!(
+                        "{:?}",
+                        self.stack_protector.unwrap_or(StackProtector::None)
+                    );
+                    write!(
+                        f,
+                        " {} {}",
+                        label.bold(),
+                        if enabled { level.green() } else { level.red() }
+                    )?;
+                }
+                (_, Some(value)) => {
+                    write!(f, " {} {}", label.bold(), colorize_bool!(value))?;
+                }
+                (_, None) => {
+                    write!(f, " {} {}", label.bold(), "N/A".yellow())?;
+                }
+            }
+        }
+        if self.sysctls != Sysctls::default() {
+            write!(f, " |")?;
+        }
+        for (name, value, hardened) in self.sysctls.entries() {
+            if let Some(value) = value {
+                let label = format!("{name}:");
+                let value = value.to_string();
+                write!(
+                    f,
+                    " {} {}",
+                    label.bold(),
+                    if value.parse::<i64>().is_ok_and(|v| v >= hardened) {
+                        value.green()
+                    } else {
+                        value.red()
+                    }
+                )?;
+            }
+        }
+        if let Some(config) = &self.config {
+            write!(f, " | {} {}", "Config:".bold(), config.display())?;
+        }
+        Ok(())
+    }
+}
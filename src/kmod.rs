@@ -0,0 +1,311 @@
+//! Implements checks of Linux kernel modules (`.ko`)
+#[cfg(feature = "color")]
+use colored::Colorize;
+use goblin::elf::header::{EM_386, EM_X86_64, ET_REL};
+use goblin::elf::section_header::SHF_EXECINSTR;
+use goblin::elf::sym::STT_FUNC;
+use goblin::elf::Elf;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[cfg(feature = "color")]
+use crate::colorize_bool;
+use crate::provenance;
+
+const SIGNATURE_MAGIC: &[u8] = b"~Module signature appended~\n";
+// `struct module_signature` in front of the magic
+const SIGNATURE_INFO_SIZE: usize = 12;
+const PKEY_ID_PGP: u8 = 0;
+const PKEY_ID_X509: u8 = 1;
+const PKEY_ID_PKCS7: u8 = 2;
+
+const ENDBR64: [u8; 4] = [0xf3, 0x0f, 0x1e, 0xfa];
+const ENDBR32: [u8; 4] = [0xf3, 0x0f, 0x1e, 0xfb];
+
+// licenses `license_is_gpl_compatible()` accepts
+static GPL_COMPATIBLE: [&str; 6] = [
+    "GPL",
+    "GPL v2",
+    "GPL and additional rights",
+    "Dual BSD/GPL",
+    "Dual MIT/GPL",
+    "Dual MPL/GPL",
+];
+
+// DER encoded OIDs (tag, length and value) of the signature and digest
+// algorithms in a PKCS#7 module signature
+static SIGNATURE_OIDS: [(&[u8], &str); 3] = [
+    (b"\x06\x09\x2a\x86\x48\x86\xf7\x0d\x01\x01", "RSA"),
+    (b"\x06\x07\x2a\x86\x48\xce\x3d\x02\x01", "ECDSA"),
+    (b"\x06\x08\x2a\x86\x48\xce\x3d\x04\x03", "ECDSA"),
+];
+static DIGEST_OIDS: [(&[u8], &str); 8] = [
+    (b"\x06\x05\x2b\x0e\x03\x02\x1a", "SHA-1"),
+    (b"\x06\x09\x60\x86\x48\x01\x65\x03\x04\x02\x04", "SHA-224"),
+    (b"\x06\x09\x60\x86\x48\x01\x65\x03\x04\x02\x01", "SHA-256"),
+    (b"\x06\x09\x60\x86\x48\x01\x65\x03\x04\x02\x02", "SHA-384"),
+    (b"\x06\x09\x60\x86\x48\x01\x65\x03\x04\x02\x03", "SHA-512"),
+    (b"\x06\x09\x60\x86\x48\x01\x65\x03\x04\x02\x08", "SHA3-256"),
+    (b"\x06\x09\x60\x86\x48\x01\x65\x03\x04\x02\x09", "SHA3-384"),
+    (b"\x06\x09\x60\x86\x48\x01\x65\x03\x04\x02\x0a", "SHA3-512"),
+];
+// `enum hash_algo` values of the pre-PKCS#7 signature format
+static HASH_ALGOS: [&str; 8] = [
+    "MD4",
+    "MD5",
+    "SHA-1",
+    "RIPEMD-160",
+    "SHA-256",
+    "SHA-384",
+    "SHA-512",
+    "SHA-224",
+];
+
+/// Kernel taint flags a module causes when loaded
+#[derive(
+    Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd,
+)]
+pub enum Taint {
+    /// `P`, license missing or not GPL compatible
+    Proprietary,
+    /// `C`, staging driver
+    Staging,
+    /// `O`, built outside the kernel tree
+    OutOfTree,
+    /// `E`, unsigned module on a kernel not enforcing signatures
+    Unsigned,
+}
+
+impl fmt::Display for Taint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Proprietary => "P",
+                Self::Staging => "C",
+                Self::OutOfTree => "O",
+                Self::Unsigned => "E",
+            }
+        )
+    }
+}
+
+/// Appended module signature (`scripts/sign-file`)
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
+pub struct ModuleSignature {
+    /// `PKCS#7`, or `X.509`/`PGP` of old kernels
+    pub format: String,
+    /// public key algorithm
+    pub algorithm: Option<String>,
+    /// digest algorithm
+    pub hash: Option<String>,
+    /// size of the signature
+    pub size: usize,
+}
+
+impl ModuleSignature {
+    /// Parse the `~Module signature appended~` trailer at the end of the
+    /// file
+    #[must_use]
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        let rest = bytes.strip_suffix(SIGNATURE_MAGIC)?;
+        let info = rest.get(rest.len().checked_sub(SIGNATURE_INFO_SIZE)?..)?;
+        let (algo, hash, id_type) = (info[0], info[1], info[2]);
+        let size = u32::from_be_bytes(info[8..12].try_into().ok()?);
+        let size = usize::try_from(size).ok()?;
+        let end = rest.len() - SIGNATURE_INFO_SIZE;
+        let signature = rest.get(end.checked_sub(size)?..end)?;
+        let find = |oids: &[(&[u8], &str)]| {
+            oids.iter().find_map(|(oid, name)| {
+                signature
+                    .windows(oid.len())
+                    .any(|window| window == *oid)
+                    .then(|| (*name).to_string())
+            })
+        };
+        let (format, algorithm, hash) = match id_type {
+            PKEY_ID_PKCS7 => {
+                ("PKCS#7", find(&SIGNATURE_OIDS), find(&DIGEST_OIDS))
+            }
+            PKEY_ID_PGP | PKEY_ID_X509 => (
+                if id_type == PKEY_ID_PGP { "PGP" } else { "X.509" },
+                match algo {
+                    0 => Some("DSA".to_string()),
+                    1 => Some("RSA".to_string()),
+                    _ => None,
+                },
+                HASH_ALGOS.get(usize::from(hash)).map(ToString::to_string),
+            ),
+            _ => return None,
+        };
+        Some(Self { format: format.to_string(), algorithm, hash, size })
+    }
+}
+
+impl fmt::Display for ModuleSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.format)?;
+        for name in [&self.algorithm, &self.hash].into_iter().flatten() {
+            write!(f, " {name}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Linux kernel module properties
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
+pub struct KernelModule {
+    /// module name (`.modinfo` `name=`)
+    pub name: Option<String>,
+    /// kernel release and options the module was built for
+    pub vermagic: Option<String>,
+    /// `MODULE_LICENSE()`
+    pub license: Option<String>,
+    /// built with retpolines (`CONFIG_MITIGATION_RETPOLINE`)
+    pub retpoline: bool,
+    /// symbol version CRCs (`__versions`, `CONFIG_MODVERSIONS`)
+    pub modversions: bool,
+    /// appended signature
+    pub signature: Option<ModuleSignature>,
+    /// kCFI type hashes in front of functions (`__cfi_*`,
+    /// `CONFIG_CFI_CLANG`)
+    pub cfi: bool,
+    /// functions start with `endbr64`/`endbr32` (`CONFIG_X86_KERNEL_IBT`)
+    pub ibt: bool,
+    /// taint flags set when loading the module
+    pub taints: Vec<Taint>,
+}
+
+impl KernelModule {
+    /// Parse relocatable objects with a `.modinfo` or
+    /// `.gnu.linkonce.this_module` section, returns `None` for anything
+    /// else
+    #[must_use]
+    pub fn parse(elf: &Elf, bytes: &[u8]) -> Option<Self> {
+        let section = |name: &str| {
+            elf.section_headers
+                .iter()
+                .any(|sh| elf.shdr_strtab.get_at(sh.sh_name) == Some(name))
+        };
+        if elf.header.e_type != ET_REL
+            || !(section(".modinfo") || section(".gnu.linkonce.this_module"))
+        {
+            return None;
+        }
+        let modinfo = provenance::section_strings(elf, bytes, ".modinfo");
+        let tag = |tag: &str| {
+            modinfo.iter().find_map(|entry| {
+                let (key, value) = entry.split_once('=')?;
+                (key == tag).then(|| value.to_string())
+            })
+        };
+        let license = tag("license");
+        let signature = ModuleSignature::parse(bytes);
+        let mut taints = Vec::new();
+        if !license.as_deref().is_some_and(|l| GPL_COMPATIBLE.contains(&l)) {
+            taints.push(Taint::Proprietary);
+        }
+        if tag("staging").as_deref() == Some("Y") {
+            taints.push(Taint::Staging);
+        }
+        if tag("intree").as_deref() != Some("Y") {
+            taints.push(Taint::OutOfTree);
+        }
+        if signature.is_none() {
+            taints.push(Taint::Unsigned);
+        }
+        Some(Self {
+            name: tag("name"),
+            vermagic: tag("vermagic").map(|v| v.trim_end().to_string()),
+            retpoline: tag("retpoline").as_deref() == Some("Y"),
+            license,
+            modversions: section("__versions"),
+            signature,
+            cfi: elf.syms.iter().any(|sym| {
+                elf.strtab
+                    .get_at(sym.st_name)
+                    .is_some_and(|name| name.starts_with("__cfi_"))
+            }),
+            ibt: has_endbr(elf, bytes),
+            taints,
+        })
+    }
+}
+
+/// whether any function of an x86/x64 module starts with an `endbr`
+fn has_endbr(elf: &Elf, bytes: &[u8]) -> bool {
+    let endbr = match elf.header.e_machine {
+        EM_X86_64 => ENDBR64,
+        EM_386 => ENDBR32,
+        _ => return false,
+    };
+    elf.syms.iter().filter(|sym| sym.st_type() == STT_FUNC).any(|sym| {
+        elf.section_headers
+            .get(sym.st_shndx)
+            .filter(|sh| sh.sh_flags & u64::from(SHF_EXECINSTR) != 0)
+            .and_then(|sh| {
+                let offset = sh.sh_offset.checked_add(sym.st_value)?;
+                let offset = usize::try_from(offset).ok()?;
+                bytes.get(offset..offset.checked_add(endbr.len())?)
+            })
+            .is_some_and(|code| code == endbr)
+    })
+}
+
+impl fmt::Display for KernelModule {
+    #[cfg(not(feature = "color"))]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Module: {} Vermagic: {} Signature: {} Retpoline Flag: {} kCFI: {} \
+            IBT: {} License: {}",
+            self.name.as_deref().unwrap_or("?"),
+            self.vermagic.as_deref().unwrap_or("None"),
+            self.signature
+                .as_ref()
+                .map_or_else(|| "None".to_string(), ToString::to_string),
+            self.retpoline,
+            self.cfi,
+            self.ibt,
+            self.license.as_deref().unwrap_or("None"),
+        )?;
+        if !self.taints.is_empty() {
+            let taints: String =
+                self.taints.iter().map(ToString::to_string).collect();
+            write!(f, " Taints: {taints}")?;
+        }
+        Ok(())
+    }
+    #[cfg(feature = "color")]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {} {} {} {} {} {} {} {} {} {}",
+            "Module:".bold(),
+            self.name.as_deref().unwrap_or("?"),
+            "Vermagic:".bold(),
+            self.vermagic.as_deref().unwrap_or("None"),
+            "Signature:".bold(),
+            self.signature.as_ref().map_or_else(
+                || "None".red().to_string(),
+                |signature| signature.to_string().green().to_string()
+            ),
+            "Retpoline Flag:".bold(),
+            colorize_bool!(self.retpoline),
+            "kCFI:".bold(),
+            colorize_bool!(self.cfi),
+            "IBT:".bold(),
+            colorize_bool!(self.ibt),
+            "License:".bold(),
+            self.license.as_deref().unwrap_or("None"),
+        )?;
+        if !self.taints.is_empty() {
+            let taints: String =
+                self.taints.iter().map(ToString::to_string).collect();
+            write!(f, " {} {}", "Taints:".bold(), taints.red())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,205 @@
+//! Implements source language and toolchain detection for ELF binaries
+use goblin::elf::Elf;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::provenance::section_data;
+
+const GO_BUILDINFO_MAGIC: &[u8] = b"\xff Go buildinf:";
+// buildinfo holds the strings inline rather than pointers to them (Go 1.18+)
+const GO_BUILDINFO_FLAG_INLINE: u8 = 0x2;
+// modinfo is wrapped in these 16 byte sentinels
+const GO_MODINFO_SENTINEL_LEN: usize = 16;
+
+static RUST_SYMBOLS: [&str; 4] =
+    ["rust_begin_unwind", "rust_panic", "__rust_alloc", "__rust_probestack"];
+static CGO_SYMBOLS: [&str; 3] = ["_cgo_init", "x_cgo_init", "crosscall2"];
+
+/// Source language a binary was (mainly) written in
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq)]
+pub enum Language {
+    /// C, C++ or anything not recognized as Rust or Go
+    C,
+    Rust,
+    Go,
+}
+
+impl Language {
+    /// Detect the language from Go build info, the `.rustc` section,
+    /// `rustc` `.comment` strings and Rust runtime symbols
+    #[must_use]
+    pub fn detect(elf: &Elf, bytes: &[u8], comment: &[String]) -> Self {
+        if section_data(elf, bytes, ".go.buildinfo").is_some()
+            || section_data(elf, bytes, ".note.go.buildid").is_some()
+        {
+            return Self::Go;
+        }
+        if section_data(elf, bytes, ".rustc").is_some()
+            || comment.iter().any(|c| c.starts_with("rustc version"))
+            || has_symbol(elf, &RUST_SYMBOLS)
+        {
+            return Self::Rust;
+        }
+        Self::C
+    }
+    /// whether the language is memory safe, making C oriented checks such as
+    /// stack canaries and `FORTIFY_SOURCE` irrelevant to its own code
+    #[must_use]
+    pub fn is_memory_safe(self) -> bool {
+        matches!(self, Self::Rust | Self::Go)
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::C => "C/C++",
+                Self::Rust => "Rust",
+                Self::Go => "Go",
+            }
+        )
+    }
+}
+
+/// Go module dependency
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
+pub struct GoModule {
+    pub path: String,
+    pub version: String,
+}
+
+/// Go build information from the `.go.buildinfo` section
+#[derive(Clone, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
+pub struct GoBuildInfo {
+    /// Go toolchain version
+    pub version: String,
+    /// main package path
+    pub path: Option<String>,
+    /// main module
+    pub main: Option<GoModule>,
+    /// module dependencies
+    pub deps: Vec<GoModule>,
+    /// build settings such as `-buildmode` or `CGO_ENABLED`
+    pub settings: Vec<(String, String)>,
+}
+
+impl GoBuildInfo {
+    /// Parse the `.go.buildinfo` section, returns `None` if there is none or
+    /// it predates the Go 1.18 inline string format
+    #[must_use]
+    pub fn parse(elf: &Elf, bytes: &[u8]) -> Option<Self> {
+        let data = section_data(elf, bytes, ".go.buildinfo")?;
+        let header = data.get(..32)?;
+        if !header.starts_with(GO_BUILDINFO_MAGIC)
+            || header[15] & GO_BUILDINFO_FLAG_INLINE == 0
+        {
+            return None;
+        }
+        let mut rest = &data[32..];
+        let version = read_string(&mut rest)?;
+        let modinfo = read_string(&mut rest).unwrap_or_default();
+        let mut info = Self {
+            version: String::from_utf8_lossy(version).into_owned(),
+            ..Self::default()
+        };
+        if modinfo.len() >= 2 * GO_MODINFO_SENTINEL_LEN {
+            let modinfo = &modinfo[GO_MODINFO_SENTINEL_LEN
+                ..modinfo.len() - GO_MODINFO_SENTINEL_LEN];
+            info.parse_modinfo(&String::from_utf8_lossy(modinfo));
+        }
+        Some(info)
+    }
+    fn parse_modinfo(&mut self, modinfo: &str) {
+        for line in modinfo.lines() {
+            let mut fields = line.split('\t');
+            let module = |fields: &mut std::str::Split<'_, char>| {
+                Some(GoModule {
+                    path: fields.next()?.to_string(),
+                    version: fields.next().unwrap_or_default().to_string(),
+                })
+            };
+            match fields.next() {
+                Some("path") => {
+                    self.path = fields.next().map(ToString::to_string);
+                }
+                Some("mod") => self.main = module(&mut fields),
+                Some("dep") => self.deps.extend(module(&mut fields)),
+                Some("build") => {
+                    if let Some((key, value)) =
+                        fields.next().and_then(|s| s.split_once('='))
+                    {
+                        self.settings
+                            .push((key.to_string(), value.to_string()));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    /// value of a build setting
+    #[must_use]
+    pub fn setting(&self, key: &str) -> Option<&str> {
+        self.settings.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+    /// `-buildmode` the binary was built with
+    #[must_use]
+    pub fn build_mode(&self) -> Option<&str> {
+        self.setting("-buildmode")
+    }
+    /// whether cgo was enabled, i.e. C code may be linked in
+    #[must_use]
+    pub fn cgo_enabled(&self) -> bool {
+        self.setting("CGO_ENABLED") == Some("1")
+    }
+}
+
+impl fmt::Display for GoBuildInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (buildmode: {}, cgo: {})",
+            self.version,
+            self.build_mode().unwrap_or("?"),
+            self.cgo_enabled()
+        )
+    }
+}
+
+/// check for symbols of the cgo runtime glue
+#[must_use]
+pub fn has_cgo_symbols(elf: &Elf) -> bool {
+    has_symbol(elf, &CGO_SYMBOLS)
+}
+
+fn has_symbol(elf: &Elf, names: &[&str]) -> bool {
+    elf.syms
+        .iter()
+        .filter_map(|sym| elf.strtab.get_at(sym.st_name))
+        .chain(
+            elf.dynsyms
+                .iter()
+                .filter_map(|sym| elf.dynstrtab.get_at(sym.st_name)),
+        )
+        .any(|name| names.contains(&name))
+}
+
+/// read a uvarint length prefixed string
+fn read_string<'a>(data: &mut &'a [u8]) -> Option<&'a [u8]> {
+    let mut len: usize = 0;
+    let mut shift = 0;
+    loop {
+        let (&byte, rest) = data.split_first()?;
+        *data = rest;
+        len |= usize::from(byte & 0x7f).checked_shl(shift)?;
+        if byte & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
+    }
+    let (string, rest) = (data.get(..len)?, data.get(len..)?);
+    *data = rest;
+    Some(string)
+}
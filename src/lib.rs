@@ -49,22 +49,41 @@ use goblin::{Object};
 use goblin::mach::Mach;
 use serde_derive::{Deserialize, Serialize};
 
-#[cfg(feature = "disassembly")]
+pub mod debuginfo;
 pub mod disassembly;
+#[cfg(feature = "pe")]
+pub mod dotnet;
 #[cfg(feature = "elf")]
 pub mod elf;
+pub mod functions;
+#[cfg(feature = "elf")]
+pub mod kernel;
+#[cfg(feature = "elf")]
+pub mod kmod;
+#[cfg(feature = "elf")]
+pub mod language;
 #[cfg(target_os = "linux")]
 pub mod ldso;
 #[cfg(feature = "macho")]
 pub mod macho;
+pub mod compression;
 pub mod macros;
 pub mod output;
 #[cfg(feature = "pe")]
 pub mod pe;
+#[cfg(feature = "elf")]
+pub mod provenance;
+#[cfg(feature = "pe")]
+pub mod resource;
+#[cfg(feature = "pe")]
+pub mod rich;
+pub mod sanitizer;
 #[cfg(feature = "shared")]
 #[macro_use]
 pub mod shared;
 
+pub use shared::BinResults;
+
 #[derive(Serialize, Deserialize)]
 struct ChecksecResult{
     binresult: shared::BinResults,
@@ -72,6 +91,8 @@ struct ChecksecResult{
 }
 
 // This is ugly, refactor :)
+/// # Errors
+/// Returns the serialized parse or compression error message.
 #[wasm_bindgen]
 pub fn checksec (buffer: &[u8]) -> Result<JsValue, JsValue> {
     match checksec_core(buffer) {
@@ -88,6 +109,8 @@ pub fn checksec (buffer: &[u8]) -> Result<JsValue, JsValue> {
 } 
 
 
+/// # Errors
+/// Returns the serialized decompression error message.
 #[wasm_bindgen]
 pub fn checksec_decompress (buffer: &[u8]) -> Result<JsValue, JsValue> {
     match shared::decompress_results(buffer) {
@@ -96,14 +119,17 @@ pub fn checksec_decompress (buffer: &[u8]) -> Result<JsValue, JsValue> {
     }
 } 
 
+/// # Errors
+/// Returns the goblin parse error message, or a message for unsupported
+/// file types.
 pub fn checksec_core (buffer: &[u8]) -> Result<shared::BinResults, String> {
-    match Object::parse(&buffer){
+    match Object::parse(buffer){
         Ok(Object::Elf(elf)) => {
-            let result = elf::CheckSecResults::parse(&elf, &buffer);
+            let result = elf::CheckSecResults::parse(&elf, buffer);
             Ok(shared::BinResults::Elf(result))
         },
         Ok(Object::PE(pe)) => {
-            let result = pe::CheckSecResults::parse(&pe, &buffer);
+            let result = pe::CheckSecResults::parse(&pe, buffer);
             Ok(shared::BinResults::Pe(result))
         },
         Ok(Object::Mach(mach)) => match mach {
@@ -111,7 +137,7 @@ pub fn checksec_core (buffer: &[u8]) -> Result<shared::BinResults, String> {
                 let result = macho::CheckSecResults::parse(&mach); 
                 Ok(shared::BinResults::Macho(result))
             }
-            _ => { Err("fat binaries currently not supported".into()) }
+            Mach::Fat(_) => { Err("fat binaries currently not supported".into()) }
         },
         Err(res) => {Err(res.to_string())},
         _ => {  Err("unsupported file type".into()) }
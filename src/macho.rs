@@ -2,8 +2,11 @@
 #[cfg(feature = "color")]
 use colored::Colorize;
 use goblin::mach::load_command::CommandVariant;
+use goblin::mach::symbols::{Nlist, N_EXT, N_SECT};
 use goblin::mach::MachO;
 use serde::{Deserialize, Serialize};
+use crate::debuginfo::{self, DebugInfo, Stripped};
+use crate::sanitizer::VecSanitizer;
 use crate::shared::{Rpath, VecRpath};
 use std::fmt;
 
@@ -55,9 +58,14 @@ pub struct CheckSecResults {
     pub pie: bool,
     /// Restrict segment
     pub restrict: bool,
+    /// Sanitizer, coverage and fuzzing instrumentation
+    /// (*CFLAGS=*`-fsanitize=address,undefined,...`, `--coverage`)
+    pub sanitizers: VecSanitizer,
     /// Load Command @rpath
     //rpath: VecRpath,
     pub rpath: VecRpath,
+    /// Debug info and symbol stripping
+    pub debug_info: DebugInfo,
 }
 impl CheckSecResults {
     #[must_use]
@@ -73,7 +81,9 @@ impl CheckSecResults {
             nx_stack: macho.has_nx_stack(),
             pie: macho.has_pie(),
             restrict: macho.has_restrict(),
+            sanitizers: macho.has_sanitizers(),
             rpath: macho.has_rpath(),
+            debug_info: get_debug_info(macho),
         }
     }
 }
@@ -86,7 +96,7 @@ impl fmt::Display for CheckSecResults {
             f,
             "ARC: {} Canary: {} Code Signature: {} Encryption: {} \
             Fortify: {} Fortified {:2} NX Heap: {} \
-            NX Stack: {} PIE: {} Restrict: {} RPath: {}",
+            NX Stack: {} PIE: {} Restrict: {} Sanitizers: {} RPath: {}",
             self.arc,
             self.canary,
             self.code_signature,
@@ -97,8 +107,10 @@ impl fmt::Display for CheckSecResults {
             self.nx_stack,
             self.pie,
             self.restrict,
+            self.sanitizers,
             self.rpath
-        )
+        )?;
+        write!(f, " Stripped: {}", self.debug_info.stripped)
     }
     #[cfg(feature = "color")]
     /// Colorized human readable format output
@@ -106,7 +118,7 @@ impl fmt::Display for CheckSecResults {
         write!(
             f,
             "{} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} \
-            {} {} {} {} {} {}",
+            {} {} {} {} {} {} {} {}",
             "ARC:".bold(),
             colorize_bool!(self.arc),
             "Canary:".bold(),
@@ -127,9 +139,70 @@ impl fmt::Display for CheckSecResults {
             colorize_bool!(self.pie),
             "Restrict:".bold(),
             colorize_bool!(self.restrict),
+            "Sanitizers:".bold(),
+            self.sanitizers,
             "RPath:".bold(),
             self.rpath
-        )
+        )?;
+        write!(f, " {} {}", "Stripped:".bold(), self.debug_info.stripped)
+    }
+}
+
+/// `LC_UUID` in the `dwarfdump --uuid` format
+fn get_uuid(macho: &MachO) -> Option<String> {
+    macho.load_commands.iter().find_map(|loadcmd| {
+        let CommandVariant::Uuid(cmd) = loadcmd.command else {
+            return None;
+        };
+        let hex: Vec<String> =
+            cmd.uuid.iter().map(|b| format!("{b:02X}")).collect();
+        Some(format!(
+            "{}-{}-{}-{}-{}",
+            hex[..4].concat(),
+            hex[4..6].concat(),
+            hex[6..8].concat(),
+            hex[8..10].concat(),
+            hex[10..].concat()
+        ))
+    })
+}
+
+fn get_debug_info(macho: &MachO) -> DebugInfo {
+    let mut debug_sections = Vec::new();
+    let mut dwarf_version = None;
+    for (section, data) in macho.segments.sections().flatten().flatten() {
+        let (Ok(segname), Ok(sectname)) = (section.segname(), section.name())
+        else {
+            continue;
+        };
+        if segname != "__DWARF" {
+            continue;
+        }
+        if sectname == "__debug_info" {
+            dwarf_version =
+                debuginfo::dwarf_version(data, macho.little_endian);
+        }
+        debug_sections.push(sectname.to_string());
+    }
+    let symbols: Vec<Nlist> =
+        macho.symbols().flatten().map(|(_, nlist)| nlist).collect();
+    let stabs = symbols.iter().any(Nlist::is_stab);
+    // exported symbols survive `strip`, local ones do not
+    let local_symbols = symbols.iter().any(|nlist| {
+        !nlist.is_stab() && nlist.n_type & N_EXT == 0 && nlist.get_type() == N_SECT
+    });
+    DebugInfo {
+        stripped: Stripped::new(
+            local_symbols,
+            stabs || !debug_sections.is_empty(),
+        ),
+        symbols: local_symbols,
+        debug_sections,
+        dwarf_version,
+        stabs,
+        debug_file: None,
+        alt_debug_file: None,
+        debug_id: get_uuid(macho),
     }
 }
 
@@ -173,6 +246,9 @@ pub trait Properties {
     fn has_pie(&self) -> bool;
     /// check for `___restrict` segment name
     fn has_restrict(&self) -> bool;
+    /// check symbols and linked libraries for sanitizer, coverage and
+    /// fuzzing runtimes
+    fn has_sanitizers(&self) -> VecSanitizer;
     //fn has_rpath(&self) -> VecRpath;
     /// check for `RPath` in load commands
     fn has_rpath(&self) -> VecRpath;
@@ -202,15 +278,11 @@ impl Properties for MachO<'_> {
     fn has_encrypted(&self) -> bool {
         for loadcmd in &self.load_commands {
             match loadcmd.command {
-                CommandVariant::EncryptionInfo32(cmd) => {
-                    if cmd.cryptid != 0 {
-                        return true;
-                    }
+                CommandVariant::EncryptionInfo32(cmd) if cmd.cryptid != 0 => {
+                    return true;
                 }
-                CommandVariant::EncryptionInfo64(cmd) => {
-                    if cmd.cryptid != 0 {
-                        return true;
-                    }
+                CommandVariant::EncryptionInfo64(cmd) if cmd.cryptid != 0 => {
+                    return true;
                 }
                 _ => (),
             }
@@ -253,6 +325,11 @@ impl Properties for MachO<'_> {
         }
         false
     }
+    fn has_sanitizers(&self) -> VecSanitizer {
+        let symbols: Vec<&str> =
+            self.symbols().flatten().map(|(name, _)| name).collect();
+        VecSanitizer::detect(symbols, self.libs.iter().copied())
+    }
     fn has_rpath(&self) -> VecRpath {
         let mut paths = Vec::new();
         for &rpath in &self.rpaths {
@@ -2,6 +2,14 @@
 #[macro_export]
 #[cfg(feature = "color")]
 macro_rules! colorize_bool {
+    // for settings that weaken a mitigation when `true`
+    (inverted $tf:expr) => {
+        if $tf {
+            format!("{:<5}", $tf).red().to_string()
+        } else {
+            format!("{:<5}", $tf).bright_green().to_string()
+        }
+    };
     ($tf:expr) => {
         if $tf {
             format!("{:<5}", $tf).bright_green().to_string()
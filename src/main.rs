@@ -18,7 +18,6 @@ use goblin::error::Error;
 #[cfg(feature = "macho")]
 use goblin::mach::{Mach, SingleArch::Archive, SingleArch::MachO};
 use goblin::Object;
-use ignore::Walk;
 #[cfg(all(feature = "maps", target_os = "linux"))]
 use itertools::Itertools;
 use memmap2::Mmap;
@@ -38,7 +37,11 @@ use std::io::ErrorKind;
 use std::os::unix::fs::PermissionsExt;
 use std::path::Path;
 use std::path::PathBuf;
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+use std::sync::OnceLock;
 use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 use std::{env, fmt, fs, process};
 
 #[cfg(feature = "color")]
@@ -48,13 +51,20 @@ use colored::{ColoredString, Colorize};
 use colored_json::to_colored_json_auto;
 
 mod binary;
+mod cache;
+mod filter;
+mod privilege;
 mod proc;
+#[cfg(feature = "watch")]
+mod watch;
 
 use binary::{BinSpecificProperties, BinType, Binary, Blob};
 use proc::{Process, Processes};
 
 #[cfg(feature = "elf")]
 use checksec::elf;
+#[cfg(feature = "elf")]
+use checksec::kernel;
 #[cfg(all(target_os = "linux", feature = "elf"))]
 use checksec::elf::LibraryLookup;
 #[cfg(all(target_os = "linux", feature = "elf"))]
@@ -64,6 +74,8 @@ use checksec::macho;
 use checksec::output;
 #[cfg(feature = "pe")]
 use checksec::pe;
+#[cfg(feature = "pe")]
+use checksec::resource;
 #[cfg(all(target_os = "linux", feature = "elf"))]
 use checksec::shared::VecRpath;
 use checksec::{bold, underline};
@@ -78,15 +90,9 @@ fn print_filename(file: &Path) -> ColoredString {
     match std::fs::metadata(file) {
         Ok(md) => {
             #[cfg(target_os = "linux")]
-            fn has_filecaps(file: &Path) -> bool {
-                xattr::get(file, "security.capability")
-                    .unwrap_or(None)
-                    .is_some()
-            }
+            let has_filecaps = privilege::has_capabilities;
             #[cfg(not(target_os = "linux"))]
-            fn has_filecaps(_file: &Path) -> bool {
-                false
-            }
+            let has_filecaps = |_file: &Path| false;
 
             let mode = md.permissions().mode();
             if mode & 0o4000 == 0o4000 {
@@ -140,20 +146,25 @@ fn print_binary_results(binaries: &[Binary], settings: &output::Settings) {
 
                 for blob in &binary.blobs {
                     println!(
-                        "{}: | {} | {} {}",
-                        blob.binarytype,
-                        blob.properties,
+                        "{} {} {}",
+                        blob,
                         underline!(bold!("File:")),
                         print_filename(&binary.file)
                     );
                 }
+                if let Some(privileges) = binary
+                    .privileges
+                    .as_ref()
+                    .filter(|privileges| privileges.is_notable())
+                {
+                    println!(" \u{21aa} {privileges}");
+                }
                 if settings.libraries {
                     for library in &binary.libraries {
                         for blob in &library.blobs {
                             println!(
-                                "{}: | {} | {} {}",
-                                blob.binarytype,
-                                blob.properties,
+                                "{} {} {}",
+                                blob,
                                 underline!(bold!("File:")),
                                 print_filename(&library.file)
                             );
@@ -165,6 +176,36 @@ fn print_binary_results(binaries: &[Binary], settings: &output::Settings) {
     }
 }
 
+#[cfg(feature = "elf")]
+fn print_kernel_results(
+    results: &kernel::CheckSecResults,
+    settings: &output::Settings,
+) {
+    match settings.format {
+        output::Format::Json => {
+            println!("{}", &json!(results));
+        }
+        output::Format::JsonPretty => {
+            #[cfg(feature = "color")]
+            if settings.color {
+                if let Ok(colored_json) = to_colored_json_auto(&json!(results))
+                {
+                    println!("{colored_json}");
+                }
+            } else if let Ok(json_str) = to_string_pretty(&json!(results)) {
+                println!("{json_str}");
+            }
+            #[cfg(not(feature = "color"))]
+            if let Ok(json_str) = to_string_pretty(&json!(results)) {
+                println!("{json_str}");
+            }
+        }
+        output::Format::Text => {
+            println!("{results}");
+        }
+    }
+}
+
 fn print_process_results(processes: &Processes, settings: &output::Settings) {
     match settings.format {
         output::Format::Json => {
@@ -197,7 +238,7 @@ fn print_process_results(processes: &Processes, settings: &output::Settings) {
 
                 for blob in &process.binary.blobs {
                     println!(
-                        "{}({})\n \u{21aa} {}: | {} | {} {}",
+                        "{}({})\n \u{21aa} {} {} {}",
                         bold!(process
                             .binary
                             .file
@@ -205,13 +246,28 @@ fn print_process_results(processes: &Processes, settings: &output::Settings) {
                             .unwrap_or_else(|| OsStr::new("n/a"))
                             .to_string_lossy()),
                         process.pid,
-                        blob.binarytype,
-                        blob.properties,
+                        blob,
                         underline!(bold!("File:")),
                         print_filename(&process.binary.file)
                     );
                 }
 
+                #[cfg(all(feature = "maps", target_os = "linux"))]
+                if let Some(properties) = &process.properties {
+                    println!(" \u{21aa} {properties}");
+                }
+                #[cfg(all(feature = "maps", feature = "elf", target_os = "linux"))]
+                if let Some(memory) = &process.memory {
+                    println!(
+                        " \u{21aa} {} 0x{:x}: | {} |",
+                        bold!("Memory"),
+                        memory.base,
+                        memory.properties
+                    );
+                    for difference in &memory.differences {
+                        println!("\t{difference}");
+                    }
+                }
                 #[cfg(all(
                     feature = "maps",
                     any(target_os = "linux", target_os = "windows")
@@ -220,9 +276,8 @@ fn print_process_results(processes: &Processes, settings: &output::Settings) {
                     for library in libraries {
                         for blob in &library.blobs {
                             println!(
-                                " \u{21aa} {}: | {} | {} {}",
-                                blob.binarytype,
-                                blob.properties,
+                                " \u{21aa} {} {} {}",
+                                blob,
                                 underline!(bold!("File:")),
                                 print_filename(&library.file)
                             );
@@ -241,6 +296,16 @@ fn print_process_results(processes: &Processes, settings: &output::Settings) {
                         }
                     }
                 }
+                #[cfg(all(feature = "maps", target_os = "linux"))]
+                if let Some(findings) = &process.findings {
+                    println!("{:>16}", "\u{21aa} Findings:");
+                    if findings.is_empty() {
+                        println!("\tNone");
+                    }
+                    for finding in findings {
+                        println!("\t{finding}");
+                    }
+                }
             }
         }
     }
@@ -308,26 +373,80 @@ impl From<LdSoError> for ParseError {
     }
 }
 
-type Cache = Arc<Mutex<HashMap<PathBuf, Vec<Binary>>>>;
+type Cache = Arc<Mutex<HashMap<PathBuf, (FileStamp, Vec<Binary>)>>>;
+
+/// version of a file on disk, cached results are only used while it is
+/// unchanged
+#[derive(Clone, PartialEq, Eq)]
+struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+    #[cfg(unix)]
+    inode: u64,
+}
+
+impl FileStamp {
+    fn new(metadata: &fs::Metadata) -> Self {
+        Self {
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+            #[cfg(unix)]
+            inode: metadata.ino(),
+        }
+    }
+}
+
+/// results of earlier runs (`--cache`)
+static RESULT_CACHE: OnceLock<cache::ResultCache> = OnceLock::new();
+
+/// write back the results cache, if there is one
+fn save_result_cache() {
+    if let Some(cache) = RESULT_CACHE.get() {
+        if let Err(err) = cache.save() {
+            eprintln!(
+                "Cannot write result cache {}: {}",
+                cache.path().display(),
+                err
+            );
+        }
+    }
+}
+
+/// user-supplied list of dangerous PE imports (`--dangerous-imports`)
+#[cfg(feature = "pe")]
+static IMPORT_POLICY: OnceLock<pe::ImportPolicy> = OnceLock::new();
 
 fn parse(
     file: &Path,
     cache: &mut Option<Cache>,
 ) -> Result<Vec<Binary>, ParseError> {
+    let fp = fs::File::open(file)?;
+    let stamp = FileStamp::new(&fp.metadata()?);
     if let Some(ref mut cache) = cache {
         let cache = cache.lock().unwrap();
-        if let Some(entry) = cache.get(file) {
+        if let Some((_, entry)) =
+            cache.get(file).filter(|(cached, _)| *cached == stamp)
+        {
             return Ok(entry.clone());
         }
     }
 
-    let fp = fs::File::open(file)?;
     let buffer = unsafe { Mmap::map(&fp)? };
 
-    let result = parse_bytes(&buffer, file)?;
+    let mut result = match RESULT_CACHE.get() {
+        Some(results) => results.get_or_parse(&buffer, file, parse_bytes)?,
+        None => parse_bytes(&buffer, file)?,
+    };
+    // shared by all members of an archive or fat binary
+    #[cfg(unix)]
+    let privileges = privilege::FilePrivileges::parse(file);
+    #[cfg(unix)]
+    for binary in &mut result {
+        binary.privileges.clone_from(&privileges);
+    }
     if let Some(ref mut cache) = cache {
         let mut cache = cache.lock().unwrap();
-        cache.insert(file.to_path_buf(), result.clone());
+        cache.insert(file.to_path_buf(), (stamp, result.clone()));
     }
 
     Ok(result)
@@ -348,13 +467,18 @@ fn parse_bytes(bytes: &[u8], file: &Path) -> Result<Vec<Binary>, ParseError> {
         }
         #[cfg(feature = "pe")]
         Object::PE(pe) => {
-            let results = pe::CheckSecResults::parse(&pe, bytes);
+            let results = pe::CheckSecResults::parse_with_policy(
+                &pe,
+                bytes,
+                IMPORT_POLICY.get_or_init(pe::ImportPolicy::default),
+            );
             let bin_type =
                 if pe.is_64 { BinType::PE64 } else { BinType::PE32 };
-            Ok(vec![Binary::new(
-                file.to_path_buf(),
-                vec![Blob::new(bin_type, BinSpecificProperties::PE(results))],
-            )])
+            let mut blob =
+                Blob::new(bin_type, BinSpecificProperties::PE(results));
+            blob.version_info = resource::Resources::parse(&pe, bytes)
+                .and_then(|resources| resources.version_info());
+            Ok(vec![Binary::new(file.to_path_buf(), vec![blob])])
         }
         #[cfg(feature = "macho")]
         Object::Mach(mach) => {
@@ -475,7 +599,7 @@ fn parse_single_file(
 fn parse_file_impl(
     file: &Path,
     _scan_dynlibs: bool,
-    _lookup: &Option<Lookup>,
+    _lookup: Option<&Lookup>,
     cache: &mut Option<Cache>,
 ) -> Result<Vec<Binary>, ParseError> {
     parse(file, cache)
@@ -524,7 +648,7 @@ fn scan_dependencies(
 fn parse_dependencies(
     binary: &mut Binary,
     lookup: &Lookup,
-    cache: &Option<Cache>,
+    cache: Option<&Cache>,
 ) {
     let mut scanned = HashSet::new();
     let mut to_scan = scan_dependencies(binary, lookup, &scanned);
@@ -533,7 +657,7 @@ fn parse_dependencies(
         let mut results: Vec<Binary> = to_scan
             .par_iter()
             .filter_map(|lib| {
-                match parse(lib, &mut cache.as_ref().map(Arc::clone)) {
+                match parse(lib, &mut cache.map(Arc::clone)) {
                     Ok(bins) => Some(bins),
                     Err(err) => {
                         eprintln!(
@@ -564,19 +688,18 @@ fn parse_dependencies(
 fn parse_file_impl(
     file: &Path,
     scan_dynlibs: bool,
-    lookup: &Option<Lookup>,
+    lookup: Option<&Lookup>,
     cache: &mut Option<Cache>,
 ) -> Result<Vec<Binary>, ParseError> {
     let mut results = parse(file, cache)?;
 
-    if !scan_dynlibs || lookup.is_none() {
-        return Ok(results);
-    }
-
-    let lookup = lookup.as_ref().unwrap();
+    let lookup = match lookup {
+        Some(lookup) if scan_dynlibs => lookup,
+        _ => return Ok(results),
+    };
 
     for result in &mut results {
-        parse_dependencies(result, lookup, cache);
+        parse_dependencies(result, lookup, cache.as_ref());
     }
 
     Ok(results)
@@ -593,47 +716,118 @@ fn parse_single_file(
 
     let lookup = Lookup { elf: LibraryLookup::new()? };
 
-    parse_file_impl(file, true, &Some(lookup), &mut None)
+    parse_file_impl(file, true, Some(&lookup), &mut None)
 }
 
-fn walk(
-    basepath: &Path,
-    scan_dynlibs: bool,
-    output_settings: &output::Settings,
-) {
-    let lookup = if scan_dynlibs {
-        Some(Lookup {
-            #[cfg(all(target_os = "linux", feature = "elf"))]
-            elf: LibraryLookup::new().unwrap_or_else(|err| {
-                eprintln!("Failed to initialize library lookup: {err}");
-                process::exit(1)
-            }),
-        })
-    } else {
-        None
-    };
+/// library lookup for `--libraries`, exits if it cannot be set up
+fn library_lookup(scan_dynlibs: bool) -> Option<Lookup> {
+    scan_dynlibs.then(|| Lookup {
+        #[cfg(all(target_os = "linux", feature = "elf"))]
+        elf: LibraryLookup::new().unwrap_or_else(|err| {
+            eprintln!("Failed to initialize library lookup: {err}");
+            process::exit(1)
+        }),
+    })
+}
 
-    let cache = Arc::new(Mutex::new(HashMap::new()));
+/// outcome of a file found walking a directory
+enum Scanned {
+    Checked(Vec<Binary>),
+    Skipped(filter::Skip),
+    Failed(String),
+}
 
-    let bins: Vec<Binary> = Walk::new(basepath)
-        .flatten()
+fn scan_directory(
+    basepath: &Path,
+    scan_dynlibs: bool,
+    options: &filter::WalkOptions,
+    lookup: Option<&Lookup>,
+    cache: &Cache,
+) -> (Vec<Binary>, filter::ScanSummary) {
+    let scanned: Vec<Scanned> = options
+        .walk(basepath)
         .filter(|entry| {
-            entry.file_type().filter(std::fs::FileType::is_file).is_some()
+            entry.as_ref().map_or(true, |entry| {
+                entry.file_type().is_some_and(|ft| ft.is_file())
+            })
         })
         .par_bridge()
-        .filter_map(|entry| {
-            parse_file_impl(
-                entry.path(),
+        .map(|entry| {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(err) => return Scanned::Failed(err.to_string()),
+            };
+            let file = entry.path();
+            match options.skip(file, basepath) {
+                Ok(Some(reason)) => return Scanned::Skipped(reason),
+                Ok(None) => {}
+                Err(err) => {
+                    return Scanned::Failed(format!(
+                        "{}: {}",
+                        file.display(),
+                        err
+                    ))
+                }
+            }
+            match parse_file_impl(
+                file,
                 scan_dynlibs,
-                &lookup,
-                &mut Some(Arc::clone(&cache)),
-            )
-            .ok()
+                lookup,
+                &mut Some(Arc::clone(cache)),
+            ) {
+                Ok(bins) => Scanned::Checked(bins),
+                Err(err) => {
+                    Scanned::Failed(format!("{}: {}", file.display(), err))
+                }
+            }
         })
-        .flatten()
         .collect();
 
+    let mut bins = Vec::new();
+    let mut summary = filter::ScanSummary::default();
+    for outcome in scanned {
+        match outcome {
+            Scanned::Checked(checked) => {
+                summary.checked += 1;
+                bins.extend(checked);
+            }
+            Scanned::Skipped(reason) => {
+                *summary.skipped.entry(reason).or_default() += 1;
+            }
+            Scanned::Failed(failure) => summary.failed.push(failure),
+        }
+    }
+    summary.failed.sort();
+    (bins, summary)
+}
+
+fn walk(
+    basepath: &Path,
+    scan_dynlibs: bool,
+    options: &filter::WalkOptions,
+    output_settings: &output::Settings,
+) {
+    let lookup = library_lookup(scan_dynlibs);
+    let cache = Arc::new(Mutex::new(HashMap::new()));
+
+    let (bins, summary) = scan_directory(
+        basepath,
+        scan_dynlibs,
+        options,
+        lookup.as_ref(),
+        &cache,
+    );
+
+    save_result_cache();
     print_binary_results(&bins, output_settings);
+    if summary.is_notable() {
+        eprintln!("{summary}");
+    }
+}
+
+/// `--include`/`--exclude` value
+fn parse_glob(glob: &str) -> Result<glob::Pattern, String> {
+    glob::Pattern::new(glob).map_err(|err| format!("invalid glob: {err}"))
 }
 
 #[cfg(all(feature = "maps", target_os = "linux"))]
@@ -701,7 +895,26 @@ fn parse_process_libraries(
     ))
 }
 
-fn parse_processes<'a, I>(processes: I, scan_dynlibs: bool) -> Vec<Process>
+/// executable of a process, through `/proc/<pid>/exe` if the file was
+/// deleted since the process started
+fn process_exe(process: &sysinfo::Process) -> PathBuf {
+    #[cfg(target_os = "linux")]
+    if !process.exe().is_file() {
+        return PathBuf::from(format!("/proc/{}/exe", process.pid()));
+    }
+    process.exe().to_path_buf()
+}
+
+#[cfg_attr(
+    not(all(feature = "maps", target_os = "linux")),
+    allow(unused_variables, unused_mut)
+)]
+fn parse_processes<'a, I>(
+    processes: I,
+    scan_dynlibs: bool,
+    findings: bool,
+    memory: bool,
+) -> Vec<Process>
 where
     I: Iterator<Item = &'a sysinfo::Process> + Send,
 {
@@ -710,7 +923,7 @@ where
     processes
         .par_bridge()
         .filter_map(|process| {
-            match parse(process.exe(), &mut Some(Arc::clone(&cache))) {
+            match parse(&process_exe(process), &mut Some(Arc::clone(&cache))) {
                 Err(err) => {
                     if let ParseError::IO(ref e) = err {
                         if e.kind() == ErrorKind::NotFound
@@ -732,7 +945,7 @@ where
                 Ok(bins) => Some(
                     bins.into_iter()
                         .map(|bin| {
-                            Process::new(
+                            let mut process = Process::new(
                                 process.pid().as_u32() as usize,
                                 bin,
                                 if scan_dynlibs {
@@ -744,7 +957,20 @@ where
                                 } else {
                                     None
                                 },
-                            )
+                            );
+                            #[cfg(all(feature = "maps", target_os = "linux"))]
+                            if findings {
+                                process = process.with_findings();
+                            }
+                            #[cfg(all(
+                                feature = "maps",
+                                feature = "elf",
+                                target_os = "linux"
+                            ))]
+                            if memory {
+                                process = process.with_memory_image();
+                            }
+                            process
                         })
                         .collect::<Vec<proc::Process>>(),
                 ),
@@ -761,6 +987,25 @@ fn main() {
         .author(crate_authors!())
         .version(crate_version!())
         .arg_required_else_help(true)
+        .arg(
+            Arg::new("cache")
+                .long("cache")
+                .value_name("FILE")
+                .num_args(0..=1)
+                .help(
+                    "Reuse results of unchanged files from earlier scans\n\
+                    (default: $XDG_CACHE_HOME/checksec/results.json.gz)",
+                ),
+        )
+        .arg(
+            Arg::new("dangerous-imports")
+                .long("dangerous-imports")
+                .value_name("FILE")
+                .help(
+                    "File listing dangerous PE imports, one per line\n\
+                    (replaces the default list)",
+                ),
+        )
         .arg(
             Arg::new("directory")
                 .short('d')
@@ -768,6 +1013,18 @@ fn main() {
                 .value_name("DIRECTORY")
                 .help("Target directory"),
         )
+        .arg(
+            Arg::new("exclude")
+                .long("exclude")
+                .value_name("GLOB")
+                .action(ArgAction::Append)
+                .value_parser(parse_glob)
+                .help(
+                    "Skip files whose path below the directory matches\n\
+                    (repeatable)",
+                )
+                .requires("directory"),
+        )
         .arg(
             Arg::new("file")
                 .short('f')
@@ -775,6 +1032,45 @@ fn main() {
                 .value_name("FILE")
                 .help("Target file"),
         )
+        .arg(
+            Arg::new("findings")
+                .long("findings")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Report anonymous, memfd, deleted or replaced and\n\
+                    writable executable mappings (linux only)",
+                )
+                .requires("pid")
+                .requires("process")
+                .requires("process-all")
+                .conflicts_with_all(["directory", "file"]),
+        )
+        .arg(
+            Arg::new("follow-symlinks")
+                .long("follow-symlinks")
+                .action(ArgAction::SetTrue)
+                .help("Follow symbolic links while walking the directory")
+                .requires("directory"),
+        )
+        .arg(
+            Arg::new("hidden")
+                .long("hidden")
+                .action(ArgAction::SetTrue)
+                .help("Also check hidden files and directories")
+                .requires("directory"),
+        )
+        .arg(
+            Arg::new("include")
+                .long("include")
+                .value_name("GLOB")
+                .action(ArgAction::Append)
+                .value_parser(parse_glob)
+                .help(
+                    "Only check files whose path below the directory\n\
+                    matches (repeatable)",
+                )
+                .requires("directory"),
+        )
         .arg(
             Arg::new("json")
                 .short('j')
@@ -782,6 +1078,16 @@ fn main() {
                 .action(ArgAction::SetTrue)
                 .help("Output in json format"),
         )
+        .arg(
+            Arg::new("kernel")
+                .long("kernel")
+                .value_name("CONFIG")
+                .num_args(0..=1)
+                .help(
+                    "Check the hardening of the running kernel, or only\n\
+                    the given kernel config (linux only)",
+                ),
+        )
         .arg(
             Arg::new("libraries")
                 .short('l')
@@ -805,12 +1111,43 @@ fn main() {
                 .requires("process-all")
                 .conflicts_with_all(["directory", "file"]),
         )
+        .arg(
+            Arg::new("max-size")
+                .long("max-size")
+                .value_name("SIZE")
+                .value_parser(filter::parse_size)
+                .help("Skip files larger than SIZE bytes (K, M, G suffixes)")
+                .requires("directory"),
+        )
+        .arg(
+            Arg::new("memory")
+                .long("memory")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Also check the executable image loaded into memory\n\
+                    and report differences from the file (linux only)",
+                )
+                .requires("pid")
+                .requires("process")
+                .requires("process-all")
+                .conflicts_with_all(["directory", "file"]),
+        )
         .arg(
             Arg::new("no-color")
                 .long("no-color")
                 .action(ArgAction::SetTrue)
                 .help("Disables color output"),
         )
+        .arg(
+            Arg::new("no-ignore")
+                .long("no-ignore")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Also check files excluded by .gitignore, .ignore\n\
+                    and git exclude files",
+                )
+                .requires("directory"),
+        )
         .arg(
             Arg::new("pid")
                 .help(
@@ -827,6 +1164,16 @@ fn main() {
                 .help("Human readable json output")
                 .requires("json"),
         )
+        .arg(
+            Arg::new("privileged")
+                .long("privileged")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Only check setuid/setgid binaries and binaries with\n\
+                    file capabilities",
+                )
+                .requires("directory"),
+        )
         .arg(
             Arg::new("process")
                 .short('p')
@@ -841,9 +1188,26 @@ fn main() {
                 .action(ArgAction::SetTrue)
                 .help("Check all running processes"),
         )
+        .arg(
+            Arg::new("watch")
+                .long("watch")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Keep running and check binaries as they are written,\n\
+                    printing only changed results",
+                )
+                .requires("directory"),
+        )
         .group(
             ArgGroup::new("operation")
-                .args(["directory", "file", "pid", "process", "process-all"])
+                .args([
+                    "directory",
+                    "file",
+                    "kernel",
+                    "pid",
+                    "process",
+                    "process-all",
+                ])
                 .required(true),
         )
         .get_matches();
@@ -854,9 +1218,26 @@ fn main() {
     let procids = args.get_one::<String>("pid");
     let procname = args.get_one::<String>("process");
     let procall = args.get_flag("process-all");
+    let kernel = args.contains_id("kernel");
 
     // optional modifiers
     let libraries = args.get_flag("libraries");
+    let findings = args.get_flag("findings");
+    let memory = args.get_flag("memory");
+    let walk_options = filter::WalkOptions {
+        include: args
+            .get_many::<glob::Pattern>("include")
+            .map_or_else(Vec::new, |globs| globs.cloned().collect()),
+        exclude: args
+            .get_many::<glob::Pattern>("exclude")
+            .map_or_else(Vec::new, |globs| globs.cloned().collect()),
+        max_size: args.get_one::<u64>("max-size").copied(),
+        follow_symlinks: args.get_flag("follow-symlinks"),
+        hidden: args.get_flag("hidden"),
+        ignore_files: !args.get_flag("no-ignore"),
+        privileged: args.get_flag("privileged"),
+    };
+    let watch = args.get_flag("watch");
 
     let format = if args.get_flag("json") {
         if args.get_flag("pretty") {
@@ -876,14 +1257,81 @@ fn main() {
         libraries,
     );
 
+    #[cfg(feature = "pe")]
+    if let Some(list) = args.get_one::<String>("dangerous-imports") {
+        match fs::read_to_string(list) {
+            Ok(list) => {
+                IMPORT_POLICY.get_or_init(|| pe::ImportPolicy::from_list(&list));
+            }
+            Err(err) => {
+                eprintln!(
+                    "Cannot read dangerous imports list {}: {}",
+                    underline!(list),
+                    err
+                );
+                process::exit(1);
+            }
+        }
+    }
+
+    if args.contains_id("cache") {
+        let path = match args.get_one::<String>("cache") {
+            Some(path) => Some(PathBuf::from(path)),
+            None => cache::ResultCache::default_path(),
+        };
+        let path = path.unwrap_or_else(|| {
+            eprintln!("No cache directory found, pass a cache file");
+            process::exit(1)
+        });
+        // the results depend on the dangerous imports list
+        let imports = args
+            .get_one::<String>("dangerous-imports")
+            .and_then(|list| fs::read_to_string(list).ok())
+            .unwrap_or_default();
+        RESULT_CACHE.get_or_init(|| cache::ResultCache::open(path, &imports));
+    }
+
+    #[cfg(feature = "elf")]
+    if kernel {
+        let results = match args.get_one::<String>("kernel") {
+            Some(config) => {
+                match kernel::CheckSecResults::from_config(Path::new(config)) {
+                    Ok(results) => results,
+                    Err(err) => {
+                        eprintln!(
+                            "Cannot read kernel config {}: {}",
+                            underline!(config),
+                            err
+                        );
+                        process::exit(1);
+                    }
+                }
+            }
+            None => kernel::CheckSecResults::running(),
+        };
+        print_kernel_results(&results, &settings);
+        return;
+    }
+    #[cfg(not(feature = "elf"))]
+    if kernel {
+        eprintln!("Kernel checks require the elf feature");
+        process::exit(1);
+    }
+
     if procall {
         let system = System::new_with_specifics(
             RefreshKind::new()
                 .with_processes(ProcessRefreshKind::new().with_cpu()),
         );
 
-        let procs = parse_processes(system.processes().values(), libraries);
+        let procs = parse_processes(
+            system.processes().values(),
+            libraries,
+            findings,
+            memory,
+        );
 
+        save_result_cache();
         print_process_results(&Processes::new(procs), &settings);
     } else if let Some(procids) = procids {
         let procids: Vec<sysinfo::Pid> = procids
@@ -911,7 +1359,7 @@ fn main() {
                     })
                 })
                 .filter(|process| {
-                    if process.exe().is_file() {
+                    if process_exe(process).is_file() {
                         true
                     } else {
                         eprintln!(
@@ -924,8 +1372,11 @@ fn main() {
                     }
                 }),
             libraries,
+            findings,
+            memory,
         );
 
+        save_result_cache();
         print_process_results(&Processes::new(procs), &settings);
     } else if let Some(procname) = procname {
         let system = System::new_with_specifics(
@@ -940,12 +1391,15 @@ fn main() {
                 .collect::<Vec<&sysinfo::Process>>()
                 .into_iter(),
             libraries,
+            findings,
+            memory,
         );
 
         if procs.is_empty() {
             eprintln!("No process found matching name {procname}");
             process::exit(1);
         }
+        save_result_cache();
         print_process_results(&Processes::new(procs), &settings);
     } else if let Some(directory) = directory {
         let directory_path = Path::new(directory);
@@ -955,7 +1409,18 @@ fn main() {
             process::exit(1);
         }
 
-        walk(directory_path, libraries, &settings);
+        #[cfg(feature = "watch")]
+        if watch {
+            watch::watch(directory_path, libraries, &walk_options, &settings);
+            return;
+        }
+        #[cfg(not(feature = "watch"))]
+        if watch {
+            eprintln!("Watching directories requires the watch feature");
+            process::exit(1);
+        }
+
+        walk(directory_path, libraries, &walk_options, &settings);
     } else if let Some(file) = file {
         let file_path = Path::new(file);
 
@@ -964,7 +1429,9 @@ fn main() {
             process::exit(1);
         }
 
-        match parse_single_file(file_path, libraries) {
+        let result = parse_single_file(file_path, libraries);
+        save_result_cache();
+        match result {
             Ok(result) => {
                 print_binary_results(&result, &settings);
             }
@@ -1,10 +1,14 @@
 //! Implements checksec for PE32/32+ binaries
 #[cfg(feature = "color")]
 use colored::Colorize;
+#[cfg(feature = "disassembly")]
+use goblin::pe::section_table::IMAGE_SCN_MEM_EXECUTE;
 use goblin::pe::utils::find_offset;
 use goblin::pe::PE;
 use goblin::pe::{
-    data_directories::DataDirectory, options::ParseOptions,
+    data_directories::DataDirectory,
+    import::{IMPORT_BY_ORDINAL_32, IMPORT_BY_ORDINAL_64},
+    options::ParseOptions,
     section_table::SectionTable,
 };
 use scroll::Pread;
@@ -15,6 +19,18 @@ use std::mem::size_of;
 
 #[cfg(feature = "color")]
 use crate::colorize_bool;
+use crate::dotnet;
+use crate::resource::{ExecutionLevel, Manifest, Resources};
+use crate::rich::RichHeader;
+use crate::debuginfo::{self, DebugInfo, Stripped};
+#[cfg(feature = "disassembly")]
+use crate::disassembly::{
+    self, speculation_mitigations, Architecture, Thunks,
+};
+use crate::disassembly::{SpeculationMitigations, StackProbe};
+#[cfg(feature = "disassembly")]
+use crate::functions::Functions;
+use crate::sanitizer::VecSanitizer;
 
 const IMAGE_DLLCHARACTERISTICS_HIGH_ENTROPY_VA: u16 = 0x0020;
 const IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE: u16 = 0x0040;
@@ -24,188 +40,311 @@ const IMAGE_DLLCHARACTERISTICS_NO_ISOLATION: u16 = 0x0200;
 const IMAGE_DLLCHARACTERISTICS_NO_SEH: u16 = 0x0400;
 const IMAGE_DLLCHARACTERISTICS_GUARD_CF: u16 = 0x4000;
 const IMAGE_DLLCHARACTERISTICS_EX_CET_COMPAT: u32 = 0x0001;
+const IMAGE_DLLCHARACTERISTICS_EX_CET_COMPAT_STRICT_MODE: u32 = 0x0002;
+const IMAGE_DLLCHARACTERISTICS_EX_CET_SET_CONTEXT_IP_VALIDATION_RELAXED_MODE:
+    u32 = 0x0004;
+const IMAGE_DLLCHARACTERISTICS_EX_FORWARD_CFI_COMPAT: u32 = 0x0040;
 
 // stored in `IMAGE_LOAD_CONFIG_DIRECTORY64`
 const IMAGE_GUARD_RF_INSTRUMENTED: u32 = 0x0002_0000;
 const IMAGE_GUARD_RF_ENABLE: u32 = 0x0004_0000;
 const IMAGE_GUARD_RF_STRICT: u32 = 0x0008_0000;
+const IMAGE_GUARD_EH_CONTINUATION_TABLE_PRESENT: u32 = 0x0040_0000;
+
+// `IMAGE_DYNAMIC_RELOCATION_TABLE` symbols
+const IMAGE_DYNAMIC_RELOCATION_GUARD_IMPORT_CONTROL_TRANSFER: u64 = 0x0000_0003;
+const IMAGE_DYNAMIC_RELOCATION_GUARD_INDIR_CONTROL_TRANSFER: u64 = 0x0000_0004;
+const IMAGE_DYNAMIC_RELOCATION_GUARD_SWITCHTABLE_BRANCH: u64 = 0x0000_0005;
+const IMAGE_DYNAMIC_RELOCATION_ARM64X: u64 = 0x0000_0006;
+
+// `ImgDelayDescr` addresses are RVAs rather than VAs
+const DLATTR_RVA: u32 = 0x0000_0001;
+
+/// Banned or unsafe CRT and Win32 APIs flagged by default, based on the SDL
+/// banned function list. `LoadLibraryA/W` search the current directory
+/// (prefer `LoadLibraryExW` with `LOAD_LIBRARY_SEARCH_*` flags) and the
+/// `Virtual*` allocators are listed as they can map RWX memory; their
+/// arguments are not evaluated.
+pub const DANGEROUS_IMPORTS: &[&str] = &[
+    "_alloca",
+    "_getws",
+    "_makepath",
+    "_mbscat",
+    "_mbscpy",
+    "_splitpath",
+    "gets",
+    "LoadLibraryA",
+    "LoadLibraryW",
+    "lstrcat",
+    "lstrcatA",
+    "lstrcatW",
+    "lstrcpy",
+    "lstrcpyA",
+    "lstrcpyn",
+    "lstrcpynA",
+    "lstrcpynW",
+    "lstrcpyW",
+    "scanf",
+    "sprintf",
+    "sscanf",
+    "strcat",
+    "StrCatA",
+    "StrCatW",
+    "strcpy",
+    "StrCpyA",
+    "StrCpyW",
+    "strncat",
+    "strncpy",
+    "strtok",
+    "swprintf",
+    "swscanf",
+    "VirtualAlloc",
+    "VirtualAllocEx",
+    "VirtualProtect",
+    "VirtualProtectEx",
+    "vsprintf",
+    "vswprintf",
+    "wcscat",
+    "wcscpy",
+    "wcsncat",
+    "wcsncpy",
+    "wcstok",
+    "wscanf",
+    "wsprintfA",
+    "wsprintfW",
+    "wvsprintfA",
+    "wvsprintfW",
+];
 
 /// `IMAGE_LOAD_CONFIG_CODE_INTEGRITY`
-#[repr(C)]
-#[derive(Debug, Copy, Clone, Default, Pread)]
-struct ImageLoadConfigCodeIntegrity {
-    flags: u16,
-    catalog: u16,
-    catalogoffset: u32,
-    reserved: u32,
-}
-/// [`IMAGE_LOAD_CONFIG_DIRECTORY32`](https://docs.microsoft.com/en-us/windows/win32/api/winnt/ns-winnt-image_load_config_directory32)
-#[allow(clippy::cast_lossless)]
-#[repr(C)]
-#[derive(Debug, Copy, Clone, Default, Pread)]
-struct ImageLoadConfigDirectory32 {
-    size: u32,
-    time_date_stamp: u32,
-    major_version: u16,
-    minor_version: u16,
-    global_flags_clear: u32,
-    global_flags_set: u32,
-    critical_section_default_timeout: u32,
-    decommit_free_block_threshold: u32,
-    decommit_total_free_threshold: u32,
-    lock_prefix_table: u32,
-    maximum_allocation_size: u32,
-    virtual_memory_threshold: u32,
-    process_heap_flags: u32,
-    process_affinity_mask: u32,
-    csd_version: u16,
-    dependent_load_flags: u16,
-    edit_list: u32,
-    security_cookie: u32,
-    sehandler_table: u32,
-    sehandler_count: u32,
-    guard_cf_check_function_pointer: u32,
-    guard_cf_dispatch_function_pointer: u32,
-    guard_cf_function_table: u32,
-    guard_cf_function_count: u32,
-    guard_flags: u32,
-    code_integrity: ImageLoadConfigCodeIntegrity,
-    guard_address_taken_iat_entry_table: u32,
-    guard_address_taken_iat_entry_count: u32,
-    guard_long_jump_target_table: u32,
-    guard_long_jump_target_count: u32,
-    dynamic_value_reloc_table: u32,
-    ch_pe_metadata_pointer: u32,
-    guard_rf_failure_routine: u32,
-    guard_rf_failure_routine_function_pointer: u32,
-    dynamic_value_reloc_table_offset: u32,
-    dynamic_value_reloc_table_section: u16,
-    reserved2: u16,
-    guard_rf_verify_stack_pointer_function_pointer: u32,
-    hot_patch_table_offset: u32,
-    reserved3: u32,
-    enclave_configuration_pointer: u32,
-    volatiile_metadata_pointer: u32,
-}
-
-/// [`IMAGE_LOAD_CONFIG_DIRECTORY64`](https://docs.microsoft.com/en-us/windows/win32/api/winnt/ns-winnt-image_load_config_directory64)
-#[allow(clippy::cast_lossless)]
-#[repr(C)]
-#[derive(Debug, Copy, Clone, Default, Pread)]
-struct ImageLoadConfigDirectory64 {
-    size: u32,
-    time_date_stamp: u32,
-    major_version: u16,
-    minor_version: u16,
-    global_flags_clear: u32,
-    global_flags_set: u32,
-    critical_section_default_timeout: u32,
-    decommit_free_block_threshold: u64,
-    decommit_total_free_threshold: u64,
-    lock_prefix_table: u64,
-    maximum_allocation_size: u64,
-    virtual_memory_threshold: u64,
-    process_affinity_mask: u64,
-    process_heap_flags: u32,
-    csd_version: u16,
-    dependent_load_flags: u16,
-    edit_list: u64,
-    security_cookie: u64,
-    sehandler_table: u64,
-    sehandler_count: u64,
-    guard_cf_check_function_pointer: u64,
-    guard_cf_dispatch_function_pointer: u64,
-    guard_cf_function_table: u64,
-    guard_cf_function_count: u64,
-    guard_flags: u32,
-    code_integrity: ImageLoadConfigCodeIntegrity,
-    guard_address_taken_iat_entry_table: u64,
-    guard_address_taken_iat_entry_count: u64,
-    guard_long_jump_target_table: u64,
-    guard_long_jump_target_count: u64,
-    dynamic_value_reloc_table: u64,
-    ch_pe_metadata_pointer: u64,
-    guard_rf_failure_routine: u64,
-    guard_rf_failure_routine_function_pointer: u64,
-    dynamic_value_reloc_table_offset: u32,
-    dynamic_value_reloc_table_section: u16,
-    reserved2: u16,
-    guard_rf_verify_stack_pointer_function_pointer: u64,
-    hot_patch_table_offset: u32,
-    reserved3: u32,
-    enclave_configuration_pointer: u64,
-    volatiile_metadata_pointer: u64,
-}
-
-/// Unified 32/64-bit `IMAGE_LOAD_CONFIG_DIRECTORY` fields
-type ImageLoadConfigDirectory = ImageLoadConfigDirectory64;
-impl From<ImageLoadConfigDirectory32> for ImageLoadConfigDirectory {
-    fn from(cfg: ImageLoadConfigDirectory32) -> Self {
-        #[allow(clippy::cast_lossless)]
-        Self {
-            size: cfg.size,
-            time_date_stamp: cfg.time_date_stamp,
-            major_version: cfg.major_version,
-            minor_version: cfg.minor_version,
-            global_flags_clear: cfg.global_flags_clear,
-            global_flags_set: cfg.global_flags_set,
-            critical_section_default_timeout: cfg
-                .critical_section_default_timeout,
-            decommit_free_block_threshold: cfg.decommit_free_block_threshold
-                as u64,
-            decommit_total_free_threshold: cfg.decommit_total_free_threshold
-                as u64,
-            lock_prefix_table: cfg.lock_prefix_table as u64,
-            maximum_allocation_size: cfg.maximum_allocation_size as u64,
-            virtual_memory_threshold: cfg.virtual_memory_threshold as u64,
-            process_affinity_mask: cfg.process_affinity_mask as u64,
-            process_heap_flags: cfg.process_heap_flags,
-            csd_version: cfg.csd_version,
-            dependent_load_flags: cfg.dependent_load_flags,
-            edit_list: cfg.edit_list as u64,
-            security_cookie: cfg.security_cookie as u64,
-            sehandler_table: cfg.sehandler_table as u64,
-            sehandler_count: cfg.sehandler_count as u64,
-            guard_cf_check_function_pointer: cfg
-                .guard_cf_check_function_pointer
-                as u64,
-            guard_cf_dispatch_function_pointer: cfg
-                .guard_cf_dispatch_function_pointer
-                as u64,
-            guard_cf_function_table: cfg.guard_cf_function_table as u64,
-            guard_cf_function_count: cfg.guard_cf_function_count as u64,
-            guard_flags: cfg.guard_flags,
-            code_integrity: cfg.code_integrity,
-            guard_address_taken_iat_entry_table: cfg
-                .guard_address_taken_iat_entry_table
-                as u64,
-            guard_address_taken_iat_entry_count: cfg
-                .guard_address_taken_iat_entry_count
-                as u64,
-            guard_long_jump_target_table: cfg.guard_long_jump_target_table
-                as u64,
-            guard_long_jump_target_count: cfg.guard_long_jump_target_count
-                as u64,
-            dynamic_value_reloc_table: cfg.dynamic_value_reloc_table as u64,
-            ch_pe_metadata_pointer: cfg.ch_pe_metadata_pointer as u64,
-            guard_rf_failure_routine: cfg.guard_rf_failure_routine as u64,
-            guard_rf_failure_routine_function_pointer: cfg
-                .guard_rf_failure_routine_function_pointer
-                as u64,
-            dynamic_value_reloc_table_offset: cfg
-                .dynamic_value_reloc_table_offset,
-            dynamic_value_reloc_table_section: cfg
-                .dynamic_value_reloc_table_section,
-            reserved2: cfg.reserved2,
-            guard_rf_verify_stack_pointer_function_pointer: cfg
-                .guard_rf_verify_stack_pointer_function_pointer
-                as u64,
-            hot_patch_table_offset: cfg.hot_patch_table_offset,
-            reserved3: cfg.reserved3,
-            enclave_configuration_pointer: cfg.enclave_configuration_pointer
-                as u64,
-            volatiile_metadata_pointer: cfg.volatiile_metadata_pointer as u64,
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Pread, Serialize,
+)]
+pub struct ImageLoadConfigCodeIntegrity {
+    pub flags: u16,
+    pub catalog: u16,
+    pub catalog_offset: u32,
+    pub reserved: u32,
+}
+
+/// Parsed
+/// [`IMAGE_LOAD_CONFIG_DIRECTORY32/64`](https://learn.microsoft.com/en-us/windows/win32/api/winnt/ns-winnt-image_load_config_directory64)
+///
+/// The structure has grown with nearly every Windows SDK release and the
+/// loader only consults the fields covered by its leading `size` field, so
+/// every field past `size` is `None` rather than whatever bytes happen to
+/// follow the structure on disk. Pointer-sized fields are widened to `u64`
+/// for both PE32 and PE32+.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct LoadConfig {
+    pub size: u32,
+    pub time_date_stamp: Option<u32>,
+    pub major_version: Option<u16>,
+    pub minor_version: Option<u16>,
+    pub global_flags_clear: Option<u32>,
+    pub global_flags_set: Option<u32>,
+    pub critical_section_default_timeout: Option<u32>,
+    pub decommit_free_block_threshold: Option<u64>,
+    pub decommit_total_free_threshold: Option<u64>,
+    pub lock_prefix_table: Option<u64>,
+    pub maximum_allocation_size: Option<u64>,
+    pub virtual_memory_threshold: Option<u64>,
+    pub process_affinity_mask: Option<u64>,
+    pub process_heap_flags: Option<u32>,
+    pub csd_version: Option<u16>,
+    pub dependent_load_flags: Option<u16>,
+    pub edit_list: Option<u64>,
+    pub security_cookie: Option<u64>,
+    pub se_handler_table: Option<u64>,
+    pub se_handler_count: Option<u64>,
+    pub guard_cf_check_function_pointer: Option<u64>,
+    pub guard_cf_dispatch_function_pointer: Option<u64>,
+    pub guard_cf_function_table: Option<u64>,
+    pub guard_cf_function_count: Option<u64>,
+    pub guard_flags: Option<u32>,
+    pub code_integrity: Option<ImageLoadConfigCodeIntegrity>,
+    pub guard_address_taken_iat_entry_table: Option<u64>,
+    pub guard_address_taken_iat_entry_count: Option<u64>,
+    pub guard_long_jump_target_table: Option<u64>,
+    pub guard_long_jump_target_count: Option<u64>,
+    pub dynamic_value_reloc_table: Option<u64>,
+    pub chpe_metadata_pointer: Option<u64>,
+    pub guard_rf_failure_routine: Option<u64>,
+    pub guard_rf_failure_routine_function_pointer: Option<u64>,
+    pub dynamic_value_reloc_table_offset: Option<u32>,
+    pub dynamic_value_reloc_table_section: Option<u16>,
+    pub reserved2: Option<u16>,
+    pub guard_rf_verify_stack_pointer_function_pointer: Option<u64>,
+    pub hot_patch_table_offset: Option<u32>,
+    pub reserved3: Option<u32>,
+    pub enclave_configuration_pointer: Option<u64>,
+    pub volatile_metadata_pointer: Option<u64>,
+    pub guard_eh_continuation_table: Option<u64>,
+    pub guard_eh_continuation_count: Option<u64>,
+    pub guard_xfg_check_function_pointer: Option<u64>,
+    pub guard_xfg_dispatch_function_pointer: Option<u64>,
+    pub guard_xfg_table_dispatch_function_pointer: Option<u64>,
+    pub cast_guard_os_determined_failure_mode: Option<u64>,
+    pub guard_memcpy_function_pointer: Option<u64>,
+    pub uma_function_pointers: Option<u64>,
+}
+
+/// sequential reader over the bytes covered by the declared `size` of an
+/// `IMAGE_LOAD_CONFIG_DIRECTORY32/64`
+struct LoadConfigReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    is_64: bool,
+}
+impl LoadConfigReader<'_> {
+    fn read<T>(&mut self, width: usize) -> Option<T>
+    where
+        T: for<'b> scroll::ctx::TryFromCtx<
+            'b,
+            scroll::Endian,
+            Error = scroll::Error,
+        >,
+    {
+        let offset = self.offset;
+        self.offset += width;
+        // a field is only present if it lies completely within `size`
+        if self.offset > self.bytes.len() {
+            return None;
         }
+        self.bytes.pread_with(offset, scroll::LE).ok()
+    }
+    fn u16(&mut self) -> Option<u16> {
+        self.read(2)
+    }
+    fn u32(&mut self) -> Option<u32> {
+        self.read(4)
+    }
+    fn u64(&mut self) -> Option<u64> {
+        self.read(8)
+    }
+    fn ptr(&mut self) -> Option<u64> {
+        if self.is_64 {
+            self.u64()
+        } else {
+            self.u32().map(u64::from)
+        }
+    }
+    fn code_integrity(&mut self) -> Option<ImageLoadConfigCodeIntegrity> {
+        self.read(size_of::<ImageLoadConfigCodeIntegrity>())
+    }
+}
+
+impl LoadConfig {
+    /// Parse an `IMAGE_LOAD_CONFIG_DIRECTORY32/64` starting at `bytes[0]`,
+    /// honouring the structure's declared `size`.
+    ///
+    /// # Errors
+    /// Fails if the leading `size` field can not be read.
+    pub fn parse(bytes: &[u8], is_64: bool) -> Result<Self, scroll::Error> {
+        let size: u32 = bytes.pread_with(0, scroll::LE)?;
+        let limit = (size as usize).min(bytes.len());
+        let mut r = LoadConfigReader { bytes: &bytes[..limit], offset: 4, is_64 };
+        let mut cfg = Self {
+            size,
+            time_date_stamp: r.u32(),
+            major_version: r.u16(),
+            minor_version: r.u16(),
+            global_flags_clear: r.u32(),
+            global_flags_set: r.u32(),
+            critical_section_default_timeout: r.u32(),
+            decommit_free_block_threshold: r.ptr(),
+            decommit_total_free_threshold: r.ptr(),
+            lock_prefix_table: r.ptr(),
+            maximum_allocation_size: r.ptr(),
+            virtual_memory_threshold: r.ptr(),
+            ..Self::default()
+        };
+        // the 64-bit layout swaps these two fields
+        if is_64 {
+            cfg.process_affinity_mask = r.u64();
+            cfg.process_heap_flags = r.u32();
+        } else {
+            cfg.process_heap_flags = r.u32();
+            cfg.process_affinity_mask = r.u32().map(u64::from);
+        }
+        Ok(Self {
+            csd_version: r.u16(),
+            dependent_load_flags: r.u16(),
+            edit_list: r.ptr(),
+            security_cookie: r.ptr(),
+            se_handler_table: r.ptr(),
+            se_handler_count: r.ptr(),
+            guard_cf_check_function_pointer: r.ptr(),
+            guard_cf_dispatch_function_pointer: r.ptr(),
+            guard_cf_function_table: r.ptr(),
+            guard_cf_function_count: r.ptr(),
+            guard_flags: r.u32(),
+            code_integrity: r.code_integrity(),
+            guard_address_taken_iat_entry_table: r.ptr(),
+            guard_address_taken_iat_entry_count: r.ptr(),
+            guard_long_jump_target_table: r.ptr(),
+            guard_long_jump_target_count: r.ptr(),
+            dynamic_value_reloc_table: r.ptr(),
+            chpe_metadata_pointer: r.ptr(),
+            guard_rf_failure_routine: r.ptr(),
+            guard_rf_failure_routine_function_pointer: r.ptr(),
+            dynamic_value_reloc_table_offset: r.u32(),
+            dynamic_value_reloc_table_section: r.u16(),
+            reserved2: r.u16(),
+            guard_rf_verify_stack_pointer_function_pointer: r.ptr(),
+            hot_patch_table_offset: r.u32(),
+            reserved3: r.u32(),
+            enclave_configuration_pointer: r.ptr(),
+            volatile_metadata_pointer: r.ptr(),
+            guard_eh_continuation_table: r.ptr(),
+            guard_eh_continuation_count: r.ptr(),
+            guard_xfg_check_function_pointer: r.ptr(),
+            guard_xfg_dispatch_function_pointer: r.ptr(),
+            guard_xfg_table_dispatch_function_pointer: r.ptr(),
+            cast_guard_os_determined_failure_mode: r.ptr(),
+            guard_memcpy_function_pointer: r.ptr(),
+            uma_function_pointers: r.ptr(),
+            ..cfg
+        })
+    }
+    /// `guard_flags`, or 0 if the field is absent
+    #[must_use]
+    pub fn guard_flags(&self) -> u32 {
+        self.guard_flags.unwrap_or(0)
+    }
+    /// check for a non-zero `security_cookie`
+    #[must_use]
+    pub fn has_gs(&self) -> bool {
+        self.security_cookie.is_some_and(|cookie| cookie != 0)
+    }
+    /// check `guard_flags` for `IMAGE_GUARD_RF_INSTRUMENTED` along with
+    /// `IMAGE_GUARD_RF_ENABLE` or `IMAGE_GUARD_RF_STRICT`
+    #[must_use]
+    pub fn has_rfg(&self) -> bool {
+        let guard_flags = self.guard_flags();
+        (guard_flags & IMAGE_GUARD_RF_INSTRUMENTED) != 0
+            && (guard_flags & IMAGE_GUARD_RF_ENABLE) != 0
+            || (guard_flags & IMAGE_GUARD_RF_STRICT) != 0
+    }
+    /// check for a non-zero `se_handler_count`
+    #[must_use]
+    pub fn has_safe_seh(&self) -> bool {
+        self.se_handler_count.is_some_and(|count| count != 0)
+    }
+    /// check `guard_flags` for `IMAGE_GUARD_EH_CONTINUATION_TABLE_PRESENT`
+    /// along with a non-zero `guard_eh_continuation_count`
+    #[must_use]
+    pub fn has_eh_continuation(&self) -> bool {
+        self.eh_continuation_count() != 0
+    }
+    /// `guard_eh_continuation_count` if
+    /// `IMAGE_GUARD_EH_CONTINUATION_TABLE_PRESENT` is set, otherwise 0
+    #[must_use]
+    pub fn eh_continuation_count(&self) -> u64 {
+        if self.guard_flags() & IMAGE_GUARD_EH_CONTINUATION_TABLE_PRESENT == 0 {
+            return 0;
+        }
+        self.guard_eh_continuation_count.unwrap_or(0)
     }
 }
 
@@ -214,23 +353,346 @@ fn get_load_config_val(
     load_config_hdr: DataDirectory,
     sections: &[SectionTable],
     file_alignment: u32,
-) -> Result<ImageLoadConfigDirectory, scroll::Error> {
-    let dir_size = size_of::<ImageLoadConfigDirectory>();
+    is_64: bool,
+) -> Result<LoadConfig, scroll::Error> {
     let rva = load_config_hdr.virtual_address as usize;
-    if let Ok(offset) =
+    if rva == 0 {
+        return Err(scroll::Error::BadOffset(rva));
+    }
+    if let Some(offset) =
         find_offset(rva, sections, file_alignment, &ParseOptions::default())
-            .ok_or_else(|| {
-                goblin::error::Error::Malformed(
-                    load_config_hdr.virtual_address.to_string(),
-                )
-            })
     {
-        mem[offset..offset + dir_size].pread(0)
+        LoadConfig::parse(
+            mem.get(offset..).ok_or(scroll::Error::BadOffset(offset))?,
+            is_64,
+        )
     } else {
         Err(scroll::Error::BadOffset(rva))
     }
 }
 
+/// `IMAGE_DYNAMIC_RELOCATION_TABLE` header
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Default, Pread)]
+struct ImageDynamicRelocationTable {
+    version: u32,
+    size: u32,
+}
+
+/// walk the `IMAGE_DYNAMIC_RELOCATION_TABLE` referenced by
+/// `dynamic_value_reloc_table_offset`/`dynamic_value_reloc_table_section`
+/// and collect the `Symbol` of every `IMAGE_DYNAMIC_RELOCATION32/64(_V2)`
+/// entry
+fn get_dynamic_relocation_symbols(
+    mem: &[u8],
+    load_config: &LoadConfig,
+    sections: &[SectionTable],
+    is_64: bool,
+) -> Result<Vec<u64>, scroll::Error> {
+    let (Some(table_offset), Some(section_idx)) = (
+        load_config.dynamic_value_reloc_table_offset,
+        load_config.dynamic_value_reloc_table_section.map(usize::from),
+    ) else {
+        return Ok(vec![]);
+    };
+    if section_idx == 0 {
+        return Ok(vec![]);
+    }
+    let section = sections
+        .get(section_idx - 1)
+        .ok_or(scroll::Error::BadOffset(section_idx))?;
+    let start = section.pointer_to_raw_data as usize + table_offset as usize;
+    let table: ImageDynamicRelocationTable = mem.pread(start)?;
+    let entries_start = start + size_of::<ImageDynamicRelocationTable>();
+    let entries = mem
+        .get(entries_start..entries_start + table.size as usize)
+        .ok_or(scroll::Error::BadOffset(entries_start))?;
+
+    let mut symbols = Vec::new();
+    let mut offset = 0;
+    while offset < entries.len() {
+        match table.version {
+            1 => {
+                // Symbol, BaseRelocSize
+                let symbol = if is_64 {
+                    entries.gread::<u64>(&mut offset)?
+                } else {
+                    u64::from(entries.gread::<u32>(&mut offset)?)
+                };
+                let base_reloc_size = entries.gread::<u32>(&mut offset)?;
+                offset += base_reloc_size as usize;
+                symbols.push(symbol);
+            }
+            2 => {
+                // HeaderSize, FixupInfoSize, Symbol, SymbolGroup, Flags
+                let header_size = entries.pread::<u32>(offset)?;
+                let fixup_info_size = entries.pread::<u32>(offset + 4)?;
+                let symbol = if is_64 {
+                    entries.pread::<u64>(offset + 8)?
+                } else {
+                    u64::from(entries.pread::<u32>(offset + 8)?)
+                };
+                if header_size == 0 {
+                    break;
+                }
+                offset += header_size as usize + fixup_info_size as usize;
+                symbols.push(symbol);
+            }
+            _ => break,
+        }
+    }
+    Ok(symbols)
+}
+
+/// `ImgDelayDescr` delay-load import descriptor
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Default, Pread)]
+struct ImgDelayDescr {
+    attributes: u32,
+    dll_name_rva: u32,
+    module_handle_rva: u32,
+    import_address_table_rva: u32,
+    import_name_table_rva: u32,
+    bound_import_address_table_rva: u32,
+    unload_information_table_rva: u32,
+    time_date_stamp: u32,
+}
+
+/// walk the delay-load import descriptors and return the DLL and imported
+/// function names, `None` for ordinal-only imports
+fn get_delay_imports(pe: &PE, mem: &[u8]) -> Vec<(String, Option<String>)> {
+    let mut imports = Vec::new();
+    let Some(optional_header) = pe.header.optional_header else {
+        return imports;
+    };
+    let Some(directory) =
+        optional_header.data_directories.get_delay_import_descriptor()
+    else {
+        return imports;
+    };
+    let file_alignment = optional_header.windows_fields.file_alignment;
+    let to_offset = |address: u64, rva_based: bool| {
+        let rva = if rva_based {
+            address
+        } else {
+            address.checked_sub(pe.image_base)?
+        };
+        find_offset(
+            usize::try_from(rva).ok()?,
+            &pe.sections,
+            file_alignment,
+            &ParseOptions::default(),
+        )
+    };
+    let ordinal_flag = if pe.is_64 {
+        IMPORT_BY_ORDINAL_64
+    } else {
+        u64::from(IMPORT_BY_ORDINAL_32)
+    };
+    let Some(mut offset) = to_offset(directory.virtual_address.into(), true)
+    else {
+        return imports;
+    };
+    while let Ok(descriptor) = mem.gread::<ImgDelayDescr>(&mut offset) {
+        if descriptor.dll_name_rva == 0
+            || descriptor.import_name_table_rva == 0
+        {
+            break;
+        }
+        let rva_based = descriptor.attributes & DLATTR_RVA != 0;
+        let dll = to_offset(descriptor.dll_name_rva.into(), rva_based)
+            .and_then(|name_offset| mem.pread::<&str>(name_offset).ok())
+            .unwrap_or_default();
+        let Some(mut thunk_offset) =
+            to_offset(descriptor.import_name_table_rva.into(), rva_based)
+        else {
+            break;
+        };
+        loop {
+            let thunk = if pe.is_64 {
+                mem.gread::<u64>(&mut thunk_offset)
+            } else {
+                mem.gread::<u32>(&mut thunk_offset).map(u64::from)
+            };
+            let thunk = match thunk {
+                Ok(0) | Err(_) => break,
+                Ok(thunk) => thunk,
+            };
+            if thunk & ordinal_flag != 0 {
+                imports.push((dll.to_string(), None));
+                continue;
+            }
+            // IMAGE_IMPORT_BY_NAME: Hint, Name
+            if let Some(name) = to_offset(thunk, rva_based)
+                .and_then(|name_offset| mem.pread::<&str>(name_offset + 2).ok())
+            {
+                imports.push((dll.to_string(), Some(name.to_string())));
+            }
+        }
+    }
+    imports
+}
+
+/// whether a DLL is the C runtime: `msvcrt.dll` and the versioned
+/// `msvcr*.dll`, the Universal CRT `ucrtbase.dll` or its `api-ms-win-crt-*`
+/// API sets
+fn is_crt_dll(dll: &str) -> bool {
+    let dll = dll.to_ascii_lowercase();
+    dll.starts_with("msvcr")
+        || dll.starts_with("ucrtbase")
+        || dll.starts_with("api-ms-win-crt-")
+}
+
+/// List of API names reported as dangerous imports, defaults to
+/// [`DANGEROUS_IMPORTS`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ImportPolicy {
+    pub dangerous: Vec<String>,
+}
+impl Default for ImportPolicy {
+    fn default() -> Self {
+        Self::new(DANGEROUS_IMPORTS.iter().copied())
+    }
+}
+impl ImportPolicy {
+    #[must_use]
+    pub fn new<I, S>(dangerous: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self { dangerous: dangerous.into_iter().map(Into::into).collect() }
+    }
+    /// Parse a list with one API name per line, ignoring blank lines and
+    /// `#` comments
+    #[must_use]
+    pub fn from_list(list: &str) -> Self {
+        Self::new(
+            list.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#')),
+        )
+    }
+    fn is_dangerous(&self, name: &str) -> bool {
+        self.dangerous.iter().any(|dangerous| dangerous == name)
+    }
+}
+
+/// `CodeView` `RSDS` (PDB 7.0) debug information
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
+pub struct PdbInfo {
+    /// PDB signature GUID
+    pub guid: String,
+    /// PDB age
+    pub age: u32,
+    /// PDB path as recorded by the linker
+    pub path: String,
+}
+impl PdbInfo {
+    /// Symbol server lookup key (`<GUID><age>`), as used in
+    /// `<pdb name>/<key>/<pdb name>`
+    #[must_use]
+    pub fn symbol_server_key(&self) -> String {
+        format!("{}{:X}", self.guid.replace('-', ""), self.age)
+    }
+}
+
+/// `IMAGE_DEBUG_TYPE_VC_FEATURE` object counts
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
+pub struct VcFeatures {
+    /// objects built with a pre-VC++ 11.0 compiler
+    pub pre_vc_plusplus: u32,
+    /// C/C++ objects
+    pub c_and_cplusplus: u32,
+    /// objects built with `/GS`
+    pub gs: u32,
+    /// objects built with `/sdl`
+    pub sdl: u32,
+    /// objects built with `/guard:cf`
+    pub guard: u32,
+}
+
+/// format a GUID stored as `Data1`, `Data2`, `Data3` (little endian) and
+/// `Data4`
+fn format_guid(guid: &[u8; 16]) -> String {
+    format!(
+        "{:08X}-{:04X}-{:04X}-{:02X}{:02X}-\
+        {:02X}{:02X}{:02X}{:02X}{:02X}{:02X}",
+        u32::from_le_bytes([guid[0], guid[1], guid[2], guid[3]]),
+        u16::from_le_bytes([guid[4], guid[5]]),
+        u16::from_le_bytes([guid[6], guid[7]]),
+        guid[8],
+        guid[9],
+        guid[10],
+        guid[11],
+        guid[12],
+        guid[13],
+        guid[14],
+        guid[15]
+    )
+}
+
+fn get_pdb_info(pe: &PE) -> Option<PdbInfo> {
+    let codeview = pe.debug_data.as_ref()?.codeview_pdb70_debug_info?;
+    let path = codeview
+        .filename
+        .split(|b| *b == 0)
+        .next()
+        .unwrap_or_default();
+    Some(PdbInfo {
+        guid: format_guid(&codeview.signature),
+        age: codeview.age,
+        path: String::from_utf8_lossy(path).into_owned(),
+    })
+}
+
+fn get_debug_info(pe: &PE, bytes: &[u8], pdb: Option<&PdbInfo>) -> DebugInfo {
+    let mut debug_sections = Vec::new();
+    let mut dwarf_version = None;
+    // MinGW keeps DWARF in `.debug_*` sections named via the COFF string
+    // table
+    for section in &pe.sections {
+        let Ok(name) = section.name() else {
+            continue;
+        };
+        if !debuginfo::is_debug_section(name) {
+            continue;
+        }
+        if let Ok(Some(data)) = section.data(bytes) {
+            dwarf_version = match name {
+                ".debug_info" => debuginfo::dwarf_version(&data, true),
+                ".zdebug_info" => debuginfo::zdebug_data(&data)
+                    .and_then(debuginfo::inflate_header)
+                    .and_then(|header| debuginfo::dwarf_version(&header, true)),
+                _ => dwarf_version,
+            };
+        }
+        debug_sections.push(name.to_string());
+    }
+    let symbols = pe.header.coff_header.number_of_symbol_table > 0;
+    DebugInfo {
+        stripped: Stripped::new(symbols, !debug_sections.is_empty()),
+        symbols,
+        debug_sections,
+        dwarf_version,
+        stabs: false,
+        debug_file: pdb.map(|pdb| pdb.path.clone()),
+        alt_debug_file: None,
+        debug_id: pdb.map(PdbInfo::symbol_server_key),
+    }
+}
+
+fn get_vc_features(pe: &PE) -> Option<VcFeatures> {
+    let info = pe.debug_data.as_ref()?.vcfeature_info?;
+    Some(VcFeatures {
+        pre_vc_plusplus: info.pre_vc_plusplus_count,
+        c_and_cplusplus: info.c_and_cplusplus_count,
+        gs: info.guard_stack_count,
+        sdl: info.sdl_count,
+        guard: info.guard_count,
+    })
+}
+
 /// Address Space Layout Randomization: `None`, `DYNBASE`, or `HIGHENTROPYVA`
 #[derive(Clone, Deserialize, Serialize, Debug, Eq, PartialEq)]
 pub enum ASLR {
@@ -315,6 +777,8 @@ pub struct CheckSecResults {
     pub force_integrity: bool,
     /// Buffer Security Check (`/GS`)
     pub gs: bool,
+    /// Stack probing (`__chkstk`, `/Gs`, clang-cl `-mstack-probe-size`)
+    pub stack_probe: StackProbe,
     /// 64-bit ASLR (`/HIGHENTROPYVA`)
     pub high_entropy_va: bool,
     /// Allow Isolation (`/ALLOWISOLATION`)
@@ -325,27 +789,164 @@ pub struct CheckSecResults {
     pub safeseh: bool,
     /// Structured Exception Handler
     pub seh: bool,
-    // CET Compatible
+    /// CET Shadow Stack Compatible (`/CETCOMPAT`)
     pub cet: bool,
+    /// CET Shadow Stack Strict Mode (`/CETCOMPATSTRICT`)
+    pub cet_strict: bool,
+    /// CET `SetContext` IP Validation Relaxed Mode
+    /// (`/CETIPVALIDATIONRELAXED`)
+    pub cet_ip_validation_relaxed: bool,
+    /// Forward CFI Compatible (`/CETFORWARDCFI`)
+    pub forward_cfi: bool,
+    /// EH Continuation Metadata (`/guard:ehcont`)
+    pub eh_continuation: bool,
+    /// Number of EH continuation targets
+    pub eh_continuation_count: u64,
+    /// Retpoline (`/guard:retpoline`) dynamic value relocations
+    pub retpoline: bool,
+    /// ARM64X dynamic value relocations
+    pub arm64x: bool,
+    /// Sanitizer, coverage and fuzzing instrumentation (`/fsanitize=address`,
+    /// `clang-cl -fsanitize=*`)
+    pub sanitizers: VecSanitizer,
+    /// CLI header and metadata checks for .NET assemblies
+    pub clr: Option<dotnet::CheckSecResults>,
+    /// Dangerous imports from the import and delay-import tables
+    pub dangerous_imports: Vec<String>,
+    /// Number of imports by ordinal only
+    pub ordinal_imports: usize,
+    /// Number of secure CRT (`*_s`) functions imported from the C runtime
+    pub secure_crt_imports: usize,
+    /// Manifest `requestedExecutionLevel`
+    pub execution_level: ExecutionLevel,
+    /// Manifest `uiAccess` (bypasses UIPI)
+    pub ui_access: bool,
+    /// Manifest `autoElevate`
+    pub auto_elevate: bool,
+    /// Manifest side-by-side dependencies
+    pub sxs_dependencies: Vec<String>,
+    /// COFF header `TimeDateStamp` *(a hash for reproducible builds)*
+    pub timestamp: u32,
+    /// `CodeView` PDB reference
+    pub pdb: Option<PdbInfo>,
+    /// Reproducible build (`IMAGE_DEBUG_TYPE_REPRO`)
+    pub repro: bool,
+    /// Number of `IMAGE_DEBUG_TYPE_POGO` entries
+    pub pogo_entries: usize,
+    /// `IMAGE_DEBUG_TYPE_VC_FEATURE` object counts
+    pub vc_features: Option<VcFeatures>,
+    /// Rich header toolchain fingerprint
+    pub rich: Option<RichHeader>,
+    /// Debug info and symbol stripping
+    pub debug_info: DebugInfo,
+    /// Retpoline, speculative load hardening (`/Qspectre`) and
+    /// straight-line speculation mitigations in x86/x64 code
+    pub speculation: Option<SpeculationMitigations>,
 }
 impl CheckSecResults {
     #[must_use]
     pub fn parse(pe: &PE, buffer: &[u8]) -> Self {
+        Self::parse_with_policy(pe, buffer, &ImportPolicy::default())
+    }
+    /// parse with a user-supplied list of dangerous imports
+    #[must_use]
+    pub fn parse_with_policy(
+        pe: &PE,
+        buffer: &[u8],
+        import_policy: &ImportPolicy,
+    ) -> Self {
+        // split into functions once for all the code checks
+        let blocks = get_function_code(pe, buffer);
+        let load_config = pe.load_config(buffer);
+        let load_config = load_config.as_ref();
+        let dynamic_relocations = load_config
+            .map(|cfg| get_dynamic_relocations(pe, buffer, cfg))
+            .unwrap_or_default();
+        // DLL and function name, goblin reports ordinal-only imports without
+        // a hint/name rva
+        let imports: Vec<(String, Option<String>)> = pe
+            .imports
+            .iter()
+            .map(|import| {
+                let name = (import.rva != 0).then(|| import.name.to_string());
+                (import.dll.to_string(), name)
+            })
+            .chain(get_delay_imports(pe, buffer))
+            .collect();
+        let mut dangerous_imports: Vec<String> = imports
+            .iter()
+            .filter_map(|(_, name)| name.as_ref())
+            .filter(|name| import_policy.is_dangerous(name))
+            .cloned()
+            .collect();
+        dangerous_imports.sort();
+        dangerous_imports.dedup();
+        let pdb = get_pdb_info(pe);
+        let manifest = Resources::parse(pe, buffer)
+            .and_then(|resources| resources.manifest())
+            .map(|xml| Manifest::parse(&xml))
+            .unwrap_or_default();
         Self {
             aslr: pe.has_aslr(),
-            authenticode: pe.has_authenticode(buffer),
+            authenticode: authenticode(pe, load_config),
             cfg: pe.has_cfg(),
             dotnet: pe.has_dotnet(),
             nx: pe.has_nx(),
             dynamic_base: pe.has_dynamic_base(),
             force_integrity: pe.has_force_integrity(),
-            gs: pe.has_gs(buffer),
+            gs: load_config.is_some_and(LoadConfig::has_gs),
+            stack_probe: pe.has_stack_probe(buffer, &blocks),
             high_entropy_va: pe.has_high_entropy_va(),
             isolation: pe.has_isolation(),
-            rfg: pe.has_rfg(buffer),
-            safeseh: pe.has_safe_seh(buffer),
+            rfg: load_config.is_some_and(LoadConfig::has_rfg),
+            safeseh: load_config.is_some_and(LoadConfig::has_safe_seh),
             seh: pe.has_seh(),
-            cet: pe.is_cet_compat()
+            cet: pe.is_cet_compat(),
+            cet_strict: pe.is_cet_strict(),
+            cet_ip_validation_relaxed: pe.is_cet_ip_validation_relaxed(),
+            forward_cfi: pe.is_forward_cfi_compat(),
+            eh_continuation: load_config
+                .is_some_and(LoadConfig::has_eh_continuation),
+            eh_continuation_count: load_config
+                .map_or(0, LoadConfig::eh_continuation_count),
+            retpoline: has_retpoline_relocations(&dynamic_relocations),
+            arm64x: dynamic_relocations
+                .contains(&IMAGE_DYNAMIC_RELOCATION_ARM64X),
+            sanitizers: pe.has_sanitizers(buffer),
+            clr: dotnet::CheckSecResults::parse(pe, buffer),
+            dangerous_imports,
+            ordinal_imports: imports
+                .iter()
+                .filter(|(_, name)| name.is_none())
+                .count(),
+            secure_crt_imports: imports
+                .iter()
+                .filter(|(dll, name)| {
+                    is_crt_dll(dll)
+                        && name.as_deref().is_some_and(|name| {
+                            name.ends_with("_s")
+                        })
+                })
+                .count(),
+            execution_level: manifest.execution_level,
+            ui_access: manifest.ui_access,
+            auto_elevate: manifest.auto_elevate,
+            sxs_dependencies: manifest.dependencies,
+            timestamp: pe.header.coff_header.time_date_stamp,
+            debug_info: get_debug_info(pe, buffer, pdb.as_ref()),
+            pdb,
+            repro: pe
+                .debug_data
+                .as_ref()
+                .is_some_and(|debug_data| debug_data.repro_info.is_some()),
+            pogo_entries: pe
+                .debug_data
+                .as_ref()
+                .and_then(|debug_data| debug_data.pogo_info)
+                .map_or(0, |pogo| pogo.entries().flatten().count()),
+            vc_features: get_vc_features(pe),
+            rich: RichHeader::parse(pe),
+            speculation: pe.speculation_mitigations(&blocks),
         }
     }
 }
@@ -356,8 +957,13 @@ impl fmt::Display for CheckSecResults {
         write!(
             f,
             "ASLR: {} Authenticode: {} CFG: {} .NET: {} NX: {} \
-            Dynamic Base: {} Force Integrity: {} GS: {} \
-            High Entropy VA: {} Isolation: {} RFG: {} SafeSEH: {} SEH: {}",
+            Dynamic Base: {} Force Integrity: {} GS: {} Stack Probe: {} \
+            High Entropy VA: {} Isolation: {} RFG: {} SafeSEH: {} SEH: {} \
+            CET: {} CET Strict: {} CET IP Relaxed: {} Forward CFI: {} \
+            EH Continuation: {} Retpoline: {} ARM64X: {} Sanitizers: {} \
+            Dangerous Imports: {} Ordinal Imports: {} Secure CRT: {} \
+            Execution Level: {} UIAccess: {} Auto Elevate: {} \
+            SxS Dependencies: {} Repro: {} PDB: {} Stripped: {}",
             self.aslr,
             self.authenticode,
             self.cfg,
@@ -366,20 +972,62 @@ impl fmt::Display for CheckSecResults {
             self.dynamic_base,
             self.force_integrity,
             self.gs,
+            self.stack_probe,
             self.high_entropy_va,
             self.isolation,
             self.rfg,
             self.safeseh,
-            self.seh
-        )
+            self.seh,
+            self.cet,
+            self.cet_strict,
+            self.cet_ip_validation_relaxed,
+            self.forward_cfi,
+            self.eh_continuation,
+            self.retpoline,
+            self.arm64x,
+            self.sanitizers,
+            self.dangerous_imports.len(),
+            self.ordinal_imports,
+            self.secure_crt_imports,
+            self.execution_level,
+            self.ui_access,
+            self.auto_elevate,
+            self.sxs_dependencies.len(),
+            self.repro,
+            self.pdb.as_ref().map_or("None", |pdb| pdb.path.as_str()),
+            self.debug_info.stripped
+        )?;
+        if let Some(clr) = &self.clr {
+            write!(f, " .NET Assembly: {clr}")?;
+        }
+        if let Some(rich) = &self.rich {
+            write!(
+                f,
+                " Toolset: {} Toolset Lacks: {}",
+                rich.toolset.as_deref().unwrap_or("Unknown"),
+                if rich.unsupported.is_empty() {
+                    "None".to_string()
+                } else {
+                    rich.unsupported.join(",")
+                }
+            )?;
+        }
+        if let Some(speculation) = &self.speculation {
+            write!(f, " {speculation}")?;
+        }
+        Ok(())
     }
     #[cfg(feature = "color")]
+    #[allow(clippy::too_many_lines)]
     /// Colorized human readable format output
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "{} {} {} {} {} {} {} {} {} {} {} {} {} {} \
-             {} {} {} {} {} {} {} {} {} {} {} {}",
+            "{} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} \
+             {} {} {} {} {} {} {} {} {} {} {} {} \
+             {} {} {} {} {} {} {} {} {} {} {} {} {} {} \
+             {} {} {} {} {} {} {} {} {} {} {} {} {} {} \
+             {} {} {} {} {} {} {} {}",
             "ASLR:".bold(),
             self.aslr,
             "Authenticode:".bold(),
@@ -396,6 +1044,8 @@ impl fmt::Display for CheckSecResults {
             colorize_bool!(self.force_integrity),
             "GS:".bold(),
             colorize_bool!(self.gs),
+            "Stack Probe:".bold(),
+            self.stack_probe,
             "High Entropy VA:".bold(),
             colorize_bool!(self.high_entropy_va),
             "Isolation:".bold(),
@@ -405,8 +1055,71 @@ impl fmt::Display for CheckSecResults {
             "SafeSEH:".bold(),
             colorize_bool!(self.safeseh),
             "SEH:".bold(),
-            colorize_bool!(self.seh)
-        )
+            colorize_bool!(self.seh),
+            "CET:".bold(),
+            colorize_bool!(self.cet),
+            "CET Strict:".bold(),
+            colorize_bool!(self.cet_strict),
+            "CET IP Relaxed:".bold(),
+            // relaxed IP validation weakens the shadow stack
+            colorize_bool!(inverted self.cet_ip_validation_relaxed),
+            "Forward CFI:".bold(),
+            colorize_bool!(self.forward_cfi),
+            "EH Continuation:".bold(),
+            colorize_bool!(self.eh_continuation),
+            "Retpoline:".bold(),
+            colorize_bool!(self.retpoline),
+            "ARM64X:".bold(),
+            colorize_bool!(self.arm64x),
+            "Sanitizers:".bold(),
+            self.sanitizers,
+            "Dangerous Imports:".bold(),
+            if self.dangerous_imports.is_empty() {
+                "0".bright_green()
+            } else {
+                self.dangerous_imports.len().to_string().red()
+            },
+            "Ordinal Imports:".bold(),
+            self.ordinal_imports,
+            "Secure CRT:".bold(),
+            self.secure_crt_imports,
+            "Execution Level:".bold(),
+            self.execution_level,
+            // both widen the attack surface of elevated processes
+            "UIAccess:".bold(),
+            colorize_bool!(inverted self.ui_access),
+            "Auto Elevate:".bold(),
+            colorize_bool!(inverted self.auto_elevate),
+            "SxS Dependencies:".bold(),
+            self.sxs_dependencies.len(),
+            "Repro:".bold(),
+            colorize_bool!(self.repro),
+            "PDB:".bold(),
+            self.pdb.as_ref().map_or("None", |pdb| pdb.path.as_str()),
+            "Stripped:".bold(),
+            self.debug_info.stripped
+        )?;
+        if let Some(clr) = &self.clr {
+            write!(f, " {} {}", ".NET Assembly:".bold(), clr)?;
+        }
+        if let Some(rich) = &self.rich {
+            write!(
+                f,
+                " {} {} {} {}",
+                "Toolset:".bold(),
+                rich.toolset.as_deref().unwrap_or("Unknown"),
+                "Toolset Lacks:".bold(),
+                if rich.unsupported.is_empty() {
+                    "None".bright_green()
+                } else {
+                    rich.unsupported.join(",").red()
+                }
+            )?;
+        }
+        if let Some(speculation) = &self.speculation {
+            write!(f, " {speculation}")?;
+        }
+        Ok(())
     }
 }
 
@@ -475,6 +1188,19 @@ pub trait Properties {
     /// of the original file to read & parse required information from the
     /// underlying binary file
     fn has_gs(&self, bytes: &[u8]) -> bool;
+    /// find stack probe loops, unrolled probes and calls to `__chkstk`,
+    /// `__chkstk_ms` or `_alloca_probe`, found by COFF symbol or, on x64,
+    /// by their code, in `(address, code)` blocks, usually one per function
+    ///
+    /// requires a
+    /// [`memmap2::Mmap`](https://docs.rs/memmap2/0.5.7/memmap2/struct.Mmap.html)
+    /// of the original file to read & parse required information from the
+    /// underlying binary file
+    fn has_stack_probe(
+        &self,
+        bytes: &[u8],
+        blocks: &[(u64, &[u8])],
+    ) -> StackProbe;
     /// check for `IMAGE_DLLCHARACTERISTICS_HIGH_ENTROPY_VA` *(`0x0020`)* in
     /// `DllCharacteristics` within the `IMAGE_OPTIONAL_HEADER32/64`
     fn has_high_entropy_va(&self) -> bool;
@@ -502,10 +1228,182 @@ pub trait Properties {
     /// check `IMAGE_DLLCHARACTERISTICS_NO_SEH` from the
     /// `IMAGE_OPTIONAL_HEADER32/64`
     fn has_seh(&self) -> bool;
-    /// check `IMAGE_DLLCHARACTERISTICS_EX_CET_COMPAT` from the
-    /// `IMAGE_OPTIONAL_HEADER32/64`
+    /// check `IMAGE_DLLCHARACTERISTICS_EX_CET_COMPAT` *(0x01)* in the
+    /// `IMAGE_DEBUG_TYPE_EX_DLLCHARACTERISTICS` debug directory entry
     fn is_cet_compat(&self) -> bool;
+    /// check `IMAGE_DLLCHARACTERISTICS_EX_CET_COMPAT_STRICT_MODE` *(0x02)* in
+    /// the `IMAGE_DEBUG_TYPE_EX_DLLCHARACTERISTICS` debug directory entry
+    fn is_cet_strict(&self) -> bool;
+    /// check
+    /// `IMAGE_DLLCHARACTERISTICS_EX_CET_SET_CONTEXT_IP_VALIDATION_RELAXED_MODE`
+    /// *(0x04)* in the `IMAGE_DEBUG_TYPE_EX_DLLCHARACTERISTICS` debug
+    /// directory entry
+    fn is_cet_ip_validation_relaxed(&self) -> bool;
+    /// check `IMAGE_DLLCHARACTERISTICS_EX_FORWARD_CFI_COMPAT` *(0x40)* in the
+    /// `IMAGE_DEBUG_TYPE_EX_DLLCHARACTERISTICS` debug directory entry
+    fn is_forward_cfi_compat(&self) -> bool;
+    /// check `guard_flags` for `IMAGE_GUARD_EH_CONTINUATION_TABLE_PRESENT`
+    /// *(`0x0040_0000`)* along with a non-zero `guard_eh_continuation_count`
+    /// in the `IMAGE_LOAD_CONFIG_DIRECTORY32/64`
+    ///
+    /// requires a
+    /// [`memmap2::Mmap`](https://docs.rs/memmap2/0.5.7/memmap2/struct.Mmap.html)
+    /// of the original file to read & parse required information from the
+    /// underlying binary file
+    fn has_eh_continuation(&self, bytes: &[u8]) -> bool;
+    /// return `guard_eh_continuation_count` from the
+    /// `IMAGE_LOAD_CONFIG_DIRECTORY32/64`
+    ///
+    /// requires a
+    /// [`memmap2::Mmap`](https://docs.rs/memmap2/0.5.7/memmap2/struct.Mmap.html)
+    /// of the original file to read & parse required information from the
+    /// underlying binary file
+    fn eh_continuation_count(&self, bytes: &[u8]) -> u64;
+    /// check the dynamic value relocation table for
+    /// `IMAGE_DYNAMIC_RELOCATION_GUARD_IMPORT_CONTROL_TRANSFER`,
+    /// `IMAGE_DYNAMIC_RELOCATION_GUARD_INDIR_CONTROL_TRANSFER` or
+    /// `IMAGE_DYNAMIC_RELOCATION_GUARD_SWITCHTABLE_BRANCH` entries
+    ///
+    /// requires a
+    /// [`memmap2::Mmap`](https://docs.rs/memmap2/0.5.7/memmap2/struct.Mmap.html)
+    /// of the original file to read & parse required information from the
+    /// underlying binary file
+    fn has_retpoline(&self, bytes: &[u8]) -> bool;
+    /// check the dynamic value relocation table for
+    /// `IMAGE_DYNAMIC_RELOCATION_ARM64X` entries
+    ///
+    /// requires a
+    /// [`memmap2::Mmap`](https://docs.rs/memmap2/0.5.7/memmap2/struct.Mmap.html)
+    /// of the original file to read & parse required information from the
+    /// underlying binary file
+    fn has_arm64x(&self, bytes: &[u8]) -> bool;
+    /// check imports, delay imports, exports and imported library names for
+    /// sanitizer, coverage and fuzzing runtimes
+    ///
+    /// requires a
+    /// [`memmap2::Mmap`](https://docs.rs/memmap2/0.5.7/memmap2/struct.Mmap.html)
+    /// of the original file to read & parse required information from the
+    /// underlying binary file
+    fn has_sanitizers(&self, bytes: &[u8]) -> VecSanitizer;
+    /// count retpoline thunks, indirect branches, `lfence` barriers and
+    /// `int3` after `ret`/`jmp` in `(address, code)` blocks, `None` for
+    /// non-x86 images or without the `disassembly` feature
+    fn speculation_mitigations(
+        &self,
+        blocks: &[(u64, &[u8])],
+    ) -> Option<SpeculationMitigations>;
+    /// parse the `IMAGE_LOAD_CONFIG_DIRECTORY32/64` referenced by the
+    /// `IMAGE_OPTIONAL_HEADER32/64`, honouring its declared `size`
+    ///
+    /// requires a
+    /// [`memmap2::Mmap`](https://docs.rs/memmap2/0.5.7/memmap2/struct.Mmap.html)
+    /// of the original file to read & parse required information from the
+    /// underlying binary file
+    fn load_config(&self, bytes: &[u8]) -> Option<LoadConfig>;
+}
+
+/// return `characteristics_ex` from the
+/// `IMAGE_DEBUG_TYPE_EX_DLLCHARACTERISTICS` debug directory entry, or 0
+fn get_ex_dll_characteristics(pe: &PE) -> u32 {
+    pe.debug_data
+        .as_ref()
+        .and_then(|debug_data| debug_data.ex_dll_characteristics_info)
+        .map_or(0, |info| info.characteristics_ex)
+}
+
+/// collect the dynamic value relocation symbols of a PE, if any
+fn get_dynamic_relocations(
+    pe: &PE,
+    bytes: &[u8],
+    load_config: &LoadConfig,
+) -> Vec<u64> {
+    get_dynamic_relocation_symbols(bytes, load_config, &pe.sections, pe.is_64)
+        .unwrap_or_default()
 }
+
+fn has_retpoline_relocations(symbols: &[u64]) -> bool {
+    symbols.iter().any(|symbol| {
+        matches!(
+            *symbol,
+            IMAGE_DYNAMIC_RELOCATION_GUARD_IMPORT_CONTROL_TRANSFER
+                | IMAGE_DYNAMIC_RELOCATION_GUARD_INDIR_CONTROL_TRANSFER
+                | IMAGE_DYNAMIC_RELOCATION_GUARD_SWITCHTABLE_BRANCH
+        )
+    })
+}
+
+/// addresses of probe functions from the COFF symbol table MinGW leaves in
+#[cfg(feature = "disassembly")]
+fn get_probe_routines(pe: &PE, bytes: &[u8]) -> Vec<u64> {
+    let coff_header = &pe.header.coff_header;
+    let (Ok(Some(symbols)), Ok(Some(strings))) =
+        (coff_header.symbols(bytes), coff_header.strings(bytes))
+    else {
+        return Vec::new();
+    };
+    symbols
+        .iter()
+        .filter_map(|(_, name, symbol)| {
+            let name = name.or_else(|| symbol.name(&strings).ok())?;
+            let section = usize::try_from(symbol.section_number).ok()?;
+            let section = pe.sections.get(section.checked_sub(1)?)?;
+            disassembly::is_probe_routine(name).then(|| {
+                pe.image_base
+                    + u64::from(section.virtual_address)
+                    + u64::from(symbol.value)
+            })
+        })
+        .collect()
+}
+
+/// code of executable sections, split into functions where `.pdata` tells
+/// where they are, empty without the `disassembly` feature
+#[allow(unused_variables)]
+fn get_function_code<'a>(pe: &PE, bytes: &'a [u8]) -> Vec<(u64, &'a [u8])> {
+    #[cfg(not(feature = "disassembly"))]
+    return Vec::new();
+    #[cfg(feature = "disassembly")]
+    {
+        let functions = Functions::from_pe(pe);
+        pe.sections
+            .iter()
+            .filter(|section| {
+                section.characteristics & IMAGE_SCN_MEM_EXECUTE != 0
+            })
+            .filter_map(|section| {
+                let start = section.pointer_to_raw_data as usize;
+                // raw data is padded to the file alignment
+                let size = match section.virtual_size {
+                    0 => section.size_of_raw_data,
+                    size => size.min(section.size_of_raw_data),
+                } as usize;
+                Some(functions.code(
+                    bytes.get(start..start + size)?,
+                    pe.image_base + u64::from(section.virtual_address),
+                ))
+            })
+            .flatten()
+            .collect()
+    }
+}
+
+/// check for a certificate table or `IMAGE_LOAD_CONFIG_CODE_INTEGRITY` flags
+fn authenticode(pe: &PE, load_config: Option<&LoadConfig>) -> bool {
+    // requires running platform to be Windows for verification
+    // just check for existence right now
+    let certificate = pe
+        .header
+        .optional_header
+        .and_then(|optional_header| {
+            optional_header.data_directories.get_certificate_table().copied()
+        })
+        .is_some_and(|certificate_table| certificate_table.virtual_address != 0);
+    let code_integrity = load_config
+        .and_then(|cfg| cfg.code_integrity)
+        .is_some_and(|code_integrity| code_integrity.flags != 0);
+    certificate || code_integrity
+}
+
 impl Properties for PE<'_> {
     fn has_aslr(&self) -> ASLR {
         if self.has_dynamic_base() & self.has_high_entropy_va() {
@@ -516,31 +1414,7 @@ impl Properties for PE<'_> {
         ASLR::None
     }
     fn has_authenticode(&self, bytes: &[u8]) -> bool {
-        // requires running platform to be Windows for verification
-        // just check for existence right now
-        if let Some(optional_header) = self.header.optional_header {
-            let file_alignment = optional_header.windows_fields.file_alignment;
-            let sections = &self.sections;
-            if let Some(load_config_hdr) =
-                optional_header.data_directories.get_load_config_table()
-            {
-                if let Ok(load_config_val) = get_load_config_val(
-                    bytes,
-                    *load_config_hdr,
-                    sections,
-                    file_alignment,
-                ) {
-                    if let Some(certificate_table) = optional_header
-                        .data_directories
-                        .get_certificate_table()
-                    {
-                        return load_config_val.code_integrity.flags != 0
-                            || certificate_table.virtual_address != 0;
-                    }
-                }
-            }
-        }
-        false
+        authenticode(self, self.load_config(bytes).as_ref())
     }
     fn has_cfg(&self) -> bool {
         if let Some(optional_header) = self.header.optional_header {
@@ -599,23 +1473,21 @@ impl Properties for PE<'_> {
         false
     }
     fn has_gs(&self, bytes: &[u8]) -> bool {
-        if let Some(optional_header) = self.header.optional_header {
-            let file_alignment = optional_header.windows_fields.file_alignment;
-            let sections = &self.sections;
-            if let Some(load_config_hdr) =
-                optional_header.data_directories.get_load_config_table()
-            {
-                if let Ok(load_config_val) = get_load_config_val(
-                    bytes,
-                    *load_config_hdr,
-                    sections,
-                    file_alignment,
-                ) {
-                    return load_config_val.security_cookie != 0;
-                }
-            }
-        }
-        false
+        self.load_config(bytes).is_some_and(|cfg| cfg.has_gs())
+    }
+    #[allow(unused_variables)]
+    fn has_stack_probe(
+        &self,
+        bytes: &[u8],
+        blocks: &[(u64, &[u8])],
+    ) -> StackProbe {
+        #[cfg(not(feature = "disassembly"))]
+        return StackProbe::None;
+        #[cfg(feature = "disassembly")]
+        Architecture::from_pe(self.header.coff_header.machine).map_or(
+            StackProbe::None,
+            |arch| arch.stack_probe(blocks, get_probe_routines(self, bytes)),
+        )
     }
     fn has_high_entropy_va(&self) -> bool {
         if let Some(optional_header) = self.header.optional_header {
@@ -640,48 +1512,10 @@ impl Properties for PE<'_> {
         false
     }
     fn has_rfg(&self, bytes: &[u8]) -> bool {
-        if let Some(optional_header) = self.header.optional_header {
-            let file_alignment = optional_header.windows_fields.file_alignment;
-            let sections = &self.sections;
-            if let Some(load_config_hdr) =
-                optional_header.data_directories.get_load_config_table()
-            {
-                if let Ok(load_config_val) = get_load_config_val(
-                    bytes,
-                    *load_config_hdr,
-                    sections,
-                    file_alignment,
-                ) {
-                    let guard_flags = load_config_val.guard_flags;
-                    if (guard_flags & IMAGE_GUARD_RF_INSTRUMENTED) != 0
-                        && (guard_flags & IMAGE_GUARD_RF_ENABLE) != 0
-                        || (guard_flags & IMAGE_GUARD_RF_STRICT) != 0
-                    {
-                        return true;
-                    }
-                }
-            }
-        }
-        false
+        self.load_config(bytes).is_some_and(|cfg| cfg.has_rfg())
     }
     fn has_safe_seh(&self, bytes: &[u8]) -> bool {
-        if let Some(optional_header) = self.header.optional_header {
-            let file_alignment = optional_header.windows_fields.file_alignment;
-            let sections = &self.sections;
-            if let Some(load_config_hdr) =
-                optional_header.data_directories.get_load_config_table()
-            {
-                if let Ok(load_config_val) = get_load_config_val(
-                    bytes,
-                    *load_config_hdr,
-                    sections,
-                    file_alignment,
-                ) {
-                    return load_config_val.sehandler_count != 0;
-                }
-            }
-        }
-        false
+        self.load_config(bytes).is_some_and(|cfg| cfg.has_safe_seh())
     }
     fn has_seh(&self) -> bool {
         #[allow(clippy::match_wildcard_for_single_variants)]
@@ -698,17 +1532,83 @@ impl Properties for PE<'_> {
         }
     }
     fn is_cet_compat(&self) -> bool {
-         match &self.debug_data {
-            Some(debug_data) => {
-                match debug_data.ex_dll_characteristics_info {
-                    Some(dllcharacteristics) => {
-                    println!("{}", IMAGE_DLLCHARACTERISTICS_EX_CET_COMPAT & dllcharacteristics.characteristics_ex);
-                    (dllcharacteristics.characteristics_ex & IMAGE_DLLCHARACTERISTICS_EX_CET_COMPAT) != 0
-                    }
-                    _ => false,
-                }
-            }
-            _ => false,
+        get_ex_dll_characteristics(self) & IMAGE_DLLCHARACTERISTICS_EX_CET_COMPAT
+            != 0
+    }
+    fn is_cet_strict(&self) -> bool {
+        get_ex_dll_characteristics(self)
+            & IMAGE_DLLCHARACTERISTICS_EX_CET_COMPAT_STRICT_MODE
+            != 0
+    }
+    fn is_cet_ip_validation_relaxed(&self) -> bool {
+        get_ex_dll_characteristics(self)
+            & IMAGE_DLLCHARACTERISTICS_EX_CET_SET_CONTEXT_IP_VALIDATION_RELAXED_MODE
+            != 0
+    }
+    fn is_forward_cfi_compat(&self) -> bool {
+        get_ex_dll_characteristics(self)
+            & IMAGE_DLLCHARACTERISTICS_EX_FORWARD_CFI_COMPAT
+            != 0
+    }
+    fn has_eh_continuation(&self, bytes: &[u8]) -> bool {
+        self.load_config(bytes).is_some_and(|cfg| cfg.has_eh_continuation())
+    }
+    fn eh_continuation_count(&self, bytes: &[u8]) -> u64 {
+        self.load_config(bytes).map_or(0, |cfg| cfg.eh_continuation_count())
+    }
+    fn has_retpoline(&self, bytes: &[u8]) -> bool {
+        self.load_config(bytes).is_some_and(|cfg| {
+            has_retpoline_relocations(&get_dynamic_relocations(
+                self, bytes, &cfg,
+            ))
+        })
+    }
+    fn has_arm64x(&self, bytes: &[u8]) -> bool {
+        self.load_config(bytes).is_some_and(|cfg| {
+            get_dynamic_relocations(self, bytes, &cfg)
+                .contains(&IMAGE_DYNAMIC_RELOCATION_ARM64X)
+        })
+    }
+    fn has_sanitizers(&self, bytes: &[u8]) -> VecSanitizer {
+        let delay_imports = get_delay_imports(self, bytes);
+        let delay_names =
+            delay_imports.iter().filter_map(|(_, name)| name.as_deref());
+        VecSanitizer::detect(
+            self.imports
+                .iter()
+                .map(|import| import.name.as_ref())
+                .chain(delay_names)
+                .chain(self.exports.iter().filter_map(|export| export.name)),
+            self.libraries.iter().copied(),
+        )
+    }
+    #[allow(unused_variables)]
+    fn speculation_mitigations(
+        &self,
+        blocks: &[(u64, &[u8])],
+    ) -> Option<SpeculationMitigations> {
+        #[cfg(not(feature = "disassembly"))]
+        return None;
+        #[cfg(feature = "disassembly")]
+        {
+            let bitness =
+                Architecture::from_pe(self.header.coff_header.machine)?
+                    .bitness()?;
+            // without symbols the thunks are only found by their code
+            Some(speculation_mitigations(blocks, bitness, Thunks::default()))
         }
     }
+    fn load_config(&self, bytes: &[u8]) -> Option<LoadConfig> {
+        let optional_header = self.header.optional_header?;
+        let load_config_hdr =
+            optional_header.data_directories.get_load_config_table()?;
+        get_load_config_val(
+            bytes,
+            *load_config_hdr,
+            &self.sections,
+            optional_header.windows_fields.file_alignment,
+            self.is_64,
+        )
+        .ok()
+    }
 }
@@ -0,0 +1,357 @@
+#[cfg(feature = "color")]
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+
+#[cfg(unix)]
+use std::collections::HashMap;
+#[cfg(unix)]
+use std::fs;
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+#[cfg(unix)]
+use std::path::Path;
+#[cfg(unix)]
+use std::sync::OnceLock;
+
+#[cfg(unix)]
+const S_ISUID: u32 = 0o4000;
+#[cfg(unix)]
+const S_ISGID: u32 = 0o2000;
+#[cfg(unix)]
+const S_ISVTX: u32 = 0o1000;
+// group or other write permission
+#[cfg(unix)]
+const S_IWOTHERS: u32 = 0o022;
+
+#[cfg(target_os = "linux")]
+const VFS_CAP_REVISION_MASK: u32 = 0xff00_0000;
+#[cfg(target_os = "linux")]
+const VFS_CAP_REVISION_1: u32 = 0x0100_0000;
+#[cfg(target_os = "linux")]
+const VFS_CAP_REVISION_2: u32 = 0x0200_0000;
+#[cfg(target_os = "linux")]
+const VFS_CAP_REVISION_3: u32 = 0x0300_0000;
+#[cfg(target_os = "linux")]
+const VFS_CAP_FLAGS_EFFECTIVE: u32 = 0x0000_0001;
+
+// capability numbers from `linux/capability.h`
+#[cfg(target_os = "linux")]
+static CAPABILITY_NAMES: [&str; 41] = [
+    "cap_chown",
+    "cap_dac_override",
+    "cap_dac_read_search",
+    "cap_fowner",
+    "cap_fsetid",
+    "cap_kill",
+    "cap_setgid",
+    "cap_setuid",
+    "cap_setpcap",
+    "cap_linux_immutable",
+    "cap_net_bind_service",
+    "cap_net_broadcast",
+    "cap_net_admin",
+    "cap_net_raw",
+    "cap_ipc_lock",
+    "cap_ipc_owner",
+    "cap_sys_module",
+    "cap_sys_rawio",
+    "cap_sys_chroot",
+    "cap_sys_ptrace",
+    "cap_sys_pacct",
+    "cap_sys_admin",
+    "cap_sys_boot",
+    "cap_sys_nice",
+    "cap_sys_resource",
+    "cap_sys_time",
+    "cap_sys_tty_config",
+    "cap_mknod",
+    "cap_lease",
+    "cap_audit_write",
+    "cap_audit_control",
+    "cap_setfcap",
+    "cap_mac_override",
+    "cap_mac_admin",
+    "cap_syslog",
+    "cap_wake_alarm",
+    "cap_block_suspend",
+    "cap_audit_read",
+    "cap_perfmon",
+    "cap_bpf",
+    "cap_checkpoint_restore",
+];
+
+/// File capabilities (`security.capability` extended attribute)
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FileCapabilities {
+    pub permitted: Vec<String>,
+    pub inheritable: Vec<String>,
+    /// permitted capabilities become effective on exec
+    pub effective: bool,
+    /// owner uid of the user namespace the capabilities apply to
+    /// (revision 3)
+    pub rootid: Option<u32>,
+}
+
+#[cfg(target_os = "linux")]
+impl FileCapabilities {
+    /// decode a `struct vfs_cap_data`/`vfs_ns_cap_data`
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let u32_at = |index: usize| -> Option<u32> {
+            let bytes = data.get(index * 4..index * 4 + 4)?;
+            Some(u32::from_le_bytes(bytes.try_into().ok()?))
+        };
+        let magic = u32_at(0)?;
+        let (words, rootid) = match magic & VFS_CAP_REVISION_MASK {
+            VFS_CAP_REVISION_1 => (1, None),
+            VFS_CAP_REVISION_2 => (2, None),
+            VFS_CAP_REVISION_3 => (2, Some(u32_at(5)?)),
+            _ => return None,
+        };
+        let (mut permitted, mut inheritable) = (0u64, 0u64);
+        for word in 0..words {
+            permitted |= u64::from(u32_at(1 + word * 2)?) << (32 * word);
+            inheritable |= u64::from(u32_at(2 + word * 2)?) << (32 * word);
+        }
+        Some(Self {
+            permitted: capability_names(permitted),
+            inheritable: capability_names(inheritable),
+            effective: magic & VFS_CAP_FLAGS_EFFECTIVE != 0,
+            rootid,
+        })
+    }
+    /// read the capabilities of a file
+    pub fn read(file: &Path) -> Option<Self> {
+        Self::parse(&xattr::get(file, "security.capability").ok()??)
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn capability_names(set: u64) -> Vec<String> {
+    (0..64)
+        .filter(|bit| set & (1 << bit) != 0)
+        .map(|bit| {
+            CAPABILITY_NAMES
+                .get(bit)
+                .map_or_else(|| format!("cap_{bit}"), ToString::to_string)
+        })
+        .collect()
+}
+
+impl fmt::Display for FileCapabilities {
+    /// `getcap` style `cap_net_raw,cap_net_admin=ep`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut names = self.permitted.clone();
+        for name in &self.inheritable {
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+        write!(f, "{}=", names.join(","))?;
+        if self.effective {
+            write!(f, "e")?;
+        }
+        if !self.inheritable.is_empty() {
+            write!(f, "i")?;
+        }
+        if !self.permitted.is_empty() {
+            write!(f, "p")?;
+        }
+        if let Some(rootid) = self.rootid {
+            write!(f, " [rootid={rootid}]")?;
+        }
+        Ok(())
+    }
+}
+
+/// Mode, ownership and privileges of a binary on disk
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FilePrivileges {
+    /// permission bits, including setuid, setgid and sticky
+    pub mode: u32,
+    pub uid: u32,
+    pub owner: Option<String>,
+    pub gid: u32,
+    pub group: Option<String>,
+    pub setuid: bool,
+    pub setgid: bool,
+    pub capabilities: Option<FileCapabilities>,
+    /// the file or parent directories that users other than the file
+    /// owner or root can write to, and so replace the file
+    pub writable_by_others: Vec<PathBuf>,
+}
+
+#[cfg(unix)]
+impl FilePrivileges {
+    pub fn parse(file: &Path) -> Option<Self> {
+        let metadata = fs::metadata(file).ok()?;
+        let mode = metadata.mode() & 0o7777;
+        let uid = metadata.uid();
+        let mut writable_by_others = Vec::new();
+        let path = fs::canonicalize(file).unwrap_or_else(|_| file.into());
+        if mode & S_IWOTHERS != 0 {
+            writable_by_others.push(path.clone());
+        }
+        // a symlink can be replaced by whoever writes the directory it is
+        // in, just like its target
+        let link_directory = match file.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        let link_directory = fs::canonicalize(link_directory)
+            .unwrap_or_else(|_| link_directory.into());
+        let mut directories: Vec<&Path> = Vec::new();
+        for directory in
+            path.ancestors().skip(1).chain(link_directory.ancestors())
+        {
+            if !directories.contains(&directory) {
+                directories.push(directory);
+            }
+        }
+        for directory in directories {
+            let metadata = match fs::metadata(directory) {
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+            // entries of sticky directories can only be replaced by their
+            // owner
+            let shared = metadata.mode() & S_IWOTHERS != 0
+                && metadata.mode() & S_ISVTX == 0;
+            let foreign = metadata.uid() != uid && metadata.uid() != 0;
+            if shared || foreign {
+                writable_by_others.push(directory.to_path_buf());
+            }
+        }
+        Some(Self {
+            mode,
+            uid,
+            owner: user_name(uid),
+            gid: metadata.gid(),
+            group: group_name(metadata.gid()),
+            setuid: mode & S_ISUID != 0,
+            setgid: mode & S_ISGID != 0,
+            #[cfg(target_os = "linux")]
+            capabilities: FileCapabilities::read(file),
+            #[cfg(not(target_os = "linux"))]
+            capabilities: None,
+            writable_by_others,
+        })
+    }
+}
+
+impl FilePrivileges {
+    /// setuid, setgid or with file capabilities
+    pub fn is_privileged(&self) -> bool {
+        self.setuid || self.setgid || self.capabilities.is_some()
+    }
+    /// worth reporting in text output
+    pub fn is_notable(&self) -> bool {
+        self.is_privileged() || !self.writable_by_others.is_empty()
+    }
+}
+
+/// setuid, setgid or with file capabilities, checked without parsing the
+/// binary
+#[cfg(unix)]
+pub fn is_privileged(file: &Path) -> bool {
+    match fs::metadata(file) {
+        Ok(metadata) if metadata.mode() & (S_ISUID | S_ISGID) != 0 => true,
+        #[cfg(target_os = "linux")]
+        Ok(_) => has_capabilities(file),
+        #[cfg(not(target_os = "linux"))]
+        Ok(_) => false,
+        Err(_) => false,
+    }
+}
+
+#[cfg(not(unix))]
+pub fn is_privileged(_file: &std::path::Path) -> bool {
+    false
+}
+
+#[cfg(target_os = "linux")]
+pub fn has_capabilities(file: &Path) -> bool {
+    xattr::get(file, "security.capability").unwrap_or(None).is_some()
+}
+
+// uid and gid to name maps from the passwd and group databases
+#[cfg(unix)]
+fn id_names(database: &str) -> HashMap<u32, String> {
+    fs::read_to_string(database)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let id = fields.nth(1)?.parse().ok()?;
+            Some((id, name.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(unix)]
+fn user_name(uid: u32) -> Option<String> {
+    static USERS: OnceLock<HashMap<u32, String>> = OnceLock::new();
+    USERS.get_or_init(|| id_names("/etc/passwd")).get(&uid).cloned()
+}
+
+#[cfg(unix)]
+fn group_name(gid: u32) -> Option<String> {
+    static GROUPS: OnceLock<HashMap<u32, String>> = OnceLock::new();
+    GROUPS.get_or_init(|| id_names("/etc/group")).get(&gid).cloned()
+}
+
+impl fmt::Display for FilePrivileges {
+    #[cfg(not(feature = "color"))]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Mode: {:04o} Owner: {}:{}",
+            self.mode,
+            self.owner.clone().unwrap_or_else(|| self.uid.to_string()),
+            self.group.clone().unwrap_or_else(|| self.gid.to_string())
+        )?;
+        if let Some(capabilities) = &self.capabilities {
+            write!(f, " Capabilities: {capabilities}")?;
+        }
+        for path in &self.writable_by_others {
+            write!(f, " Writable: {}", path.display())?;
+        }
+        Ok(())
+    }
+    #[cfg(feature = "color")]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mode = format!("{:04o}", self.mode);
+        write!(
+            f,
+            "{} {} {} {}:{}",
+            "Mode:".bold(),
+            if self.setuid || self.setgid {
+                mode.red()
+            } else {
+                mode.normal()
+            },
+            "Owner:".bold(),
+            self.owner.clone().unwrap_or_else(|| self.uid.to_string()),
+            self.group.clone().unwrap_or_else(|| self.gid.to_string())
+        )?;
+        if let Some(capabilities) = &self.capabilities {
+            write!(
+                f,
+                " {} {}",
+                "Capabilities:".bold(),
+                capabilities.to_string().red()
+            )?;
+        }
+        for path in &self.writable_by_others {
+            write!(
+                f,
+                " {} {}",
+                "Writable:".bold(),
+                path.display().to_string().red()
+            )?;
+        }
+        Ok(())
+    }
+}
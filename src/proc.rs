@@ -8,9 +8,8 @@ use serde::{Deserialize, Serialize};
 #[cfg(all(feature = "maps", not(target_os = "macos")))]
 use std::fmt;
 
-use std::usize;
 #[cfg(all(feature = "maps", target_os = "linux"))]
-use std::{fs, io::ErrorKind};
+use std::{fs, io::ErrorKind, os::unix::fs::MetadataExt};
 #[cfg(all(
     feature = "maps",
     any(target_os = "linux", target_os = "windows")
@@ -69,6 +68,17 @@ use std::{
 };
 
 use crate::binary::Binary;
+#[cfg(all(feature = "maps", feature = "elf", target_os = "linux"))]
+use crate::binary::BinSpecificProperties;
+#[cfg(all(feature = "maps", feature = "elf", target_os = "linux"))]
+use checksec::elf;
+#[cfg(all(feature = "color", feature = "maps", target_os = "linux"))]
+use checksec::colorize_bool;
+
+// personality(2) flag disabling address space randomization, set by
+// `setarch -R`
+#[cfg(all(feature = "maps", target_os = "linux"))]
+const ADDR_NO_RANDOMIZE: u32 = 0x0004_0000;
 
 #[cfg(all(feature = "maps", any(target_os = "linux", target_os = "windows")))]
 #[derive(Deserialize, Serialize)]
@@ -214,6 +224,11 @@ pub struct MapEntry {
     pub region: Region,
     pub flags: MapFlags,
     pub pathname: Option<PathBuf>,
+    /// device and inode of the mapped file, 0 for anonymous mappings
+    #[cfg(all(feature = "maps", target_os = "linux"))]
+    pub device: u64,
+    #[cfg(all(feature = "maps", target_os = "linux"))]
+    pub inode: u64,
     #[cfg(all(feature = "maps", target_os = "windows"))]
     pub etype: MapType,
 }
@@ -323,6 +338,432 @@ impl fmt::Display for MapEntry {
     }
 }
 
+/// Address space layout randomization in effect for a process:
+/// `kernel.randomize_va_space` unless the process personality disables it
+#[cfg(all(feature = "maps", target_os = "linux"))]
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Aslr {
+    Disabled,
+    /// stack, mmap and VDSO but no heap (`brk`) randomization
+    Partial,
+    Full,
+}
+#[cfg(all(feature = "maps", target_os = "linux"))]
+impl fmt::Display for Aslr {
+    #[cfg(not(feature = "color"))]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<8}",
+            match *self {
+                Self::Disabled => "Disabled",
+                Self::Partial => "Partial",
+                Self::Full => "Full",
+            }
+        )
+    }
+    #[cfg(feature = "color")]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<8}",
+            match *self {
+                Self::Disabled => "Disabled".red(),
+                Self::Partial => "Partial".yellow(),
+                Self::Full => "Full".green(),
+            }
+        )
+    }
+}
+
+/// seccomp mode from `/proc/<pid>/status`
+#[cfg(all(feature = "maps", target_os = "linux"))]
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Seccomp {
+    Disabled,
+    /// `SECCOMP_MODE_STRICT`, only `read`, `write`, `_exit` and `sigreturn`
+    Strict,
+    /// `SECCOMP_MODE_FILTER` with BPF filters
+    Filter,
+}
+#[cfg(all(feature = "maps", target_os = "linux"))]
+impl fmt::Display for Seccomp {
+    #[cfg(not(feature = "color"))]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<8}",
+            match *self {
+                Self::Disabled => "Disabled",
+                Self::Strict => "Strict",
+                Self::Filter => "Filter",
+            }
+        )
+    }
+    #[cfg(feature = "color")]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<8}",
+            match *self {
+                Self::Disabled => "Disabled".red(),
+                Self::Strict => "Strict".green(),
+                Self::Filter => "Filter".green(),
+            }
+        )
+    }
+}
+
+/// Properties of a running process rather than its executable file, read
+/// from `/proc`. Fields are `None` where the kernel does not provide them
+/// or they are not readable for the current user.
+#[cfg(all(feature = "maps", target_os = "linux"))]
+#[derive(Deserialize, Serialize)]
+pub struct ProcessProperties {
+    pub aslr: Option<Aslr>,
+    /// `[stack]` mapping is executable
+    pub exec_stack: Option<bool>,
+    /// `PR_SET_NO_NEW_PRIVS` is set
+    pub no_new_privs: Option<bool>,
+    pub seccomp: Option<Seccomp>,
+    /// number of installed seccomp filters
+    pub seccomp_filters: Option<u32>,
+    /// effective capabilities
+    pub cap_eff: Option<u64>,
+    /// capability bounding set
+    pub cap_bnd: Option<u64>,
+    /// speculative store bypass (Spectre v4) mitigation state, such as
+    /// `thread force mitigated`
+    pub speculation_store_bypass: Option<String>,
+    /// `SELinux` context, `AppArmor` profile or Smack label
+    pub lsm_label: Option<String>,
+}
+#[cfg(all(feature = "maps", target_os = "linux"))]
+impl ProcessProperties {
+    /// read the properties of process `pid`, `maps` tells whether its
+    /// stack is executable
+    pub fn parse(pid: usize, maps: Option<&[MapEntry]>) -> Result<Self, Error> {
+        let status = fs::read_to_string(format!("/proc/{pid}/status"))?;
+        let field = |name: &str| {
+            status.lines().find_map(|line| {
+                line.strip_prefix(name)?.strip_prefix(':').map(str::trim)
+            })
+        };
+        let hex = |name: &str| {
+            field(name).and_then(|value| u64::from_str_radix(value, 16).ok())
+        };
+        let randomize_va_space =
+            fs::read_to_string("/proc/sys/kernel/randomize_va_space").ok();
+        let personality =
+            fs::read_to_string(format!("/proc/{pid}/personality")).ok();
+        let aslr = match randomize_va_space.as_deref().map(str::trim) {
+            _ if personality
+                .and_then(|p| u32::from_str_radix(p.trim(), 16).ok())
+                .is_some_and(|p| p & ADDR_NO_RANDOMIZE != 0) =>
+            {
+                Some(Aslr::Disabled)
+            }
+            Some("0") => Some(Aslr::Disabled),
+            Some("1") => Some(Aslr::Partial),
+            Some("2") => Some(Aslr::Full),
+            _ => None,
+        };
+        let exec_stack = maps.and_then(|maps| {
+            maps.iter()
+                .find(|map| {
+                    map.pathname.as_deref()
+                        == Some(std::path::Path::new("[stack]"))
+                })
+                .map(|map| map.flags.x)
+        });
+        let lsm_label = fs::read_to_string(format!("/proc/{pid}/attr/current"))
+            .ok()
+            .map(|label| label.trim_end_matches(['\0', '\n']).to_string())
+            .filter(|label| !label.is_empty());
+        Ok(Self {
+            aslr,
+            exec_stack,
+            no_new_privs: field("NoNewPrivs").map(|value| value == "1"),
+            seccomp: match field("Seccomp") {
+                Some("0") => Some(Seccomp::Disabled),
+                Some("1") => Some(Seccomp::Strict),
+                Some("2") => Some(Seccomp::Filter),
+                _ => None,
+            },
+            seccomp_filters: field("Seccomp_filters")
+                .and_then(|value| value.parse().ok()),
+            cap_eff: hex("CapEff"),
+            cap_bnd: hex("CapBnd"),
+            speculation_store_bypass: field("Speculation_Store_Bypass")
+                .map(ToString::to_string),
+            lsm_label,
+        })
+    }
+}
+#[cfg(all(feature = "maps", target_os = "linux"))]
+impl fmt::Display for ProcessProperties {
+    #[cfg(not(feature = "color"))]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn or_na<T: ToString>(value: Option<T>) -> String {
+            value.map_or_else(|| "N/A".to_string(), |v| v.to_string())
+        }
+        write!(
+            f,
+            "ASLR: {} Exec Stack: {} NoNewPrivs: {} Seccomp: {}",
+            or_na(self.aslr.as_ref()),
+            or_na(self.exec_stack),
+            or_na(self.no_new_privs),
+            or_na(self.seccomp.as_ref()),
+        )?;
+        if let Some(filters) = self.seccomp_filters.filter(|&n| n > 0) {
+            write!(f, " Filters: {filters}")?;
+        }
+        if let Some(cap_eff) = self.cap_eff {
+            write!(f, " CapEff: {cap_eff:#018x}")?;
+        }
+        if let Some(cap_bnd) = self.cap_bnd {
+            write!(f, " CapBnd: {cap_bnd:#018x}")?;
+        }
+        if let Some(ssb) = &self.speculation_store_bypass {
+            write!(f, " SSB: {ssb}")?;
+        }
+        if let Some(label) = &self.lsm_label {
+            write!(f, " LSM: {label}")?;
+        }
+        Ok(())
+    }
+    #[cfg(feature = "color")]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn or_na<T: ToString>(value: Option<T>) -> String {
+            value.map_or_else(
+                || "N/A".yellow().to_string(),
+                |v| v.to_string(),
+            )
+        }
+        write!(
+            f,
+            "{} {} {} {} {} {} {} {}",
+            "ASLR:".bold(),
+            or_na(self.aslr.as_ref()),
+            "Exec Stack:".bold(),
+            or_na(self.exec_stack.map(|x| {
+                if x { "true ".red() } else { "false".green() }
+            })),
+            "NoNewPrivs:".bold(),
+            or_na(self.no_new_privs.map(|x| colorize_bool!(x))),
+            "Seccomp:".bold(),
+            or_na(self.seccomp.as_ref()),
+        )?;
+        if let Some(filters) = self.seccomp_filters.filter(|&n| n > 0) {
+            write!(f, " {} {}", "Filters:".bold(), filters)?;
+        }
+        if let Some(cap_eff) = self.cap_eff {
+            let hex = format!("{cap_eff:#018x}");
+            write!(
+                f,
+                " {} {}",
+                "CapEff:".bold(),
+                if cap_eff == 0 { hex.green() } else { hex.red() }
+            )?;
+        }
+        if let Some(cap_bnd) = self.cap_bnd {
+            write!(f, " {} {:#018x}", "CapBnd:".bold(), cap_bnd)?;
+        }
+        if let Some(ssb) = &self.speculation_store_bypass {
+            write!(f, " {} {}", "SSB:".bold(), ssb)?;
+        }
+        if let Some(label) = &self.lsm_label {
+            write!(f, " {} {}", "LSM:".bold(), label)?;
+        }
+        Ok(())
+    }
+}
+
+/// `st_dev` encoding of a device number, as returned by `stat(2)`
+#[cfg(all(feature = "maps", target_os = "linux"))]
+fn makedev(major: u64, minor: u64) -> u64 {
+    ((major & 0xffff_f000) << 32)
+        | ((major & 0xfff) << 8)
+        | ((minor & 0xffff_ff00) << 12)
+        | (minor & 0xff)
+}
+
+/// Suspicious mapping kinds, as left behind by injected code, fileless
+/// malware or replaced binaries
+#[cfg(all(feature = "maps", target_os = "linux"))]
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum FindingKind {
+    /// executable mapping without a backing file
+    AnonymousExec,
+    /// executable mapping of a `memfd_create(2)` file
+    Memfd,
+    /// executable mapping of a file deleted since
+    Deleted,
+    /// executable mapping of a path now holding a different file
+    Replaced,
+    /// writable and executable mapping
+    WriteExec,
+}
+#[cfg(all(feature = "maps", target_os = "linux"))]
+impl fmt::Display for FindingKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                Self::AnonymousExec => "anonymous executable",
+                Self::Memfd => "memfd executable",
+                Self::Deleted => "deleted file",
+                Self::Replaced => "replaced file",
+                Self::WriteExec => "writable and executable",
+            }
+        )
+    }
+}
+
+/// Suspicious mapping of a process
+#[cfg(all(feature = "maps", target_os = "linux"))]
+#[derive(Deserialize, Serialize)]
+pub struct Finding {
+    pub kind: FindingKind,
+    pub region: Region,
+    pub pathname: Option<PathBuf>,
+}
+#[cfg(all(feature = "maps", target_os = "linux"))]
+impl Finding {
+    /// flag executable mappings with no, a `memfd`, a deleted or a replaced
+    /// backing file, and writable and executable mappings
+    pub fn scan(pid: usize, maps: &[MapEntry]) -> Vec<Self> {
+        let mut findings = Vec::new();
+        for map in maps.iter().filter(|map| map.flags.x) {
+            let mut add = |kind| {
+                findings.push(Self {
+                    kind,
+                    region: Region::new(map.region.start, map.region.end),
+                    pathname: map.pathname.clone(),
+                });
+            };
+            if map.flags.w {
+                add(FindingKind::WriteExec);
+            }
+            let name = map
+                .pathname
+                .as_ref()
+                .map(|p| p.to_string_lossy().into_owned());
+            match name.as_deref() {
+                // kernel provided code
+                Some("[vdso]" | "[vsyscall]") => {}
+                None => add(FindingKind::AnonymousExec),
+                // [stack], [heap], [anon:<name>]
+                Some(name) if name.starts_with('[') => {
+                    add(FindingKind::AnonymousExec);
+                }
+                Some(name) if name.starts_with("/memfd:") => {
+                    add(FindingKind::Memfd);
+                }
+                Some(name) if name.ends_with(" (deleted)") => {
+                    add(FindingKind::Deleted);
+                }
+                // resolved in the mount namespace of the process
+                Some(name) => {
+                    if let Ok(metadata) =
+                        fs::metadata(format!("/proc/{pid}/root{name}"))
+                    {
+                        if map.inode != 0
+                            && (metadata.ino() != map.inode
+                                || metadata.dev() != map.device)
+                        {
+                            add(FindingKind::Replaced);
+                        }
+                    }
+                }
+            }
+        }
+        findings
+    }
+}
+#[cfg(all(feature = "maps", target_os = "linux"))]
+impl fmt::Display for Finding {
+    #[cfg(not(feature = "color"))]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "0x{:x}->0x{:x} {} {}",
+            self.region.start,
+            self.region.end,
+            self.kind,
+            match &self.pathname {
+                Some(pathname) => pathname.display().to_string(),
+                None => String::new(),
+            }
+        )
+    }
+    #[cfg(feature = "color")]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "0x{:x}->0x{:x} {} {}",
+            self.region.start,
+            self.region.end,
+            self.kind.to_string().red(),
+            match &self.pathname {
+                Some(pathname) => pathname.display().to_string(),
+                None => String::new(),
+            }
+        )
+    }
+}
+
+/// Checks run against the executable image loaded into a process rather
+/// than the file on disk
+#[cfg(all(feature = "maps", feature = "elf", target_os = "linux"))]
+#[derive(Deserialize, Serialize)]
+pub struct MemoryImage {
+    pub base: usize,
+    pub properties: elf::CheckSecResults,
+    /// checks that differ from the file on disk
+    pub differences: Vec<elf::Difference>,
+}
+#[cfg(all(feature = "maps", feature = "elf", target_os = "linux"))]
+impl MemoryImage {
+    /// rebuild the ELF image of the executable of process `pid` from
+    /// `/proc/<pid>/mem`, starting at its lowest mapping
+    pub fn parse(
+        pid: usize,
+        maps: &[MapEntry],
+        binary: &Binary,
+    ) -> Result<Self, Error> {
+        use std::os::unix::fs::FileExt;
+        let exe = fs::read_link(format!("/proc/{pid}/exe"))?;
+        let base = maps
+            .iter()
+            .filter(|map| map.pathname.as_ref() == Some(&exe))
+            .map(|map| map.region.start)
+            .min()
+            .ok_or(ErrorKind::NotFound)?;
+        let mem = fs::File::open(format!("/proc/{pid}/mem"))?;
+        let image = elf::image_from_memory(base as u64, |address, buf| {
+            mem.read_exact_at(buf, address)
+        })
+        .map_err(|e| Error::new(ErrorKind::InvalidData, e.to_string()))?;
+        let loaded = goblin::elf::Elf::parse(&image)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e.to_string()))?;
+        let properties = elf::CheckSecResults::parse(&loaded, &image);
+        let differences = binary
+            .blobs
+            .iter()
+            .find_map(|blob| match &blob.properties {
+                BinSpecificProperties::Elf(file) => {
+                    Some(file.differences(&properties))
+                }
+                #[allow(unreachable_patterns)]
+                _ => None,
+            })
+            .unwrap_or_default();
+        Ok(Self { base, properties, differences })
+    }
+}
+
 #[cfg(all(feature = "maps", target_os = "windows"))]
 struct WinProcInfo {
     pub pid: u32,
@@ -346,6 +787,14 @@ pub struct Process {
         any(target_os = "linux", target_os = "windows")
     ))]
     pub maps: Option<Vec<MapEntry>>,
+    #[cfg(all(feature = "maps", target_os = "linux"))]
+    pub properties: Option<ProcessProperties>,
+    /// suspicious mappings, only looked for on request
+    #[cfg(all(feature = "maps", target_os = "linux"))]
+    pub findings: Option<Vec<Finding>>,
+    /// checks of the loaded image, only run on request
+    #[cfg(all(feature = "maps", feature = "elf", target_os = "linux"))]
+    pub memory: Option<MemoryImage>,
     pub libraries: Option<Vec<Binary>>,
 }
 impl Process {
@@ -366,15 +815,62 @@ impl Process {
         binary: Binary,
         libraries: Option<Vec<Binary>>,
     ) -> Self {
-        match Process::parse_maps(pid) {
-            Ok(maps) => Self { pid, binary, maps: Some(maps), libraries },
+        let maps = match Process::parse_maps(pid) {
+            Ok(maps) => Some(maps),
             Err(e) => {
                 eprintln!(
                     "Failed to parse maps for process with ID {pid}: {e}"
                 );
-                Self { pid, binary, maps: None, libraries }
+                None
             }
+        };
+        #[cfg(target_os = "linux")]
+        let properties =
+            match ProcessProperties::parse(pid, maps.as_deref()) {
+                Ok(properties) => Some(properties),
+                Err(e) => {
+                    eprintln!(
+                        "Failed to read /proc/{pid} for process with ID {pid}: {e}"
+                    );
+                    None
+                }
+            };
+        Self {
+            pid,
+            binary,
+            maps,
+            #[cfg(target_os = "linux")]
+            properties,
+            #[cfg(target_os = "linux")]
+            findings: None,
+            #[cfg(all(feature = "elf", target_os = "linux"))]
+            memory: None,
+            libraries,
+        }
+    }
+    /// look for suspicious mappings in the process maps
+    #[cfg(all(feature = "maps", target_os = "linux"))]
+    #[must_use]
+    pub fn with_findings(mut self) -> Self {
+        self.findings =
+            self.maps.as_deref().map(|maps| Finding::scan(self.pid, maps));
+        self
+    }
+    /// run the checks against the loaded image of the executable
+    #[cfg(all(feature = "maps", feature = "elf", target_os = "linux"))]
+    #[must_use]
+    pub fn with_memory_image(mut self) -> Self {
+        let Some(maps) = &self.maps else {
+            return self;
+        };
+        match MemoryImage::parse(self.pid, maps, &self.binary) {
+            Ok(memory) => self.memory = Some(memory),
+            Err(e) => eprintln!(
+                "Failed to read the memory image of process with ID {}: {e}",
+                self.pid
+            ),
         }
+        self
     }
     #[cfg(all(feature = "maps", target_os = "linux"))]
     pub fn parse_maps(pid: usize) -> Result<Vec<MapEntry>, Error> {
@@ -394,12 +890,24 @@ impl Process {
                 split_line.next().ok_or(ErrorKind::InvalidData)?,
             );
             split_line.next(); // skip offset
-            split_line.next(); // skip dev
-            split_line.next(); // skip inode
+            let (major, minor) = split_line
+                .next()
+                .ok_or(ErrorKind::InvalidData)?
+                .split_once(':')
+                .ok_or(ErrorKind::InvalidData)?;
+            let device = makedev(
+                u64::from_str_radix(major, 16).unwrap_or(0),
+                u64::from_str_radix(minor, 16).unwrap_or(0),
+            );
+            let inode = split_line
+                .next()
+                .ok_or(ErrorKind::InvalidData)?
+                .parse()
+                .unwrap_or(0);
             let pathname = Some(split_line.collect::<Vec<&str>>().join(" "))
                 .filter(|x| !x.is_empty())
                 .map(PathBuf::from);
-            maps.push(MapEntry { region, flags, pathname });
+            maps.push(MapEntry { region, flags, pathname, device, inode });
         }
         Ok(maps)
     }
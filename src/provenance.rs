@@ -0,0 +1,312 @@
+//! Implements compiler and build provenance checks for ELF binaries
+use goblin::elf::note::NT_GNU_BUILD_ID;
+use goblin::elf::Elf;
+use scroll::{Endian, Pread};
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Write};
+
+/// `FDO` note type carrying systemd package metadata
+pub const NT_FDO_PACKAGING_METADATA: u32 = 0xcafe_1a7e;
+/// annobin note covering a whole object file
+pub const NT_GNU_BUILD_ATTRIBUTE_OPEN: u32 = 0x100;
+/// annobin note covering a single function
+pub const NT_GNU_BUILD_ATTRIBUTE_FUNC: u32 = 0x101;
+
+// annobin attribute ids, used instead of a name for well known attributes
+const GNU_BUILD_ATTRIBUTE_TOOL: u8 = 5;
+const GNU_BUILD_ATTRIBUTE_STACK_PROT: u8 = 2;
+
+/// Package metadata from the `.note.package` section, see
+/// <https://systemd.io/ELF_PACKAGE_METADATA/>
+#[derive(Clone, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
+pub struct PackageNote {
+    #[serde(rename = "type", default)]
+    pub package_type: Option<String>,
+    #[serde(default)]
+    pub os: Option<String>,
+    #[serde(rename = "osVersion", default)]
+    pub os_version: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub architecture: Option<String>,
+}
+
+impl fmt::Display for PackageNote {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let field = |value: &Option<String>| {
+            value.clone().unwrap_or_else(|| "?".to_string())
+        };
+        write!(
+            f,
+            "{} {} ({})",
+            field(&self.name),
+            field(&self.version),
+            field(&self.package_type)
+        )
+    }
+}
+
+/// Stack protector level recorded by annobin, weakest first
+#[derive(
+    Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd,
+)]
+pub enum StackProtector {
+    None,
+    Explicit,
+    Basic,
+    Strong,
+    All,
+}
+
+impl StackProtector {
+    fn from_level(level: u64) -> Option<Self> {
+        match level {
+            0 => Some(Self::None),
+            1 => Some(Self::Basic),
+            2 => Some(Self::All),
+            3 => Some(Self::Strong),
+            4 => Some(Self::Explicit),
+            _ => None,
+        }
+    }
+}
+
+/// `-fcf-protection` mode recorded by annobin
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq)]
+pub enum CfProtection {
+    None,
+    Branch,
+    Return,
+    Full,
+}
+
+impl CfProtection {
+    // annobin records gcc's `flag_cf_protection + 1`, where bit 0 is
+    // branch and bit 1 is return protection
+    fn bits(value: u64) -> Option<u64> {
+        value.checked_sub(1).map(|flags| flags & 3)
+    }
+    fn from_bits(bits: u64) -> Self {
+        match bits {
+            1 => Self::Branch,
+            2 => Self::Return,
+            3 => Self::Full,
+            _ => Self::None,
+        }
+    }
+}
+
+/// Hardening flags recorded in annobin `.gnu.build.attributes` notes.
+///
+/// Each attribute holds the weakest setting found across all notes, so a
+/// single object built without a flag makes the whole binary lack it.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
+pub struct Annobin {
+    /// compilers that produced the objects
+    pub tools: Vec<String>,
+    /// `-fstack-protector*`
+    pub stack_protector: Option<StackProtector>,
+    /// `_FORTIFY_SOURCE` level
+    pub fortify: Option<u8>,
+    /// `-fcf-protection`
+    pub cf_protection: Option<CfProtection>,
+    /// `-fstack-clash-protection`
+    pub stack_clash: Option<bool>,
+}
+
+enum AttributeValue<'a> {
+    Numeric(u64),
+    String(&'a str),
+    Bool(bool),
+}
+
+impl Annobin {
+    /// Parse the `.gnu.build.attributes` section, returns `None` if the
+    /// binary has none
+    #[must_use]
+    pub fn parse(elf: &Elf, bytes: &[u8]) -> Option<Self> {
+        let data = section_data(elf, bytes, ".gnu.build.attributes")?;
+        let endian =
+            if elf.little_endian { Endian::Little } else { Endian::Big };
+        let mut annobin = Self::default();
+        let mut cf_bits: Option<u64> = None;
+        let mut offset = 0;
+        while offset + 12 <= data.len() {
+            let (Ok(namesz), Ok(descsz), Ok(n_type)) = (
+                data.pread_with::<u32>(offset, endian),
+                data.pread_with::<u32>(offset + 4, endian),
+                data.pread_with::<u32>(offset + 8, endian),
+            ) else {
+                break;
+            };
+            let name_end = offset + 12 + namesz as usize;
+            // a truncated note ends the section
+            let Some(name) = data.get(offset + 12..name_end) else {
+                break;
+            };
+            offset = align4(align4(name_end) + descsz as usize);
+            if n_type != NT_GNU_BUILD_ATTRIBUTE_OPEN
+                && n_type != NT_GNU_BUILD_ATTRIBUTE_FUNC
+            {
+                continue;
+            }
+            let Some((attribute, value)) = parse_attribute(name) else {
+                continue;
+            };
+            match (attribute, value) {
+                (
+                    Attribute::Id(GNU_BUILD_ATTRIBUTE_TOOL),
+                    AttributeValue::String(tool),
+                ) if !annobin.tools.iter().any(|known| known == tool) => {
+                    annobin.tools.push(tool.to_string());
+                }
+                (
+                    Attribute::Id(GNU_BUILD_ATTRIBUTE_STACK_PROT),
+                    AttributeValue::Numeric(level),
+                ) => {
+                    if let Some(level) = StackProtector::from_level(level) {
+                        annobin.stack_protector = Some(
+                            annobin
+                                .stack_protector
+                                .map_or(level, |sp| sp.min(level)),
+                        );
+                    }
+                }
+                (
+                    Attribute::Name("FORTIFY"),
+                    AttributeValue::Numeric(level),
+                ) => {
+                    // levels above 3 mean the level could not be recorded
+                    if let Ok(level @ 0..=3) = u8::try_from(level) {
+                        annobin.fortify = Some(
+                            annobin
+                                .fortify
+                                .map_or(level, |fortify| fortify.min(level)),
+                        );
+                    }
+                }
+                (
+                    Attribute::Name("cf_protection"),
+                    AttributeValue::Numeric(value),
+                ) => {
+                    if let Some(bits) = CfProtection::bits(value) {
+                        cf_bits = Some(cf_bits.map_or(bits, |cf| cf & bits));
+                    }
+                }
+                (
+                    Attribute::Name("stack_clash"),
+                    AttributeValue::Bool(enabled),
+                ) => {
+                    annobin.stack_clash =
+                        Some(annobin.stack_clash.unwrap_or(true) && enabled);
+                }
+                _ => {}
+            }
+        }
+        annobin.cf_protection = cf_bits.map(CfProtection::from_bits);
+        Some(annobin)
+    }
+}
+
+enum Attribute<'a> {
+    Id(u8),
+    Name(&'a str),
+}
+
+/// Decode an annobin note name: `GA`, a value type, the attribute id or
+/// NUL terminated name, then the value
+fn parse_attribute(
+    name: &[u8],
+) -> Option<(Attribute<'_>, AttributeValue<'_>)> {
+    let rest = name.strip_prefix(b"GA")?;
+    let (&kind, rest) = rest.split_first()?;
+    let (&id, _) = rest.split_first()?;
+    let (attribute, value) = if id.is_ascii_graphic() {
+        let end = rest.iter().position(|&b| b == 0)?;
+        (
+            Attribute::Name(std::str::from_utf8(&rest[..end]).ok()?),
+            &rest[end + 1..],
+        )
+    } else {
+        (Attribute::Id(id), &rest[1..])
+    };
+    // the value is NUL terminated as well
+    let value = value.strip_suffix(b"\0").unwrap_or(value);
+    let value = match kind {
+        b'*' => AttributeValue::Numeric(
+            value
+                .iter()
+                .take(8)
+                .rev()
+                .fold(0, |acc, &b| (acc << 8) | u64::from(b)),
+        ),
+        b'$' => AttributeValue::String(std::str::from_utf8(value).ok()?),
+        b'+' => AttributeValue::Bool(true),
+        b'!' => AttributeValue::Bool(false),
+        _ => return None,
+    };
+    Some((attribute, value))
+}
+
+const fn align4(offset: usize) -> usize {
+    (offset + 3) & !3
+}
+
+/// Raw contents of the first section named `name`
+pub(crate) fn section_data<'a>(
+    elf: &Elf,
+    bytes: &'a [u8],
+    name: &str,
+) -> Option<&'a [u8]> {
+    let sh = elf
+        .section_headers
+        .iter()
+        .find(|sh| elf.shdr_strtab.get_at(sh.sh_name) == Some(name))?;
+    let start = usize::try_from(sh.sh_offset).ok()?;
+    let size = usize::try_from(sh.sh_size).ok()?;
+    bytes.get(start..start.checked_add(size)?)
+}
+
+/// NUL separated strings of a section, such as `.comment`
+#[must_use]
+pub fn section_strings(elf: &Elf, bytes: &[u8], name: &str) -> Vec<String> {
+    section_data(elf, bytes, name)
+        .map(|data| {
+            data.split(|&b| b == 0)
+                .filter(|s| !s.is_empty())
+                .map(|s| String::from_utf8_lossy(s).into_owned())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Hex encoded `NT_GNU_BUILD_ID` note
+#[must_use]
+pub fn build_id(elf: &Elf, bytes: &[u8]) -> Option<String> {
+    // notes after a malformed one can not be located
+    elf.iter_note_sections(bytes, None)?
+        .map_while(Result::ok)
+        .find(|note| note.n_type == NT_GNU_BUILD_ID && note.name == "GNU")
+        .map(|note| {
+            note.desc.iter().fold(String::new(), |mut hex, b| {
+                let _ = write!(hex, "{b:02x}");
+                hex
+            })
+        })
+}
+
+/// systemd package metadata from the `.note.package` section
+#[must_use]
+pub fn package(elf: &Elf, bytes: &[u8]) -> Option<PackageNote> {
+    let note = elf
+        .iter_note_sections(bytes, None)?
+        .map_while(Result::ok)
+        .find(|note| {
+            note.n_type == NT_FDO_PACKAGING_METADATA && note.name == "FDO"
+        })?;
+    let json = note.desc.split(|&b| b == 0).next()?;
+    serde_json::from_slice(json).ok()
+}
@@ -0,0 +1,450 @@
+//! Implements resource directory parsing for PE32/32+ binaries
+#[cfg(feature = "color")]
+use colored::Colorize;
+use goblin::pe::utils::find_offset;
+use goblin::pe::{options::ParseOptions, PE};
+use scroll::Pread;
+use scroll_derive::Pread;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const RT_VERSION: u32 = 16;
+const RT_MANIFEST: u32 = 24;
+
+// `OffsetToData` of an entry pointing to a subdirectory
+const IMAGE_RESOURCE_DATA_IS_DIRECTORY: u32 = 0x8000_0000;
+// `Name` of an entry identified by a string
+const IMAGE_RESOURCE_NAME_IS_STRING: u32 = 0x8000_0000;
+
+// `VS_FIXEDFILEINFO.dwSignature`
+const VS_FFI_SIGNATURE: u32 = 0xFEEF_04BD;
+
+/// `IMAGE_RESOURCE_DIRECTORY`
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Default, Pread)]
+struct ImageResourceDirectory {
+    characteristics: u32,
+    time_date_stamp: u32,
+    major_version: u16,
+    minor_version: u16,
+    number_of_named_entries: u16,
+    number_of_id_entries: u16,
+}
+
+/// `IMAGE_RESOURCE_DIRECTORY_ENTRY`
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Default, Pread)]
+struct ImageResourceDirectoryEntry {
+    name: u32,
+    offset_to_data: u32,
+}
+
+/// `IMAGE_RESOURCE_DATA_ENTRY`
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Default, Pread)]
+struct ImageResourceDataEntry {
+    offset_to_data: u32,
+    size: u32,
+    code_page: u32,
+    reserved: u32,
+}
+
+/// Resource section of a PE, used to look up resources by type
+pub struct Resources<'a> {
+    pe: &'a PE<'a>,
+    bytes: &'a [u8],
+    /// file offset of the root `IMAGE_RESOURCE_DIRECTORY`
+    root: usize,
+}
+
+impl<'a> Resources<'a> {
+    /// Locate the resource directory, returns `None` if the PE has none
+    #[must_use]
+    pub fn parse(pe: &'a PE<'a>, bytes: &'a [u8]) -> Option<Self> {
+        let optional_header = pe.header.optional_header?;
+        let directory =
+            optional_header.data_directories.get_resource_table()?;
+        let root = find_offset(
+            directory.virtual_address as usize,
+            &pe.sections,
+            optional_header.windows_fields.file_alignment,
+            &ParseOptions::default(),
+        )?;
+        Some(Self { pe, bytes, root })
+    }
+    /// entries of the directory at `offset` relative to the root
+    fn entries(&self, offset: u32) -> Vec<ImageResourceDirectoryEntry> {
+        let mut offset = self.root + offset as usize;
+        let Ok(directory) =
+            self.bytes.gread::<ImageResourceDirectory>(&mut offset)
+        else {
+            return vec![];
+        };
+        let count = usize::from(directory.number_of_named_entries)
+            + usize::from(directory.number_of_id_entries);
+        (0..count).map_while(|_| self.bytes.gread(&mut offset).ok()).collect()
+    }
+    /// first leaf below a directory entry (`Name` and `Language` levels)
+    fn first_leaf(
+        &self,
+        entry: ImageResourceDirectoryEntry,
+        depth: usize,
+    ) -> Option<&'a [u8]> {
+        if entry.offset_to_data & IMAGE_RESOURCE_DATA_IS_DIRECTORY == 0 {
+            let data: ImageResourceDataEntry = self
+                .bytes
+                .pread(self.root + entry.offset_to_data as usize)
+                .ok()?;
+            let offset = find_offset(
+                data.offset_to_data as usize,
+                &self.pe.sections,
+                self.pe.header.optional_header?.windows_fields.file_alignment,
+                &ParseOptions::default(),
+            )?;
+            return self.bytes.get(offset..offset + data.size as usize);
+        }
+        // guard against directory loops
+        if depth > 3 {
+            return None;
+        }
+        self.entries(entry.offset_to_data & !IMAGE_RESOURCE_DATA_IS_DIRECTORY)
+            .into_iter()
+            .find_map(|child| self.first_leaf(child, depth + 1))
+    }
+    /// data of the first resource with the integer type `resource_type`
+    fn find(&self, resource_type: u32) -> Option<&'a [u8]> {
+        self.entries(0)
+            .into_iter()
+            .filter(|entry| {
+                entry.name & IMAGE_RESOURCE_NAME_IS_STRING == 0
+                    && entry.name == resource_type
+            })
+            .find_map(|entry| self.first_leaf(entry, 1))
+    }
+    /// `RT_MANIFEST` XML
+    #[must_use]
+    pub fn manifest(&self) -> Option<String> {
+        self.find(RT_MANIFEST).map(decode_text)
+    }
+    /// `RT_VERSION` `VS_VERSIONINFO`
+    #[must_use]
+    pub fn version_info(&self) -> Option<VersionInfo> {
+        self.find(RT_VERSION).and_then(VersionInfo::parse)
+    }
+}
+
+/// decode a UTF-8 or UTF-16LE (with BOM) manifest
+fn decode_text(bytes: &[u8]) -> String {
+    if let Some(utf16) = bytes.strip_prefix(&[0xFF, 0xFE]) {
+        let units: Vec<u16> = utf16
+            .chunks_exact(2)
+            .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+            .collect();
+        return String::from_utf16_lossy(&units);
+    }
+    let bytes = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(bytes);
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+/// Manifest `requestedExecutionLevel`: `None`, `asInvoker`,
+/// `highestAvailable` or `requireAdministrator`
+#[derive(Clone, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
+pub enum ExecutionLevel {
+    #[default]
+    None,
+    AsInvoker,
+    HighestAvailable,
+    RequireAdministrator,
+}
+impl fmt::Display for ExecutionLevel {
+    #[cfg(not(feature = "color"))]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<20}",
+            match *self {
+                Self::None => "None",
+                Self::AsInvoker => "asInvoker",
+                Self::HighestAvailable => "highestAvailable",
+                Self::RequireAdministrator => "requireAdministrator",
+            }
+        )
+    }
+    #[cfg(feature = "color")]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<20}",
+            match *self {
+                Self::None => "None".normal(),
+                Self::AsInvoker => "asInvoker".green(),
+                Self::HighestAvailable => "highestAvailable".yellow(),
+                Self::RequireAdministrator => "requireAdministrator".red(),
+            }
+        )
+    }
+}
+
+/// Security relevant settings of the embedded application manifest
+///
+/// **Example**
+///
+/// ```rust
+/// use checksec::resource::Manifest;
+///
+/// let manifest = Manifest::parse(
+///     r#"<requestedExecutionLevel level="asInvoker" uiAccess="false"/>"#,
+/// );
+/// assert!(!manifest.ui_access);
+/// ```
+#[derive(Clone, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
+pub struct Manifest {
+    /// `requestedExecutionLevel` `level`
+    pub execution_level: ExecutionLevel,
+    /// `requestedExecutionLevel` `uiAccess`
+    pub ui_access: bool,
+    /// `autoElevate`
+    pub auto_elevate: bool,
+    /// side-by-side `dependentAssembly` names
+    pub dependencies: Vec<String>,
+}
+
+impl Manifest {
+    #[must_use]
+    pub fn parse(xml: &str) -> Self {
+        let mut manifest = Self::default();
+        for (tag, rest) in elements(xml) {
+            match element_name(tag) {
+                "requestedExecutionLevel" => {
+                    manifest.execution_level = match attribute(tag, "level") {
+                        Some("asInvoker") => ExecutionLevel::AsInvoker,
+                        Some("highestAvailable") => {
+                            ExecutionLevel::HighestAvailable
+                        }
+                        Some("requireAdministrator") => {
+                            ExecutionLevel::RequireAdministrator
+                        }
+                        _ => ExecutionLevel::None,
+                    };
+                    manifest.ui_access = attribute(tag, "uiAccess")
+                        .is_some_and(|value| {
+                            value.eq_ignore_ascii_case("true")
+                        });
+                }
+                "autoElevate" => {
+                    manifest.auto_elevate =
+                        rest.split('<').next().is_some_and(|text| {
+                            text.trim().eq_ignore_ascii_case("true")
+                        });
+                }
+                "dependentAssembly" => {
+                    if let Some(name) = elements(rest)
+                        .find(|(tag, _)| {
+                            element_name(tag) == "assemblyIdentity"
+                        })
+                        .and_then(|(tag, _)| attribute(tag, "name"))
+                    {
+                        manifest.dependencies.push(name.to_string());
+                    }
+                }
+                _ => (),
+            }
+        }
+        manifest
+    }
+}
+
+/// iterate over the start tags of `xml`, yielding the tag contents and the
+/// remaining document
+fn elements(xml: &str) -> impl Iterator<Item = (&str, &str)> {
+    xml.match_indices('<').filter_map(move |(idx, _)| {
+        let chunk = &xml[idx + 1..];
+        if chunk.starts_with(['/', '?', '!']) {
+            return None;
+        }
+        chunk.split_once('>')
+    })
+}
+
+/// element name without namespace prefix
+fn element_name(tag: &str) -> &str {
+    let name = tag
+        .split(|c: char| c.is_whitespace() || c == '/')
+        .next()
+        .unwrap_or_default();
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+/// value of the attribute `name` within a start tag
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = tag;
+    while let Some(idx) = rest.find(name) {
+        let preceded = rest[..idx].ends_with(|c: char| c.is_whitespace());
+        rest = &rest[idx + name.len()..];
+        let Some(value) = rest.trim_start().strip_prefix('=') else {
+            continue;
+        };
+        if !preceded {
+            continue;
+        }
+        let value = value.trim_start();
+        let quote = value.chars().next()?;
+        if quote != '"' && quote != '\'' {
+            return None;
+        }
+        return value[1..].split(quote).next();
+    }
+    None
+}
+
+/// `VS_VERSIONINFO` metadata
+#[derive(Clone, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
+pub struct VersionInfo {
+    /// `CompanyName`
+    pub company: Option<String>,
+    /// `ProductName`
+    pub product: Option<String>,
+    /// `VS_FIXEDFILEINFO` file version, or the `FileVersion` string
+    pub file_version: Option<String>,
+}
+
+impl VersionInfo {
+    /// Parse a `VS_VERSIONINFO` resource
+    #[must_use]
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        let root = VersionBlock::parse(bytes)?;
+        if root.key != "VS_VERSION_INFO" {
+            return None;
+        }
+        let mut info = Self {
+            file_version: fixed_file_version(root.value),
+            ..Self::default()
+        };
+        let strings = root
+            .children()
+            .into_iter()
+            .filter(|child| child.key == "StringFileInfo")
+            .flat_map(|string_file_info| string_file_info.children())
+            .flat_map(|string_table| string_table.children());
+        for string in strings {
+            let value = Some(string.text());
+            match string.key.as_str() {
+                "CompanyName" if info.company.is_none() => {
+                    info.company = value;
+                }
+                "ProductName" if info.product.is_none() => {
+                    info.product = value;
+                }
+                "FileVersion" if info.file_version.is_none() => {
+                    info.file_version = value;
+                }
+                _ => (),
+            }
+        }
+        Some(info)
+    }
+}
+
+impl fmt::Display for VersionInfo {
+    #[cfg(not(feature = "color"))]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Company: {} Product: {} File Version: {}",
+            self.company.as_deref().unwrap_or("None"),
+            self.product.as_deref().unwrap_or("None"),
+            self.file_version.as_deref().unwrap_or("None")
+        )
+    }
+    #[cfg(feature = "color")]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {} {}",
+            "Company:".bold(),
+            self.company.as_deref().unwrap_or("None"),
+            "Product:".bold(),
+            self.product.as_deref().unwrap_or("None"),
+            "File Version:".bold(),
+            self.file_version.as_deref().unwrap_or("None")
+        )
+    }
+}
+
+/// `dwFileVersionMS`/`dwFileVersionLS` of a `VS_FIXEDFILEINFO`
+fn fixed_file_version(value: &[u8]) -> Option<String> {
+    if value.pread::<u32>(0).ok()? != VS_FFI_SIGNATURE {
+        return None;
+    }
+    let ms: u32 = value.pread(8).ok()?;
+    let ls: u32 = value.pread(12).ok()?;
+    Some(format!("{}.{}.{}.{}", ms >> 16, ms & 0xFFFF, ls >> 16, ls & 0xFFFF))
+}
+
+fn align4(offset: usize) -> usize {
+    (offset + 3) & !3
+}
+
+/// `VS_VERSIONINFO`, `StringFileInfo`, `StringTable` and `String` share the
+/// `wLength`, `wValueLength`, `wType`, `szKey` layout
+struct VersionBlock<'a> {
+    key: String,
+    value: &'a [u8],
+    children: &'a [u8],
+}
+
+impl<'a> VersionBlock<'a> {
+    fn parse(bytes: &'a [u8]) -> Option<Self> {
+        let length = usize::from(bytes.pread::<u16>(0).ok()?);
+        let value_length = usize::from(bytes.pread::<u16>(2).ok()?);
+        let is_text = bytes.pread::<u16>(4).ok()? == 1;
+        let bytes = bytes.get(..length)?;
+        let mut offset = 6;
+        let mut key = Vec::new();
+        loop {
+            let unit: u16 = bytes.gread(&mut offset).ok()?;
+            if unit == 0 {
+                break;
+            }
+            key.push(unit);
+        }
+        let value_start = align4(offset);
+        // text values are sized in words
+        let value_end = value_start
+            + if is_text { value_length * 2 } else { value_length };
+        let value = bytes.get(value_start..value_end.min(length))?;
+        Some(Self {
+            key: String::from_utf16_lossy(&key),
+            value,
+            children: bytes.get(align4(value_end).min(length)..)?,
+        })
+    }
+    fn children(&self) -> Vec<Self> {
+        let mut children = Vec::new();
+        let mut offset = 0;
+        while offset < self.children.len() {
+            let Ok(length) = self.children.pread::<u16>(offset) else {
+                break;
+            };
+            if length == 0 {
+                break;
+            }
+            if let Some(child) = VersionBlock::parse(&self.children[offset..])
+            {
+                children.push(child);
+            }
+            offset = align4(offset + usize::from(length));
+        }
+        children
+    }
+    /// null terminated UTF-16 value
+    fn text(&self) -> String {
+        let units: Vec<u16> = self
+            .value
+            .chunks_exact(2)
+            .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+            .take_while(|unit| *unit != 0)
+            .collect();
+        String::from_utf16_lossy(&units)
+    }
+}
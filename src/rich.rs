@@ -0,0 +1,152 @@
+//! Implements Rich header toolchain fingerprinting for PE32/32+ binaries
+use goblin::pe::PE;
+use serde::{Deserialize, Serialize};
+
+/// MSVC toolset release, identified by the lowest `@comp.id` product id and
+/// build number it ships with
+pub struct Toolset {
+    pub name: &'static str,
+    pub product_min: u16,
+    pub build_min: u16,
+}
+
+const fn toolset(
+    name: &'static str,
+    product_min: u16,
+    build_min: u16,
+) -> Toolset {
+    Toolset { name, product_min, build_min }
+}
+
+// first product id of the VS2015+ (`Utc1900`) tools
+const PRODUCT_VS2015: u16 = 0x00DD;
+
+/// Known MSVC toolsets, oldest first. Product ids are shared from VS2015
+/// onwards, so later releases are told apart by build number only; append
+/// new releases at the end.
+pub const TOOLSETS: &[Toolset] = &[
+    toolset("VS2008 9.0", 0x0083, 21022),
+    toolset("VS2008 SP1 9.0", 0x0083, 30729),
+    toolset("VS2010 10.0", 0x0098, 30319),
+    toolset("VS2010 SP1 10.0", 0x0098, 40219),
+    toolset("VS2012 11.0", 0x00B0, 50727),
+    toolset("VS2013 12.0", 0x00C7, 21005),
+    toolset("VS2015 14.0", PRODUCT_VS2015, 23026),
+    toolset("VS2015 Update 1 14.0", PRODUCT_VS2015, 23506),
+    toolset("VS2015 Update 2 14.0", PRODUCT_VS2015, 23918),
+    toolset("VS2015 Update 3 14.0", PRODUCT_VS2015, 24210),
+    toolset("VS2017 15.0 (14.10)", PRODUCT_VS2015, 25017),
+    toolset("VS2017 15.3 (14.11)", PRODUCT_VS2015, 25506),
+    toolset("VS2017 15.5 (14.12)", PRODUCT_VS2015, 25830),
+    toolset("VS2017 15.6 (14.13)", PRODUCT_VS2015, 26128),
+    toolset("VS2017 15.7 (14.14)", PRODUCT_VS2015, 26428),
+    toolset("VS2017 15.8 (14.15)", PRODUCT_VS2015, 26726),
+    toolset("VS2017 15.9 (14.16)", PRODUCT_VS2015, 27023),
+    toolset("VS2019 16.0 (14.20)", PRODUCT_VS2015, 27508),
+    toolset("VS2019 16.1 (14.21)", PRODUCT_VS2015, 27702),
+    toolset("VS2019 16.2 (14.22)", PRODUCT_VS2015, 27905),
+    toolset("VS2019 16.3 (14.23)", PRODUCT_VS2015, 28105),
+    toolset("VS2019 16.4 (14.24)", PRODUCT_VS2015, 28314),
+    toolset("VS2019 16.5 (14.25)", PRODUCT_VS2015, 28610),
+    toolset("VS2019 16.6 (14.26)", PRODUCT_VS2015, 28805),
+    toolset("VS2019 16.7 (14.27)", PRODUCT_VS2015, 29110),
+    toolset("VS2019 16.8 (14.28)", PRODUCT_VS2015, 29333),
+    toolset("VS2019 16.9 (14.28)", PRODUCT_VS2015, 29910),
+    toolset("VS2019 16.10 (14.29)", PRODUCT_VS2015, 30037),
+    toolset("VS2019 16.11 (14.29)", PRODUCT_VS2015, 30133),
+    toolset("VS2022 17.0 (14.30)", PRODUCT_VS2015, 30705),
+    toolset("VS2022 17.1 (14.31)", PRODUCT_VS2015, 31104),
+    toolset("VS2022 17.2 (14.32)", PRODUCT_VS2015, 31326),
+    toolset("VS2022 17.3 (14.33)", PRODUCT_VS2015, 31629),
+    toolset("VS2022 17.4 (14.34)", PRODUCT_VS2015, 31933),
+    toolset("VS2022 17.5 (14.35)", PRODUCT_VS2015, 32215),
+    toolset("VS2022 17.6 (14.36)", PRODUCT_VS2015, 32532),
+    toolset("VS2022 17.7 (14.37)", PRODUCT_VS2015, 32822),
+    toolset("VS2022 17.8 (14.38)", PRODUCT_VS2015, 33130),
+    toolset("VS2022 17.9 (14.39)", PRODUCT_VS2015, 33519),
+    toolset("VS2022 17.10 (14.40)", PRODUCT_VS2015, 33808),
+    toolset("VS2022 17.11 (14.41)", PRODUCT_VS2015, 34120),
+    toolset("VS2022 17.12 (14.42)", PRODUCT_VS2015, 34433),
+    toolset("VS2022 17.13 (14.43)", PRODUCT_VS2015, 34808),
+    toolset("VS2022 17.14 (14.44)", PRODUCT_VS2015, 35207),
+];
+
+/// Mitigation flags and the first VS2015+ build supporting them
+pub const MITIGATION_BUILDS: &[(&str, u16)] =
+    &[("/guard:cf", 23026), ("/Qspectre", 25830), ("/CETCOMPAT", 29110)];
+
+/// index into [`TOOLSETS`] of the newest toolset matching `product`/`build`
+fn toolset_index(product: u16, build: u16) -> Option<usize> {
+    TOOLSETS.iter().rposition(|toolset| {
+        product >= toolset.product_min && build >= toolset.build_min
+    })
+}
+
+/// Decoded `@comp.id` entry of the Rich header
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
+pub struct RichEntry {
+    /// product id (tool type)
+    pub product: u16,
+    /// tool build number
+    pub build: u16,
+    /// number of objects built with the tool
+    pub count: u32,
+    /// MSVC toolset, if known
+    pub toolset: Option<String>,
+}
+
+/// Decoded Rich header and the toolchain derived from it
+#[derive(Clone, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
+pub struct RichHeader {
+    /// XOR key
+    pub key: u32,
+    /// `@comp.id` entries
+    pub entries: Vec<RichEntry>,
+    /// newest MSVC toolset used
+    pub toolset: Option<String>,
+    /// mitigation flags the newest toolset is too old to support
+    pub unsupported: Vec<String>,
+}
+
+impl RichHeader {
+    /// Decode the Rich header of a PE, returns `None` if it has none
+    #[must_use]
+    pub fn parse(pe: &PE) -> Option<Self> {
+        let rich_header = pe.header.rich_header?;
+        let entries: Vec<RichEntry> = rich_header
+            .metadatas()
+            .flatten()
+            .map(|metadata| RichEntry {
+                product: metadata.product,
+                build: metadata.build,
+                count: metadata.use_count,
+                toolset: toolset_index(metadata.product, metadata.build)
+                    .map(|idx| TOOLSETS[idx].name.to_string()),
+            })
+            .collect();
+        let newest = entries
+            .iter()
+            .filter_map(|entry| {
+                toolset_index(entry.product, entry.build)
+                    .map(|idx| (idx, entry))
+            })
+            .max_by_key(|(idx, _)| *idx);
+        let unsupported = newest
+            .map(|(_, entry)| {
+                MITIGATION_BUILDS
+                    .iter()
+                    .filter(|(_, build)| {
+                        entry.product < PRODUCT_VS2015 || entry.build < *build
+                    })
+                    .map(|(flag, _)| (*flag).to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        Some(Self {
+            key: rich_header.key,
+            toolset: newest.map(|(idx, _)| TOOLSETS[idx].name.to_string()),
+            entries,
+            unsupported,
+        })
+    }
+}
@@ -0,0 +1,151 @@
+//! Implements sanitizer and instrumentation detection shared by all formats
+#[cfg(feature = "color")]
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Deref;
+
+/// Sanitizer, coverage or fuzzing instrumentation that should not ship in
+/// release builds
+#[derive(
+    Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd,
+)]
+pub enum Sanitizer {
+    /// `AddressSanitizer` (*CFLAGS=*`-fsanitize=address`)
+    ASan,
+    /// Hardware-assisted `AddressSanitizer` (*CFLAGS=*`-fsanitize=hwaddress`)
+    HWASan,
+    /// `MemorySanitizer` (*CFLAGS=*`-fsanitize=memory`)
+    MSan,
+    /// `ThreadSanitizer` (*CFLAGS=*`-fsanitize=thread`)
+    TSan,
+    /// `UndefinedBehaviorSanitizer` (*CFLAGS=*`-fsanitize=undefined`)
+    UBSan,
+    /// `SanitizerCoverage` (*CFLAGS=*`-fsanitize-coverage=*`)
+    Coverage,
+    /// LLVM profiling (*CFLAGS=*`-fprofile-instr-generate`)
+    Profile,
+    /// gcov (*CFLAGS=*`--coverage`)
+    Gcov,
+    /// libFuzzer (*CFLAGS=*`-fsanitize=fuzzer`)
+    LibFuzzer,
+}
+
+impl fmt::Display for Sanitizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::ASan => "ASan",
+                Self::HWASan => "HWASan",
+                Self::MSan => "MSan",
+                Self::TSan => "TSan",
+                Self::UBSan => "UBSan",
+                Self::Coverage => "SanCov",
+                Self::Profile => "Profile",
+                Self::Gcov => "gcov",
+                Self::LibFuzzer => "libFuzzer",
+            }
+        )
+    }
+}
+
+// symbol prefixes of the sanitizer runtimes and instrumentation, with the
+// leading underscores stripped as their number differs between formats
+static SYMBOL_PREFIXES: [(&str, Sanitizer); 11] = [
+    ("asan_", Sanitizer::ASan),
+    ("hwasan_", Sanitizer::HWASan),
+    ("msan_", Sanitizer::MSan),
+    ("tsan_", Sanitizer::TSan),
+    ("ubsan_handle_", Sanitizer::UBSan),
+    ("sanitizer_cov_", Sanitizer::Coverage),
+    ("llvm_profile_", Sanitizer::Profile),
+    ("gcov_", Sanitizer::Gcov),
+    ("llvm_gcov_init", Sanitizer::Gcov),
+    ("LLVMFuzzerTestOneInput", Sanitizer::LibFuzzer),
+    ("LLVMFuzzerInitialize", Sanitizer::LibFuzzer),
+];
+
+// shared runtime library name fragments (`libasan.so.8`,
+// `libclang_rt.asan_osx_dynamic.dylib`, `clang_rt.asan_dynamic-x86_64.dll`)
+static LIBRARY_NAMES: [(&str, Sanitizer); 10] = [
+    ("libasan.", Sanitizer::ASan),
+    ("rt.asan", Sanitizer::ASan),
+    ("libhwasan.", Sanitizer::HWASan),
+    ("rt.hwasan", Sanitizer::HWASan),
+    ("rt.msan", Sanitizer::MSan),
+    ("libtsan.", Sanitizer::TSan),
+    ("rt.tsan", Sanitizer::TSan),
+    ("libubsan.", Sanitizer::UBSan),
+    ("rt.ubsan", Sanitizer::UBSan),
+    ("rt.fuzzer", Sanitizer::LibFuzzer),
+];
+
+/// wrapper for Vec<Sanitizer> to allow easy color output
+#[derive(Clone, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
+pub struct VecSanitizer {
+    sanitizers: Vec<Sanitizer>,
+}
+
+impl VecSanitizer {
+    /// Detect instrumentation from symbol names and linked library names
+    #[must_use]
+    pub fn detect<'a, S, L>(symbols: S, libraries: L) -> Self
+    where
+        S: IntoIterator<Item = &'a str>,
+        L: IntoIterator<Item = &'a str>,
+    {
+        let mut sanitizers: Vec<Sanitizer> = symbols
+            .into_iter()
+            .filter_map(|name| {
+                let name = name.trim_start_matches('_');
+                SYMBOL_PREFIXES.iter().find_map(|(prefix, sanitizer)| {
+                    name.starts_with(prefix).then_some(*sanitizer)
+                })
+            })
+            .chain(libraries.into_iter().filter_map(|library| {
+                let library = library.to_ascii_lowercase();
+                LIBRARY_NAMES.iter().find_map(|(fragment, sanitizer)| {
+                    library.contains(fragment).then_some(*sanitizer)
+                })
+            }))
+            .collect();
+        sanitizers.sort();
+        sanitizers.dedup();
+        Self { sanitizers }
+    }
+}
+
+impl Deref for VecSanitizer {
+    type Target = Vec<Sanitizer>;
+    fn deref(&self) -> &Self::Target {
+        &self.sanitizers
+    }
+}
+
+#[cfg(not(feature = "color"))]
+impl fmt::Display for VecSanitizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.sanitizers.is_empty() {
+            return write!(f, "None");
+        }
+        let s: Vec<String> =
+            self.sanitizers.iter().map(ToString::to_string).collect();
+        write!(f, "{}", s.join(","))
+    }
+}
+#[cfg(feature = "color")]
+impl fmt::Display for VecSanitizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.sanitizers.is_empty() {
+            return write!(f, "{}", "None".green());
+        }
+        let s: Vec<String> = self
+            .sanitizers
+            .iter()
+            .map(|sanitizer| sanitizer.to_string().red().to_string())
+            .collect();
+        write!(f, "{}", s.join(","))
+    }
+}
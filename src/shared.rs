@@ -64,6 +64,7 @@ impl fmt::Display for VecRpath {
     }
 }
 
+#[allow(clippy::large_enum_variant)]
 #[derive(Serialize, Deserialize)]
 pub enum BinResults {
     Elf(elf::CheckSecResults),
@@ -71,6 +72,9 @@ pub enum BinResults {
     Macho(macho::CheckSecResults),
 }
 
+/// # Errors
+/// Returns a message describing the failing serialization or compression
+/// step.
 pub fn compress_results(results: &BinResults) -> Result<String, String> {
     // Serialize
     let serialized = bincode::serialize(&results)
@@ -87,10 +91,12 @@ pub fn compress_results(results: &BinResults) -> Result<String, String> {
         .map_err(|_| "IO error occurred during flush".to_string())?;
 
     // Encode
-    let encoded = BASE64_STANDARD.encode(compressed);
-    Ok(encoded)
+    Ok(BASE64_STANDARD.encode(compressed))
 }
 
+/// # Errors
+/// Returns a message describing the failing decoding, decompression or
+/// deserialization step.
 pub fn decompress_results(encoded_bytes: &[u8]) -> Result<BinResults, String> {
     let encoded = std::str::from_utf8(encoded_bytes).map_err(|_| "Error converting bytes to utf".to_string())?.to_string();
     let compressed = BASE64_STANDARD.decode(encoded).map_err(|_| "Decoding failed".to_string())?;
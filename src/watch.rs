@@ -0,0 +1,207 @@
+#[cfg(all(feature = "color", feature = "elf"))]
+use colored::Colorize;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use checksec::output;
+#[cfg(feature = "elf")]
+use checksec::underline;
+
+#[cfg(feature = "elf")]
+use crate::binary::BinSpecificProperties;
+use crate::binary::Binary;
+use crate::filter::WalkOptions;
+use crate::{
+    library_lookup, parse_file_impl, print_binary_results, save_result_cache,
+    scan_directory, Cache, Lookup,
+};
+
+/// quiet time after the last change to a file before it is checked, so
+/// files still being written are not reported
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Check the binaries below `basepath`, then keep checking binaries as they
+/// are written and print those whose results changed
+pub fn watch(
+    basepath: &Path,
+    scan_dynlibs: bool,
+    options: &WalkOptions,
+    settings: &output::Settings,
+) {
+    let lookup = library_lookup(scan_dynlibs);
+    let cache: Cache = Arc::new(Mutex::new(HashMap::new()));
+
+    let (bins, summary) = scan_directory(
+        basepath,
+        scan_dynlibs,
+        options,
+        lookup.as_ref(),
+        &cache,
+    );
+    save_result_cache();
+    print_binary_results(&bins, settings);
+    if summary.is_notable() {
+        eprintln!("{summary}");
+    }
+
+    let mut reported: HashMap<PathBuf, Vec<Binary>> = HashMap::new();
+    for bin in bins {
+        reported.entry(bin.file.clone()).or_default().push(bin);
+    }
+
+    let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
+    let mut watcher = notify::recommended_watcher(tx).unwrap_or_else(|err| {
+        eprintln!("Failed to initialize file watcher: {err}");
+        process::exit(1)
+    });
+    if let Err(err) = watcher.watch(basepath, RecursiveMode::Recursive) {
+        eprintln!("Failed to watch {}: {}", basepath.display(), err);
+        process::exit(1);
+    }
+
+    let recheck = Recheck {
+        basepath,
+        scan_dynlibs,
+        options,
+        lookup: lookup.as_ref(),
+        cache: &cache,
+        settings,
+    };
+    let mut pending: HashMap<PathBuf, Instant> = HashMap::new();
+    loop {
+        let timeout = pending.values().min().map_or(Duration::MAX, |last| {
+            DEBOUNCE.saturating_sub(last.elapsed())
+        });
+        match rx.recv_timeout(timeout) {
+            Ok(Ok(event)) => match event.kind {
+                EventKind::Create(_) | EventKind::Modify(_) => {
+                    for path in event.paths {
+                        pending.insert(path, Instant::now());
+                    }
+                }
+                EventKind::Remove(_) => {
+                    for path in &event.paths {
+                        pending.remove(path);
+                        forget(path, &mut reported);
+                    }
+                }
+                _ => {}
+            },
+            Ok(Err(err)) => eprintln!("File watcher error: {err}"),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        let settled: Vec<PathBuf> = pending
+            .iter()
+            .filter(|(_, last)| last.elapsed() >= DEBOUNCE)
+            .map(|(path, _)| path.clone())
+            .collect();
+        if settled.is_empty() {
+            continue;
+        }
+        for path in settled {
+            pending.remove(&path);
+            recheck.path(&path, &mut reported);
+        }
+        save_result_cache();
+    }
+}
+
+struct Recheck<'a> {
+    basepath: &'a Path,
+    scan_dynlibs: bool,
+    options: &'a WalkOptions,
+    lookup: Option<&'a Lookup>,
+    cache: &'a Cache,
+    settings: &'a output::Settings,
+}
+
+impl Recheck<'_> {
+    /// re-check a changed file, or the files of a directory created or
+    /// moved into the watched one
+    fn path(&self, path: &Path, reported: &mut HashMap<PathBuf, Vec<Binary>>) {
+        if path.is_dir() {
+            for entry in self.options.walk(path).flatten() {
+                if entry.file_type().is_some_and(|ft| ft.is_file()) {
+                    self.file(entry.path(), reported);
+                }
+            }
+        } else if path.is_file() {
+            self.file(path, reported);
+        } else {
+            // renamed away
+            forget(path, reported);
+        }
+    }
+    /// re-check a file and print the results if they differ from the last
+    /// ones reported
+    fn file(&self, path: &Path, reported: &mut HashMap<PathBuf, Vec<Binary>>) {
+        // filtered out, or not a binary
+        if !matches!(self.options.skip(path, self.basepath), Ok(None)) {
+            return;
+        }
+        // a truncated binary the next write event retries
+        let bins = match parse_file_impl(
+            path,
+            self.scan_dynlibs,
+            self.lookup,
+            &mut Some(Arc::clone(self.cache)),
+        ) {
+            Ok(bins) => bins,
+            Err(_) => return,
+        };
+        let previous = reported.get(path);
+        if previous.is_some_and(|previous| json!(previous) == json!(bins)) {
+            return;
+        }
+        print_binary_results(&bins, self.settings);
+        #[cfg(feature = "elf")]
+        if let Some(previous) = previous
+            .filter(|_| matches!(self.settings.format, output::Format::Text))
+        {
+            print_changes(previous, &bins);
+        }
+        reported.insert(path.to_path_buf(), bins);
+    }
+}
+
+/// drop the reported results of a removed file, or of the files below a
+/// removed directory
+fn forget(path: &Path, reported: &mut HashMap<PathBuf, Vec<Binary>>) {
+    reported.retain(|file, _| !file.starts_with(path));
+}
+
+/// list the checks of ELF binaries that changed since the last report
+#[cfg(feature = "elf")]
+fn print_changes(previous: &[Binary], current: &[Binary]) {
+    let blobs = |bins: &'_ [Binary]| -> Vec<BinSpecificProperties> {
+        bins.iter()
+            .flat_map(|bin| {
+                bin.blobs.iter().map(|blob| blob.properties.clone())
+            })
+            .collect()
+    };
+    for (before, after) in blobs(previous).iter().zip(blobs(current).iter()) {
+        #[allow(irrefutable_let_patterns)]
+        if let (
+            BinSpecificProperties::Elf(before),
+            BinSpecificProperties::Elf(after),
+        ) = (before, after)
+        {
+            for difference in before.differences(after) {
+                println!(
+                    " \u{21aa} {} {} \u{2192} {}",
+                    underline!(format!("{}:", difference.check)),
+                    difference.file,
+                    difference.memory
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,14 @@
+#!/bin/sh
+# Build the debug info fixtures from the elf/ directory:
+#   debuginfo - DWARF 4 in zlib compressed (SHF_COMPRESSED) sections
+#   debuglink - fully stripped, DWARF 5 split off behind .gnu_debuglink
+#               plus a .gnu_debugaltlink as written by dwz
+set -e
+gcc -O2 -g -gdwarf-4 -gz=zlib -o debuginfo src/provenance.c
+gcc -O2 -g -gdwarf-5 -o debuglink src/provenance.c
+objcopy --only-keep-debug debuglink debuglink.debug
+strip --strip-all debuglink
+objcopy --add-gnu-debuglink=debuglink.debug debuglink
+printf 'common.debug\0' > debugaltlink
+objcopy --add-section .gnu_debugaltlink=debugaltlink debuglink
+rm debuglink.debug debugaltlink
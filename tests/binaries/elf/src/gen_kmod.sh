@@ -0,0 +1,26 @@
+#!/bin/sh
+# Build the kernel module fixtures from the elf/ directory:
+#   kmod.ko             - GPL, in-tree, retpoline, kCFI and IBT, signed with
+#                         a throwaway key like scripts/sign-file does
+#   kmod_proprietary.ko - proprietary, out-of-tree, unsigned
+set -e
+gcc -O2 -c -fno-pie -mcmodel=kernel -fcf-protection=branch \
+    -mindirect-branch=thunk-extern -o kmod.ko src/kmod.c
+gcc -O2 -c -fno-pie -mcmodel=kernel -fcf-protection=none -DPROPRIETARY \
+    -o kmod_proprietary.ko src/kmod.c
+openssl req -new -nodes -utf8 -sha256 -days 1 -batch -x509 \
+    -subj "/CN=checksec test key" -newkey rsa:2048 \
+    -keyout signing_key.pem -out signing_key.pem 2>/dev/null
+openssl cms -sign -in kmod.ko -signer signing_key.pem -noattr -binary \
+    -outform DER -nocerts -md sha256 -out kmod.ko.p7s
+python3 - <<'PY'
+import os, struct
+sig = open("kmod.ko.p7s", "rb").read()
+with open("kmod.ko", "ab") as ko:
+    ko.write(sig)
+    # struct module_signature: algo, hash, id_type (PKEY_ID_PKCS7),
+    # signer_len, key_id_len, pad[3], be32 sig_len
+    ko.write(struct.pack(">BBBBB3xI", 0, 0, 2, 0, 0, len(sig)))
+    ko.write(b"~Module signature appended~\n")
+PY
+rm signing_key.pem kmod.ko.p7s
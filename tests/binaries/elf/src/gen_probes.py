@@ -0,0 +1,112 @@
+#!/usr/bin/env python3
+"""Build the stack probe fixtures for architectures without a toolchain in
+CI. Each is a minimal static ELF64 with a hand assembled .text section:
+
+  probe_aarch64   - GCC style `sub sp, sp, #0x1000; str xzr, [sp]` loop
+  noprobe_aarch64 - the same loop without the probing store
+  probe_riscv64   - GCC style `sub sp, sp, t0; sd zero, 0(sp)` loop
+
+Run from the elf/ directory.
+"""
+import struct
+
+EM_AARCH64 = 183
+EM_RISCV = 243
+BASE = 0x400000
+TEXT_OFFSET = 0x1000
+# EF_RISCV_RVC | EF_RISCV_FLOAT_ABI_DOUBLE
+EF_RISCV_RVC_DOUBLE = 0x5
+
+
+def u32(*insns):
+    return b"".join(struct.pack("<I", insn) for insn in insns)
+
+
+def u16(*insns):
+    return b"".join(struct.pack("<H", insn) for insn in insns)
+
+
+def elf(machine, text, flags=0):
+    shstrtab = b"\0.text\0.shstrtab\0"
+    shstrtab_offset = TEXT_OFFSET + len(text)
+    shoff = (shstrtab_offset + len(shstrtab) + 7) & ~7
+    ehdr = struct.pack(
+        "<4sBBBBB7sHHIQQQIHHHHHH",
+        b"\x7fELF", 2, 1, 1, 0, 0, b"\0" * 7,
+        2, machine, 1, BASE + TEXT_OFFSET, 64, shoff, flags,
+        64, 56, 2, 64, 3, 2,
+    )
+    # PT_LOAD r-x over the whole file and a non executable PT_GNU_STACK
+    phdrs = struct.pack(
+        "<IIQQQQQQ", 1, 5, 0, BASE, BASE, shoff, shoff, 0x1000
+    ) + struct.pack("<IIQQQQQQ", 0x6474E551, 6, 0, 0, 0, 0, 0, 16)
+    shdrs = bytes(64) + struct.pack(
+        "<IIQQQQIIQQ", 1, 1, 6, BASE + TEXT_OFFSET, TEXT_OFFSET, len(text),
+        0, 0, 4, 0,
+    ) + struct.pack(
+        "<IIQQQQIIQQ", 7, 3, 0, 0, shstrtab_offset, len(shstrtab), 0, 0, 1, 0
+    )
+    image = ehdr + phdrs
+    image += bytes(TEXT_OFFSET - len(image)) + text + shstrtab
+    return image + bytes(shoff - len(image)) + shdrs
+
+
+# AArch64
+def a64_sub_sp_imm(pages, rd=31):
+    """sub rd, sp, #pages, lsl #12"""
+    return 0xD1400000 | (pages << 10) | (31 << 5) | rd
+
+
+def a64_b_ne(offset):
+    return 0x54000001 | (((offset // 4) & 0x7FFFF) << 5)
+
+
+A64_NOP = 0xD503201F
+
+
+def aarch64(probe):
+    return u32(
+        0xA9BF7BFD,  # stp x29, x30, [sp, #-16]!
+        0x910003FD,  # mov x29, sp
+        a64_sub_sp_imm(0x20, rd=16),  # sub x16, sp, #0x20, lsl #12
+        a64_sub_sp_imm(1),  # sub sp, sp, #0x1, lsl #12
+        # str xzr, [sp, #1024]
+        0xF9000000 | ((1024 // 8) << 10) | (31 << 5) | 31 if probe else A64_NOP,
+        0xEB30603F,  # cmp sp, x16
+        a64_b_ne(-12),  # b.ne <sub sp>
+        0xD65F03C0,  # ret
+    )
+
+
+# RISC-V
+def rv_b(funct3, rs1, rs2, offset):
+    imm = offset & 0x1FFF
+    return (
+        ((imm >> 12) & 1) << 31 | ((imm >> 5) & 0x3F) << 25 | rs2 << 20
+        | rs1 << 15 | funct3 << 12 | ((imm >> 1) & 0xF) << 8
+        | ((imm >> 11) & 1) << 7 | 0x63
+    )
+
+
+def riscv64():
+    sp, t0, t1 = 2, 5, 6
+    return (
+        u32(
+            0x13 | sp << 7 | sp << 15 | ((-16 & 0xFFF) << 20),  # addi sp, sp, -16
+            0x37 | t0 << 7 | 0x1 << 12,  # lui t0, 0x1
+            0x37 | t1 << 7 | 0x20 << 12,  # lui t1, 0x20
+            0x33 | t1 << 7 | sp << 15 | t1 << 20 | 0x20 << 25,  # sub t1, sp, t1
+            0x33 | sp << 7 | sp << 15 | t0 << 20 | 0x20 << 25,  # sub sp, sp, t0
+        )
+        + u16(0xE002)  # c.sdsp zero, 0(sp)
+        + u32(rv_b(1, sp, t1, -6), 0x00008067)  # bne sp, t1, <sub sp>; ret
+    )
+
+
+for name, image in [
+    ("probe_aarch64", elf(EM_AARCH64, aarch64(True))),
+    ("noprobe_aarch64", elf(EM_AARCH64, aarch64(False))),
+    ("probe_riscv64", elf(EM_RISCV, riscv64(), flags=EF_RISCV_RVC_DOUBLE)),
+]:
+    with open(name, "wb") as f:
+        f.write(image)
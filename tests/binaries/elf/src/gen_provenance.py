@@ -0,0 +1,75 @@
+#!/usr/bin/env python3
+"""Build `provenance` with a build-id, package metadata note, recorded
+GCC switches and annobin-style `.gnu.build.attributes` notes.
+
+The notes are what the annobin plugin emits for an object compiled with
+`-fstack-protector-strong -D_FORTIFY_SOURCE=2 -fcf-protection=full
+-fstack-clash-protection`.
+"""
+import os
+import subprocess
+
+NT_GNU_BUILD_ATTRIBUTE_OPEN = 0x100
+PACKAGE = '{"type":"deb","os":"debian","name":"provenance","version":"1.0-1","architecture":"amd64"}'
+CFLAGS = [
+    "-O2",
+    "-fstack-protector-strong",
+    "-D_FORTIFY_SOURCE=2",
+    "-fcf-protection=full",
+    "-fstack-clash-protection",
+    "-frecord-gcc-switches",
+]
+
+
+def string(attr, value):
+    return b"GA$" + attr + value.encode() + b"\0"
+
+
+def numeric(attr, value):
+    return b"GA*" + attr + bytes([value]) + b"\0"
+
+
+def boolean(attr, value):
+    return b"GA" + (b"+" if value else b"!") + attr + b"\0"
+
+
+NOTES = [
+    string(b"\x01", "3p1"),
+    string(b"\x05", "gcc 12.2.0"),
+    numeric(b"\x02", 3),
+    numeric(b"FORTIFY\0", 2),
+    numeric(b"cf_protection\0", 4),
+    boolean(b"stack_clash", True),
+]
+
+
+def note(name, first):
+    # the first note of an object carries the address range it covers
+    desc = "\t.quad main, main_end\n" if first else ""
+    data = ", ".join(str(b) for b in name)
+    pad = -len(name) % 4
+    return (
+        f"\t.long {len(name)}, {16 if first else 0}, {NT_GNU_BUILD_ATTRIBUTE_OPEN}\n"
+        f"\t.byte {data}\n" + (f"\t.zero {pad}\n" if pad else "") + desc
+    )
+
+
+def main():
+    here = os.path.dirname(os.path.abspath(__file__))
+    asm = os.path.join(here, "provenance_notes.s")
+    with open(asm, "w") as f:
+        f.write("\t.text\n\t.globl main_end\nmain_end:\n")
+        f.write('\t.section .gnu.build.attributes, "", %note\n\t.balign 4\n')
+        for i, name in enumerate(NOTES):
+            f.write(note(name, i == 0))
+        f.write('\t.section .note.GNU-stack, "", %progbits\n')
+    subprocess.check_call(
+        ["gcc", *CFLAGS, "-o", os.path.join(here, "..", "provenance"),
+         os.path.join(here, "provenance.c"), asm,
+         "-Wl,--build-id=sha1", "-Xlinker", f"--package-metadata={PACKAGE}"]
+    )
+    os.remove(asm)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,43 @@
+/* Stand-in for a kernel module, with the sections modpost and the module
+ * macros would add. Built by gen_kmod.sh. */
+#define MODINFO(tag, value) \
+	static const char __mod_##tag[] __attribute__((used, \
+		section(".modinfo"), aligned(1))) = #tag "=" value
+
+#ifdef PROPRIETARY
+MODINFO(license, "Proprietary");
+MODINFO(vermagic, "6.1.0 SMP preempt mod_unload modversions ");
+#else
+MODINFO(license, "GPL");
+MODINFO(retpoline, "Y");
+MODINFO(intree, "Y");
+MODINFO(vermagic, "6.1.0-18-amd64 SMP preempt mod_unload modversions ");
+#endif
+MODINFO(name, "kmod");
+
+struct modversion_info {
+	unsigned long crc;
+	char name[64 - sizeof(unsigned long)];
+};
+
+static const struct modversion_info ____versions[]
+	__attribute__((used, section("__versions"))) = {
+	{ 0x12345678, "module_layout" },
+	{ 0x92997ed8, "_printk" },
+};
+
+/* struct module is opaque here, only its section matters */
+char __this_module[0x500]
+	__attribute__((section(".gnu.linkonce.this_module")));
+
+extern int printk(const char *fmt, ...);
+
+#ifndef PROPRIETARY
+/* kCFI type hash preamble, as emitted by clang -fsanitize=kcfi */
+__asm__(".text\n__cfi_kmod_init:\n.long 0x12345678\n");
+#endif
+
+int kmod_init(void)
+{
+	return printk("kmod loaded\n");
+}
@@ -0,0 +1,15 @@
+/* A page sized frame whose first slot is zeroed right after the
+ * allocation, which looks like a single unrolled stack clash probe */
+#include <stdio.h>
+
+__attribute__((noinline)) long page(long seed) {
+    volatile long a[512];
+    a[0] = 0;
+    printf("%ld\n", seed);
+    return a[0] + seed;
+}
+
+int main(int argc, char **argv) {
+    (void)argv;
+    return (int)page(argc);
+}
@@ -0,0 +1,7 @@
+/* Trivial program: -fstack-protector-strong emits no canary and
+ * _FORTIFY_SOURCE has nothing to fortify, so only the annobin notes tell
+ * which hardening flags were in effect. */
+int main(int argc, char **argv) {
+    (void)argv;
+    return argc > 1;
+}
@@ -0,0 +1,9 @@
+/* Built with sanitizers, coverage and fuzzing instrumentation for the
+ * sanitizer detection tests, see ../README.md */
+#include <string.h>
+
+int main(int argc, char **argv) {
+    char buf[16];
+    strcpy(buf, argc > 1 ? argv[1] : "");
+    return buf[0] / argc;
+}
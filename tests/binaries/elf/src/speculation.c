@@ -0,0 +1,26 @@
+/* Spectre v1/v2 and straight-line speculation hardening fixture */
+#include <stdio.h>
+#include <stdlib.h>
+#include <emmintrin.h>
+
+static int table[16];
+
+static int twice(int x) { return 2 * x; }
+static int square(int x) { return x * x; }
+
+int (*volatile ops[2])(int) = {twice, square};
+
+/* bounds check bypass barrier, as inserted by speculative load hardening */
+__attribute__((noinline)) int lookup(unsigned int index) {
+    if (index < 16) {
+        _mm_lfence();
+        return table[index];
+    }
+    return 0;
+}
+
+int main(int argc, char **argv) {
+    int op = argc > 1 ? atoi(argv[1]) & 1 : 0;
+    printf("%d\n", ops[op](lookup((unsigned int)argc)));
+    return 0;
+}
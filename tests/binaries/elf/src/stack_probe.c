@@ -0,0 +1,18 @@
+/* Stack clash probes for a fixed size frame, unrolled when the frame spans
+ * only a few probe intervals and looped otherwise */
+#include <stdio.h>
+#include <string.h>
+
+__attribute__((noinline)) int fixed(int seed) {
+    char buf[20000];
+    memset(buf, seed, sizeof buf);
+    /* keep the frame alive */
+    __asm__ volatile("" : : "r"(buf) : "memory");
+    return buf[seed % sizeof buf];
+}
+
+int main(int argc, char **argv) {
+    (void)argv;
+    printf("%d\n", fixed(argc));
+    return 0;
+}
@@ -0,0 +1,10 @@
+#!/usr/bin/env python3
+"""Write `noshdr`, a copy of `all` without section headers, as left behind
+by packers and `sstrip`. Run from the elf/ directory."""
+import struct
+
+image = bytearray(open("all", "rb").read())
+# e_shoff, then e_shnum and e_shstrndx of the ELF64 header
+struct.pack_into("<Q", image, 0x28, 0)
+struct.pack_into("<HH", image, 0x3C, 0, 0)
+open("noshdr", "wb").write(image)
@@ -0,0 +1,88 @@
+#!/usr/bin/env python3
+"""Generate `chkstk.exe.bin`, a PE32+ image whose `main` allocates a large
+frame through a copy of the MSVC x64 `__chkstk`, with `.pdata` entries for
+both functions but no symbols. Needs GNU as and objcopy."""
+import os
+import struct
+import subprocess
+import tempfile
+
+import minipe
+
+CODE = """
+    .intel_syntax noprefix
+    .text
+main:
+    mov     eax, 0x2010
+    call    __chkstk
+    sub     rsp, rax
+    xor     eax, eax
+    add     rsp, 0x2010
+    ret
+    .p2align 4, 0xcc
+__chkstk:
+    sub     rsp, 0x10
+    mov     [rsp], r10
+    mov     [rsp+8], r11
+    xor     r11, r11
+    lea     r10, [rsp+0x18]
+    sub     r10, rax
+    cmovb   r10, r11
+    mov     r11, gs:[0x10]
+    cmp     r10, r11
+    jae     2f
+    and     r10w, 0xf000
+1:
+    lea     r11, [r11-0x1000]
+    mov     byte ptr [r11], 0
+    cmp     r10, r11
+    jne     1b
+2:
+    mov     r10, [rsp]
+    mov     r11, [rsp+8]
+    add     rsp, 0x10
+    ret
+end:
+"""
+
+
+def assemble(source):
+    """assemble `source`, return the code and its label offsets"""
+    with tempfile.TemporaryDirectory() as tmp:
+        obj, raw = os.path.join(tmp, "code.o"), os.path.join(tmp, "code.bin")
+        subprocess.run(["as", "--64", "-o", obj], input=source.encode(),
+                       check=True)
+        subprocess.run(["objcopy", "-O", "binary", "-j", ".text", obj, raw],
+                       check=True)
+        nm = subprocess.run(["nm", obj], capture_output=True, check=True)
+        labels = {
+            name: int(value, 16)
+            for value, _, name in (line.split() for line in nm.stdout.decode().splitlines())
+        }
+        with open(raw, "rb") as fp:
+            return fp.read(), labels
+
+
+if __name__ == "__main__":
+    code, labels = assemble(CODE)
+    section = minipe.Section()
+    text = section.add(code, 16)
+    # UNWIND_INFO version 1 without unwind codes
+    unwind = section.add(struct.pack("<BBBB", 1, 0, 0, 0), 4)
+    pdata = section.add(
+        b"".join(
+            struct.pack("<III", text + labels[start], text + labels[end], unwind)
+            for start, end in [("main", "__chkstk"), ("__chkstk", "end")]
+        ),
+        4,
+    )
+    with open("../chkstk.exe.bin", "wb") as fp:
+        fp.write(
+            minipe.build(
+                section.data,
+                {3: (pdata, 24)},
+                minipe.DLLCHARACTERISTICS_DYNAMIC_BASE
+                | minipe.DLLCHARACTERISTICS_NX_COMPAT,
+                pe32_plus=True,
+            )
+        )
@@ -0,0 +1,26 @@
+#!/usr/bin/env python3
+"""Generate a minimal MinGW style PE32+ image carrying DWARF sections and a
+COFF symbol table."""
+import struct
+
+import minipe
+
+# DWARF 4 compilation unit header: unit_length, version, debug_abbrev_offset,
+# address_size, followed by an empty DIE
+DEBUG_INFO = struct.pack("<IHIB", 8, 4, 0, 8) + b"\0"
+
+if __name__ == "__main__":
+    with open("debuginfo-mingw.exe.bin", "wb") as fp:
+        fp.write(
+            minipe.build(
+                b"\xc3",
+                {},
+                minipe.DLLCHARACTERISTICS_DYNAMIC_BASE
+                | minipe.DLLCHARACTERISTICS_NX_COMPAT,
+                pe32_plus=True,
+                debug_sections={
+                    ".debug_info": DEBUG_INFO,
+                    ".debug_abbrev": b"\0",
+                },
+            )
+        )
@@ -0,0 +1,129 @@
+#!/usr/bin/env python3
+"""Generate minimal PE32 .NET assemblies for the CLI header/metadata tests.
+
+Only the pieces checksec parses are emitted: the CLI header, strong name
+signature, ReadyToRun header and a metadata root with the Module, TypeRef,
+MemberRef, CustomAttribute and Assembly tables.
+"""
+import struct
+
+import minipe
+from minipe import (
+    DLLCHARACTERISTICS_DYNAMIC_BASE,
+    DLLCHARACTERISTICS_NX_COMPAT,
+    TEXT_RVA,
+)
+
+COMIMAGE_FLAGS_ILONLY = 0x1
+COMIMAGE_FLAGS_32BITREQUIRED = 0x2
+COMIMAGE_FLAGS_STRONGNAMESIGNED = 0x8
+
+
+def compressed(n):
+    if n < 0x80:
+        return bytes([n])
+    return struct.pack(">H", n | 0x8000)
+
+
+class Heap:
+    def __init__(self, blob):
+        self.data = b"\0"
+        self.blob = blob
+
+    def add(self, value):
+        idx = len(self.data)
+        if self.blob:
+            self.data += compressed(len(value)) + value
+        else:
+            self.data += value.encode() + b"\0"
+        return idx
+
+
+def pad4(data):
+    return data + b"\0" * (-len(data) % 4)
+
+
+def public_key():
+    # PublicKeyBlob + PUBLICKEYBLOB (BLOBHEADER, RSAPUBKEY, modulus)
+    key = struct.pack("<BBHI", 6, 2, 0, 0x2400)
+    key += b"RSA1" + struct.pack("<II", 1024, 65537) + bytes(range(128))
+    return struct.pack("<III", 0x2400, 0x8004, len(key)) + key
+
+
+def metadata(framework):
+    strings, blobs = Heap(False), Heap(True)
+    module = struct.pack("<HHHHH", 0, strings.add("checksec.dll"), 0, 0, 0)
+    type_ref = struct.pack(
+        "<HHH",
+        0,
+        strings.add("TargetFrameworkAttribute"),
+        strings.add("System.Runtime.Versioning"),
+    )
+    # MemberRefParent: TypeRef row 1
+    member_ref = struct.pack(
+        "<HHH", (1 << 3) | 1, strings.add(".ctor"), blobs.add(b"\x20\x01\x01\x0e")
+    )
+    value = b"\x01\x00" + compressed(len(framework)) + framework.encode() + b"\0\0"
+    # HasCustomAttribute: Assembly row 1, CustomAttributeType: MemberRef row 1
+    custom_attribute = struct.pack(
+        "<HHH", (1 << 5) | 14, (1 << 3) | 3, blobs.add(value)
+    )
+    assembly = struct.pack(
+        "<IHHHHIHHH",
+        0x8004,
+        1, 0, 0, 0,
+        1,
+        blobs.add(public_key()),
+        strings.add("checksec"),
+        0,
+    )
+    tables = [(0x00, module), (0x01, type_ref), (0x0A, member_ref),
+              (0x0C, custom_attribute), (0x20, assembly)]
+    valid = sum(1 << table for table, _ in tables)
+    stream = struct.pack("<IBBBBQQ", 0, 2, 0, 0, 1, valid, 0)
+    stream += b"".join(struct.pack("<I", 1) for _ in tables)
+    stream += b"".join(row for _, row in tables)
+    streams = [(b"#~", pad4(stream)), (b"#Strings", pad4(strings.data)),
+               (b"#Blob", pad4(blobs.data))]
+
+    version = pad4(b"v4.0.30319\0")
+    header_size = 16 + len(version) + 4
+    header_size += sum(8 + len(pad4(name + b"\0")) for name, _ in streams)
+    root = b"BSJB" + struct.pack("<HHII", 1, 1, 0, len(version)) + version
+    root += struct.pack("<HH", 0, len(streams))
+    offset, body = header_size, b""
+    for name, data in streams:
+        root += struct.pack("<II", offset, len(data)) + pad4(name + b"\0")
+        offset += len(data)
+        body += data
+    return root + body
+
+
+def assembly(flags, dll_characteristics, signature, ready_to_run):
+    md = metadata(".NETCoreApp,Version=v8.0")
+    sig_rva = TEXT_RVA + 0x48
+    r2r_rva = sig_rva + len(signature)
+    md_rva = TEXT_RVA + 0x100
+    cor20 = struct.pack("<IHH", 72, 2, 5)
+    cor20 += struct.pack("<II", md_rva, len(md))
+    cor20 += struct.pack("<II", flags, 0)
+    cor20 += struct.pack("<II", 0, 0)  # Resources
+    cor20 += struct.pack("<II", sig_rva, len(signature))
+    cor20 += struct.pack("<IIIIII", 0, 0, 0, 0, 0, 0)
+    cor20 += struct.pack("<II", r2r_rva if ready_to_run else 0,
+                         16 if ready_to_run else 0)
+    text = cor20 + signature + struct.pack("<IHHI", 0x00525452, 9, 1, 0)
+    text = text.ljust(0x100, b"\0") + md
+    return minipe.build(text, {14: (TEXT_RVA, 72)}, dll_characteristics)
+
+
+if __name__ == "__main__":
+    with open("dotnet-signed.dll.bin", "wb") as fp:
+        fp.write(assembly(
+            COMIMAGE_FLAGS_ILONLY | COMIMAGE_FLAGS_STRONGNAMESIGNED,
+            DLLCHARACTERISTICS_DYNAMIC_BASE | DLLCHARACTERISTICS_NX_COMPAT,
+            bytes(range(1, 129)),
+            False,
+        ))
+    with open("dotnet-mixed-delaysigned.dll.bin", "wb") as fp:
+        fp.write(assembly(COMIMAGE_FLAGS_32BITREQUIRED, 0, bytes(128), True))
@@ -0,0 +1,101 @@
+#!/usr/bin/env python3
+"""Generate minimal PE32+ images with import and delay-import tables.
+
+Imports are given as names or, for ordinal-only imports, integers.
+`sanitizers.exe.bin` imports from the clang AddressSanitizer runtime and the
+SanitizerCoverage callbacks, `secure_crt.exe.bin` `_s` functions from the CRT
+and from a DLL that is not part of it.
+"""
+import struct
+
+import minipe
+
+IMPORTS = {
+    "msvcrt.dll": ["strcpy", "strcpy_s", "memcpy_s", 12],
+    "KERNEL32.dll": ["VirtualAlloc", "GetLastError"],
+}
+DELAY_IMPORTS = {
+    "SHLWAPI.dll": ["StrCpyW", 5],
+}
+SANITIZER_IMPORTS = {
+    "clang_rt.asan_dynamic-x86_64.dll": ["__asan_init", "__asan_report_load8"],
+    "KERNEL32.dll": ["GetLastError"],
+}
+SANITIZER_DELAY_IMPORTS = {
+    "sancov.dll": ["__sanitizer_cov_trace_pc_guard"],
+}
+SECURE_CRT_IMPORTS = {
+    "ucrtbase.dll": ["strcat_s"],
+    "vendor.dll": ["decode_s"],
+}
+SECURE_CRT_DELAY_IMPORTS = {
+    "api-ms-win-crt-string-l1-1-0.dll": ["wcscpy_s"],
+    "vendor_delay.dll": ["encode_s"],
+}
+
+IMAGE_ORDINAL_FLAG64 = 1 << 63
+DLATTR_RVA = 0x1
+
+
+def thunks(section, functions):
+    entries = []
+    for function in functions:
+        if isinstance(function, int):
+            entries.append(IMAGE_ORDINAL_FLAG64 | function)
+        else:
+            # IMAGE_IMPORT_BY_NAME: Hint, Name
+            entries.append(
+                section.add(struct.pack("<H", 0) + function.encode() + b"\0", 2)
+            )
+    return b"".join(struct.pack("<Q", entry) for entry in entries + [0])
+
+
+def image(imports, delay_imports):
+    section = minipe.Section()
+    descriptors = []
+    for dll, functions in imports.items():
+        name = section.add(dll.encode() + b"\0")
+        table = thunks(section, functions)
+        descriptors.append((section.add(table), name, section.add(table)))
+    delay_descriptors = []
+    for dll, functions in delay_imports.items():
+        name = section.add(dll.encode() + b"\0")
+        table = thunks(section, functions)
+        delay_descriptors.append(
+            (name, section.add(bytes(8)), section.add(table), section.add(table))
+        )
+
+    # IMAGE_IMPORT_DESCRIPTOR
+    import_directory = b"".join(
+        struct.pack("<IIIII", lookup, 0, 0, name, address)
+        for lookup, name, address in descriptors
+    ) + bytes(20)
+    import_rva = section.add(import_directory)
+    # ImgDelayDescr
+    delay_directory = b"".join(
+        struct.pack("<IIIIIIII", DLATTR_RVA, name, handle, address, lookup,
+                    0, 0, 0)
+        for name, handle, address, lookup in delay_descriptors
+    ) + bytes(32)
+    delay_rva = section.add(delay_directory)
+
+    directories = {
+        1: (import_rva, len(import_directory)),
+        13: (delay_rva, len(delay_directory)),
+    }
+    return minipe.build(
+        section.data,
+        directories,
+        minipe.DLLCHARACTERISTICS_DYNAMIC_BASE
+        | minipe.DLLCHARACTERISTICS_NX_COMPAT,
+        pe32_plus=True,
+    )
+
+
+if __name__ == "__main__":
+    with open("imports.exe.bin", "wb") as fp:
+        fp.write(image(IMPORTS, DELAY_IMPORTS))
+    with open("sanitizers.exe.bin", "wb") as fp:
+        fp.write(image(SANITIZER_IMPORTS, SANITIZER_DELAY_IMPORTS))
+    with open("secure_crt.exe.bin", "wb") as fp:
+        fp.write(image(SECURE_CRT_IMPORTS, SECURE_CRT_DELAY_IMPORTS))
@@ -0,0 +1,114 @@
+#!/usr/bin/env python3
+"""Generate a minimal PE32+ image with RT_MANIFEST and RT_VERSION resources."""
+import struct
+
+import minipe
+
+RT_VERSION = 16
+RT_MANIFEST = 24
+LANG_EN_US = 0x409
+
+MANIFEST = b"""<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<assembly xmlns="urn:schemas-microsoft-com:asm.v1" manifestVersion="1.0">
+  <trustInfo xmlns="urn:schemas-microsoft-com:asm.v3">
+    <security>
+      <requestedPrivileges>
+        <requestedExecutionLevel level="requireAdministrator" uiAccess="true"/>
+      </requestedPrivileges>
+    </security>
+  </trustInfo>
+  <asmv3:application xmlns:asmv3="urn:schemas-microsoft-com:asm.v3">
+    <asmv3:windowsSettings xmlns="http://schemas.microsoft.com/SMI/2005/WindowsSettings">
+      <autoElevate>true</autoElevate>
+    </asmv3:windowsSettings>
+  </asmv3:application>
+  <dependency>
+    <dependentAssembly>
+      <assemblyIdentity type="win32" name="Microsoft.Windows.Common-Controls"
+        version="6.0.0.0" processorArchitecture="*"
+        publicKeyToken="6595b64144ccf1df" language="*"/>
+    </dependentAssembly>
+  </dependency>
+</assembly>
+"""
+
+
+def pad4(data):
+    return data + b"\0" * (-len(data) % 4)
+
+
+def utf16(text):
+    return text.encode("utf-16-le") + b"\0\0"
+
+
+def version_block(key, value=b"", text=False, children=()):
+    """VS_VERSIONINFO/StringFileInfo/StringTable/String block."""
+    value_length = len(value) // 2 if text else len(value)
+    # the value and children are aligned relative to the block start
+    header = struct.pack("<HHH", 0, value_length, int(text)) + utf16(key)
+    block = pad4(header) + pad4(value) + b"".join(pad4(c) for c in children)
+    return struct.pack("<H", len(block)) + block[2:]
+
+
+def version_info():
+    fixed = struct.pack(
+        "<13I", 0xFEEF04BD, 0x10000, 0x00010002, 0x00030004,
+        0x00010002, 0x00030004, 0x3F, 0, 0x40004, 1, 0, 0, 0,
+    )
+    strings = [
+        version_block(key, utf16(value), True)
+        for key, value in [
+            ("CompanyName", "Checksec Test"),
+            ("ProductName", "checksec resources"),
+            ("FileVersion", "1.2.3.4-dev"),
+        ]
+    ]
+    string_table = version_block("040904B0", text=True, children=strings)
+    string_file_info = version_block(
+        "StringFileInfo", text=True, children=[string_table]
+    )
+    return version_block("VS_VERSION_INFO", fixed, children=[string_file_info])
+
+
+def directory(entries):
+    """IMAGE_RESOURCE_DIRECTORY with (id, offset) entries."""
+    data = struct.pack("<IIHHHH", 0, 0, 0, 0, 0, len(entries))
+    return data + b"".join(struct.pack("<II", *entry) for entry in entries)
+
+
+def resources(section, items):
+    """Lay out a Type/Name/Language tree for (type, data) items."""
+    leaves = [section.add(data) for _, data in items]
+    # root, then per type a name and a language directory
+    root_size = 16 + 8 * len(items)
+    name_offsets = [root_size + i * 48 for i in range(len(items))]
+    data_entries = root_size + len(items) * 48
+    tree = directory([
+        (resource_type, 0x8000_0000 | name_offsets[i])
+        for i, (resource_type, _) in enumerate(items)
+    ])
+    for i in range(len(items)):
+        tree += directory([(1, 0x8000_0000 | (name_offsets[i] + 24))])
+        tree += directory([(LANG_EN_US, data_entries + i * 16)])
+    for rva, (_, data) in zip(leaves, items):
+        tree += struct.pack("<IIII", rva, len(data), 0, 0)
+    return section.add(tree), len(tree)
+
+
+def image():
+    section = minipe.Section()
+    rva, size = resources(
+        section, [(RT_VERSION, version_info()), (RT_MANIFEST, MANIFEST)]
+    )
+    return minipe.build(
+        section.data,
+        {2: (rva, size)},
+        minipe.DLLCHARACTERISTICS_DYNAMIC_BASE
+        | minipe.DLLCHARACTERISTICS_NX_COMPAT,
+        pe32_plus=True,
+    )
+
+
+if __name__ == "__main__":
+    with open("resources.exe.bin", "wb") as fp:
+        fp.write(image())
@@ -0,0 +1,99 @@
+"""Minimal single section PE32/PE32+ writer shared by the generators."""
+import struct
+
+FILE_ALIGNMENT = 0x200
+SECTION_ALIGNMENT = 0x2000
+TEXT_RVA = 0x2000
+
+DLLCHARACTERISTICS_DYNAMIC_BASE = 0x0040
+DLLCHARACTERISTICS_NX_COMPAT = 0x0100
+
+
+def align(value, alignment):
+    return -(-value // alignment) * alignment
+
+
+class Section:
+    """Bump allocator for the contents of the `.text` section."""
+
+    def __init__(self):
+        self.data = b""
+
+    def add(self, data, alignment=8):
+        self.data = self.data.ljust(align(len(self.data), alignment), b"\0")
+        rva = TEXT_RVA + len(self.data)
+        self.data += data
+        return rva
+
+
+def build(text, directories, dll_characteristics, pe32_plus=False,
+          debug_sections=None):
+    """Wrap `text` in a PE image, `directories` maps index to (rva, size).
+
+    `debug_sections` maps names to contents of non-loaded sections appended
+    after `.text` MinGW style: long names go through the COFF string table,
+    which follows a COFF symbol table with a single `main` symbol.
+    """
+    debug_sections = debug_sections or {}
+    text = text.ljust(align(len(text), FILE_ALIGNMENT), b"\0")
+    image_size = TEXT_RVA + align(len(text), SECTION_ALIGNMENT)
+    sections = len(debug_sections) + 1
+
+    dos = b"MZ".ljust(0x3C, b"\0") + struct.pack("<I", 0x80)
+    dos = dos.ljust(0x80, b"\0")
+    if pe32_plus:
+        machine, optional_size, characteristics = 0x8664, 0xF0, 0x2022
+        optional = struct.pack(
+            "<HBBIIIIIQIIHHHHHHIIIIHHQQQQII",
+            0x20B, 14, 0, len(text), 0, 0, 0, TEXT_RVA, 0x1_8000_0000,
+            SECTION_ALIGNMENT, FILE_ALIGNMENT, 6, 0, 0, 0, 6, 0, 0,
+            image_size, FILE_ALIGNMENT, 0, 3, dll_characteristics,
+            0x10_0000, 0x1000, 0x10_0000, 0x1000, 0, 16,
+        )
+    else:
+        machine, optional_size, characteristics = 0x14C, 0xE0, 0x2102
+        optional = struct.pack(
+            "<HBBIIIIIIIIIHHHHHHIIIIHHIIIIII",
+            0x10B, 14, 0, len(text), 0, 0, 0, TEXT_RVA, 0, 0x1000_0000,
+            SECTION_ALIGNMENT, FILE_ALIGNMENT, 6, 0, 0, 0, 6, 0, 0,
+            image_size, FILE_ALIGNMENT, 0, 3, dll_characteristics,
+            0x10_0000, 0x1000, 0x10_0000, 0x1000, 0, 16,
+        )
+    headers_size = len(dos) + 4 + 20 + optional_size + 40 * sections
+    offset = align(headers_size, FILE_ALIGNMENT) + len(text)
+    strings = b""
+    section_headers = b""
+    raw = b""
+    for name, data in debug_sections.items():
+        if len(name) > 8:
+            short = f"/{4 + len(strings)}".encode()
+            strings += name.encode() + b"\0"
+        else:
+            short = name.encode()
+        data = data.ljust(align(len(data), FILE_ALIGNMENT), b"\0")
+        # IMAGE_SCN_CNT_INITIALIZED_DATA | MEM_DISCARDABLE | MEM_READ
+        section_headers += short.ljust(8, b"\0") + struct.pack(
+            "<IIIIIIHHI", len(data), 0, len(data), offset + len(raw),
+            0, 0, 0, 0, 0x4200_0040,
+        )
+        raw += data
+    symbols = b""
+    if debug_sections:
+        # IMAGE_SYMBOL `main`: section 1, IMAGE_SYM_CLASS_EXTERNAL
+        symbols = b"main\0\0\0\0" + struct.pack("<IhHBB", 0, 1, 0x20, 2, 0)
+        symbols += struct.pack("<I", 4 + len(strings)) + strings
+    coff = b"PE\0\0" + struct.pack(
+        "<HHIIIHH", machine, sections, 0,
+        offset + len(raw) if symbols else 0, 1 if symbols else 0,
+        optional_size, characteristics
+    )
+    optional += b"".join(
+        struct.pack("<II", *directories.get(idx, (0, 0))) for idx in range(16)
+    )
+    section = b".text\0\0\0" + struct.pack(
+        "<IIIIIIHHI", len(text), TEXT_RVA, len(text), FILE_ALIGNMENT,
+        0, 0, 0, 0, 0x6000_0020,
+    )
+    headers = dos + coff + optional + section + section_headers
+    headers = headers.ljust(align(len(headers), FILE_ALIGNMENT), b"\0")
+    return headers + text + raw + symbols
@@ -0,0 +1,61 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+
+/// sorted `file` of every binary `checksec --directory --cache --json`
+/// reports
+fn checked_files(directory: &Path, cache: &Path) -> Vec<String> {
+    let output = Command::new(env!("CARGO_BIN_EXE_checksec"))
+        .arg("--directory")
+        .arg(directory)
+        .arg("--cache")
+        .arg(cache)
+        .arg("--json")
+        .output()
+        .expect("checksec failed to run");
+    assert!(output.status.success());
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("invalid json");
+    let mut files: Vec<String> = json
+        .as_array()
+        .expect("binaries missing")
+        .iter()
+        .map(|binary| binary["file"].as_str().expect("file missing").into())
+        .collect();
+    files.sort();
+    files
+}
+
+fn scratch_directory(name: &str) -> PathBuf {
+    let directory =
+        env::temp_dir().join(format!("checksec-{}-{}", name, process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).expect("cannot create scratch directory");
+    directory
+}
+
+#[test]
+fn test_cached_archive_members() {
+    let directory = scratch_directory("cached-archive");
+    let cache = directory.join("results.json.gz");
+    let first = directory.join("first");
+    let second = directory.join("second");
+    for (scan, name) in [(&first, "rel.a"), (&second, "copy.a")] {
+        fs::create_dir(scan).unwrap();
+        fs::copy("./tests/binaries/elf/rel.a", scan.join(name)).unwrap();
+    }
+    let members = |archive: PathBuf| -> Vec<String> {
+        ["rel.o", "rel_cl.o"]
+            .iter()
+            .map(|member| format!("{}\u{2794}{}", archive.display(), member))
+            .collect()
+    };
+
+    assert_eq!(checked_files(&first, &cache), members(first.join("rel.a")));
+    assert!(cache.is_file());
+    // from the cache, for the same archive and an identical copy
+    assert_eq!(checked_files(&first, &cache), members(first.join("rel.a")));
+    assert_eq!(checked_files(&second, &cache), members(second.join("copy.a")));
+    fs::remove_dir_all(&directory).unwrap();
+}
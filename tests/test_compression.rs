@@ -1,4 +1,4 @@
-use checksec::{elf, shared, checksec_core, BinResults, compression::{compress, decompress}};
+use checksec::{checksec_core, BinResults, compression::{compress, decompress}};
 mod utils;
 use utils::file_to_buf;
 
@@ -1,4 +1,6 @@
-use checksec::{elf, shared, checksec_core, BinResults};
+#![allow(clippy::bool_assert_comparison)]
+#![allow(non_snake_case)]
+use checksec::{debuginfo::Stripped, disassembly::StackProbe, elf::{self, CanarySource}, functions::{Function, Functions}, kmod::Taint, language::Language, provenance, sanitizer::Sanitizer, shared, checksec_core, BinResults};
 mod utils;
 use utils::file_to_buf;
 
@@ -319,6 +321,491 @@ fn test_no_clang_safestack(){
     }
 }
 
+#[test]
+fn test_truncated_note(){
+    // a build-id note whose name runs past the end of the file, goblin
+    // 0.10.0 returns the same error from its note iterator forever
+    let mut buf = file_to_buf("./tests/binaries/elf/all".into());
+    let elf = goblin::elf::Elf::parse(&buf).expect("valid ELF");
+    let note = elf.section_headers.iter()
+        .find(|sh| elf.shdr_strtab.get_at(sh.sh_name) == Some(".note.gnu.build-id"))
+        .expect("build-id note missing")
+        .sh_offset as usize;
+    drop(elf);
+    buf[note..note + 4].copy_from_slice(&0x00ff_ffff_u32.to_le_bytes());
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let elf = goblin::elf::Elf::parse(&buf).expect("valid ELF");
+        tx.send(provenance::build_id(&elf, &buf)).unwrap();
+    });
+    let build_id = rx.recv_timeout(std::time::Duration::from_secs(10))
+        .expect("note iteration did not stop");
+    assert_eq!(build_id, None);
+}
+
+#[test]
+fn test_build_provenance(){
+    let buf = file_to_buf("./tests/binaries/elf/all".into());
+    if let Ok(BinResults::Elf(elf_result)) = checksec_core(&buf){
+        assert_eq!(elf_result.comment, vec!["GCC: (Ubuntu 13.3.0-6ubuntu2~24.04) 13.3.0"]);
+        assert_eq!(elf_result.build_id.as_deref(), Some("18f64b74cb5ee1a63ced387b0c9abeacab321626"));
+        assert!(elf_result.package.is_none());
+        assert!(elf_result.command_line.is_empty());
+        assert!(elf_result.annobin.is_none());
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_package_note_and_gcc_switches(){
+    let buf = file_to_buf("./tests/binaries/elf/provenance".into());
+    if let Ok(BinResults::Elf(elf_result)) = checksec_core(&buf){
+        let package = elf_result.package.expect("missing package note");
+        assert_eq!(package.package_type.as_deref(), Some("deb"));
+        assert_eq!(package.name.as_deref(), Some("provenance"));
+        assert_eq!(package.version.as_deref(), Some("1.0-1"));
+        assert_eq!(package.architecture.as_deref(), Some("amd64"));
+        assert_eq!(elf_result.command_line.len(), 1);
+        assert!(elf_result.command_line[0].contains("-fstack-protector-strong"));
+        assert!(elf_result.comment[0].starts_with("GCC: "));
+        assert_eq!(elf_result.build_id.map(|id| id.len()), Some(40));
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_annobin_overrides_heuristics(){
+    let buf = file_to_buf("./tests/binaries/elf/provenance".into());
+    if let Ok(BinResults::Elf(elf_result)) = checksec_core(&buf){
+        let annobin = elf_result.annobin.expect("missing annobin notes");
+        assert_eq!(annobin.tools, vec!["gcc 12.2.0"]);
+        assert_eq!(annobin.stack_protector, Some(provenance::StackProtector::Strong));
+        assert_eq!(annobin.fortify, Some(2));
+        assert_eq!(annobin.cf_protection, Some(provenance::CfProtection::Full));
+        assert_eq!(annobin.stack_clash, Some(true));
+        // main() needs no canary and calls nothing fortifiable, so only
+        // annobin knows these were enabled
+        assert_eq!(elf_result.fortifiable, 0);
+        assert!(elf_result.canary);
+        assert_eq!(elf_result.fortify, elf::Fortify::Full);
+        assert!(elf_result.stack_clash_protection);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_language_c(){
+    let buf = file_to_buf("./tests/binaries/elf/all".into());
+    if let Ok(BinResults::Elf(elf_result)) = checksec_core(&buf){
+        assert_eq!(elf_result.language, Language::C);
+        assert!(elf_result.native_code);
+        assert!(elf_result.go_buildinfo.is_none());
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_language_rust(){
+    let buf = file_to_buf("./tests/binaries/elf/rust_hello".into());
+    if let Ok(BinResults::Elf(elf_result)) = checksec_core(&buf){
+        assert_eq!(elf_result.language, Language::Rust);
+        // std imports fortifiable libc functions, but has no C code
+        assert!(elf_result.fortifiable > 0);
+        assert!(!elf_result.native_code);
+        assert!(elf_result.stack_clash_applies());
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_language_go(){
+    let buf = file_to_buf("./tests/binaries/elf/go_static".into());
+    if let Ok(BinResults::Elf(elf_result)) = checksec_core(&buf){
+        assert_eq!(elf_result.language, Language::Go);
+        assert!(!elf_result.native_code);
+        assert!(!elf_result.stack_clash_applies());
+        let go = elf_result.go_buildinfo.expect("missing Go build info");
+        assert_eq!(go.version, "go1.21.5");
+        assert_eq!(go.path.as_deref(), Some("example.com/hello"));
+        assert_eq!(go.main.as_ref().map(|m| m.version.as_str()), Some("(devel)"));
+        assert_eq!(go.deps.len(), 1);
+        assert_eq!(go.deps[0].path, "golang.org/x/sys");
+        assert_eq!(go.deps[0].version, "v0.15.0");
+        assert_eq!(go.build_mode(), Some("exe"));
+        assert!(!go.cgo_enabled());
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_language_go_cgo(){
+    let buf = file_to_buf("./tests/binaries/elf/go_cgo".into());
+    if let Ok(BinResults::Elf(elf_result)) = checksec_core(&buf){
+        assert_eq!(elf_result.language, Language::Go);
+        assert!(elf_result.native_code);
+        let go = elf_result.go_buildinfo.expect("missing Go build info");
+        assert_eq!(go.build_mode(), Some("pie"));
+        assert!(go.cgo_enabled());
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_sanitizers(){
+    let buf = file_to_buf("./tests/binaries/elf/asan_ubsan".into());
+    if let Ok(BinResults::Elf(elf_result)) = checksec_core(&buf){
+        assert_eq!(*elf_result.sanitizers, vec![Sanitizer::ASan, Sanitizer::UBSan]);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_gcov(){
+    let buf = file_to_buf("./tests/binaries/elf/gcov".into());
+    if let Ok(BinResults::Elf(elf_result)) = checksec_core(&buf){
+        assert_eq!(*elf_result.sanitizers, vec![Sanitizer::Gcov]);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_no_sanitizers(){
+    let buf = file_to_buf("./tests/binaries/elf/all".into());
+    if let Ok(BinResults::Elf(elf_result)) = checksec_core(&buf){
+        assert!(elf_result.sanitizers.is_empty());
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_debug_info(){
+    let buf = file_to_buf("./tests/binaries/elf/debuginfo".into());
+    if let Ok(BinResults::Elf(elf_result)) = checksec_core(&buf){
+        let debug_info = elf_result.debug_info;
+        assert_eq!(debug_info.stripped, Stripped::None);
+        assert!(debug_info.symbols);
+        assert!(debug_info.debug_sections.contains(&".debug_info".to_string()));
+        // zlib compressed with SHF_COMPRESSED
+        assert_eq!(debug_info.dwarf_version, Some(4));
+        assert!(debug_info.debug_file.is_none());
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_debug_link(){
+    let buf = file_to_buf("./tests/binaries/elf/debuglink".into());
+    if let Ok(BinResults::Elf(elf_result)) = checksec_core(&buf){
+        let debug_info = elf_result.debug_info;
+        assert_eq!(debug_info.stripped, Stripped::Full);
+        assert!(debug_info.debug_sections.is_empty());
+        assert_eq!(debug_info.debug_file.as_deref(), Some("debuglink.debug"));
+        assert_eq!(debug_info.alt_debug_file.as_deref(), Some("common.debug"));
+        assert_eq!(debug_info.debug_id, elf_result.build_id);
+        assert!(debug_info.has_separate_debug());
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_debug_info_stripped(){
+    let buf = file_to_buf("./tests/binaries/elf/go_cgo".into());
+    if let Ok(BinResults::Elf(elf_result)) = checksec_core(&buf){
+        assert_eq!(elf_result.debug_info.stripped, Stripped::Partial);
+        assert!(elf_result.debug_info.dwarf_version.is_none());
+    }
+    else {
+        panic!("Checksec failed");
+    }
+    let buf = file_to_buf("./tests/binaries/elf/all".into());
+    if let Ok(BinResults::Elf(elf_result)) = checksec_core(&buf){
+        assert_eq!(elf_result.debug_info.stripped, Stripped::Full);
+        assert!(!elf_result.debug_info.symbols);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_speculation_mitigations(){
+    let buf = file_to_buf("./tests/binaries/elf/speculation".into());
+    if let Ok(BinResults::Elf(elf_result)) = checksec_core(&buf){
+        let speculation = elf_result.speculation.expect("x86-64 code");
+        assert_eq!(speculation.retpoline_thunks, 1);
+        assert_eq!(speculation.retpoline_branches, 1);
+        assert_eq!(speculation.return_thunk_branches, 4);
+        assert_eq!(speculation.lfences, 3);
+        // the thunks themselves end in ret; int3
+        assert_eq!(speculation.sls_hardened, 2);
+        // PLT stubs and crt code are not rebuilt with retpolines
+        assert!(speculation.indirect_branches > 0);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+    let buf = file_to_buf("./tests/binaries/elf/all".into());
+    if let Ok(BinResults::Elf(elf_result)) = checksec_core(&buf){
+        let speculation = elf_result.speculation.expect("x86-64 code");
+        assert_eq!(speculation.retpoline_thunks, 0);
+        assert_eq!(speculation.retpoline_branches, 0);
+        assert_eq!(speculation.return_thunk_branches, 0);
+        assert_eq!(speculation.lfences, 0);
+        assert_eq!(speculation.sls_hardened, 0);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_stack_clash_aarch64(){
+    let buf = file_to_buf("./tests/binaries/elf/probe_aarch64".into());
+    if let Ok(BinResults::Elf(elf_result)) = checksec_core(&buf){
+        assert!(elf_result.stack_clash_protection);
+        // x86 only
+        assert!(elf_result.speculation.is_none());
+    }
+    else {
+        panic!("Checksec failed");
+    }
+    let buf = file_to_buf("./tests/binaries/elf/noprobe_aarch64".into());
+    if let Ok(BinResults::Elf(elf_result)) = checksec_core(&buf){
+        assert!(!elf_result.stack_clash_protection);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_stack_clash_riscv64(){
+    let buf = file_to_buf("./tests/binaries/elf/probe_riscv64".into());
+    if let Ok(BinResults::Elf(elf_result)) = checksec_core(&buf){
+        assert!(elf_result.stack_clash_protection);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_functions(){
+    let buf = file_to_buf("./tests/binaries/elf/speculation".into());
+    let elf = goblin::elf::Elf::parse(&buf).expect("valid ELF");
+    let functions = Functions::from_elf(&elf, &buf);
+    // main and lookup from .symtab, the PLT from .eh_frame
+    assert!(functions.contains(&Function { start: 0x1060, end: 0x10b0 }));
+    assert!(functions.contains(&Function { start: 0x11c0, end: 0x11cf }));
+    assert!(functions.iter().any(|f| f.start == 0x1020));
+    assert!(functions.windows(2).all(|w| w[0].end <= w[1].start));
+    let code = functions.code(&buf[0x1060..0x10d0], 0x1060);
+    assert_eq!(code[0], (0x1060, &buf[0x1060..0x10b0]));
+    assert_eq!(code[1].0, 0x10b0);
+}
+
+#[test]
+fn test_functions_without_section_headers(){
+    let buf = file_to_buf("./tests/binaries/elf/all".into());
+    let elf = goblin::elf::Elf::parse(&buf).expect("valid ELF");
+    let from_eh_frame = Functions::from_elf(&elf, &buf);
+    // same FDEs, found through PT_GNU_EH_FRAME
+    let buf = file_to_buf("./tests/binaries/elf/noshdr".into());
+    let elf = goblin::elf::Elf::parse(&buf).expect("valid ELF");
+    let from_eh_frame_hdr = Functions::from_elf(&elf, &buf);
+    assert!(!from_eh_frame.is_empty());
+    assert_eq!(
+        from_eh_frame.iter().map(|f| f.start).collect::<Vec<_>>(),
+        from_eh_frame_hdr.iter().map(|f| f.start).collect::<Vec<_>>()
+    );
+    if let Ok(BinResults::Elf(elf_result)) = checksec_core(&buf){
+        // code is taken from the executable segment instead
+        assert!(elf_result.speculation.expect("x86-64 code").sls_candidates > 0);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_stack_probe(){
+    for (file, probe) in [
+        ("probe_loop", StackProbe::Loop),
+        ("probe_unrolled", StackProbe::Unrolled),
+        // a single page frame stored to, built without stack clash probes
+        ("noprobe_unrolled", StackProbe::None),
+        ("all", StackProbe::None),
+    ] {
+        let buf = file_to_buf(format!("./tests/binaries/elf/{file}"));
+        if let Ok(BinResults::Elf(elf_result)) = checksec_core(&buf){
+            assert_eq!(elf_result.stack_probe, probe, "{file}");
+            assert_eq!(elf_result.stack_clash_protection, probe != StackProbe::None);
+        }
+        else {
+            panic!("Checksec failed");
+        }
+    }
+}
+
+#[test]
+fn test_canary_from_code(){
+    for file in ["canary_static", "canary_static32"] {
+        let buf = file_to_buf(format!("./tests/binaries/elf/{file}"));
+        if let Ok(BinResults::Elf(elf_result)) = checksec_core(&buf){
+            // stripped and static, no __stack_chk_fail to find
+            assert!(elf_result.canary, "{file}");
+            assert_eq!(elf_result.canary_source, Some(CanarySource::Code));
+        }
+        else {
+            panic!("Checksec failed");
+        }
+    }
+    let buf = file_to_buf("./tests/binaries/elf/all".into());
+    if let Ok(BinResults::Elf(elf_result)) = checksec_core(&buf){
+        assert_eq!(elf_result.canary_source, Some(CanarySource::Symbol));
+    }
+    else {
+        panic!("Checksec failed");
+    }
+    let buf = file_to_buf("./tests/binaries/elf/nolibc".into());
+    if let Ok(BinResults::Elf(elf_result)) = checksec_core(&buf){
+        assert!(!elf_result.canary);
+        assert_eq!(elf_result.canary_source, None);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_image_from_memory(){
+    let buf = file_to_buf("./tests/binaries/elf/all".into());
+    let elf = goblin::elf::Elf::parse(&buf).expect("valid ELF");
+    // load the PIE like ld.so does, relocating .dynamic entries in place
+    let base = 0x5555_5555_4000_u64;
+    let mut memory = vec![0u8; 0x10000];
+    for ph in elf.program_headers.iter().filter(|ph| ph.p_type == goblin::elf::program_header::PT_LOAD) {
+        let (vaddr, offset, size) = (ph.p_vaddr as usize, ph.p_offset as usize, ph.p_filesz as usize);
+        memory[vaddr..vaddr + size].copy_from_slice(&buf[offset..offset + size]);
+    }
+    let dynamic = elf.program_headers.iter().find(|ph| ph.p_type == goblin::elf::program_header::PT_DYNAMIC).expect("PT_DYNAMIC");
+    for (i, dyn_entry) in elf.dynamic.as_ref().expect("dynamic").dyns.iter().enumerate() {
+        if [goblin::elf::dynamic::DT_STRTAB, goblin::elf::dynamic::DT_SYMTAB, goblin::elf::dynamic::DT_GNU_HASH].contains(&dyn_entry.d_tag) {
+            let at = dynamic.p_vaddr as usize + i * 16 + 8;
+            memory[at..at + 8].copy_from_slice(&(dyn_entry.d_val + base).to_le_bytes());
+        }
+    }
+    let image = elf::image_from_memory(base, |address, out| {
+        let start = (address - base) as usize;
+        out.copy_from_slice(memory.get(start..start + out.len()).ok_or(std::io::ErrorKind::UnexpectedEof)?);
+        Ok(())
+    }).expect("image rebuilt");
+    let loaded = goblin::elf::Elf::parse(&image).expect("valid ELF");
+    assert!(loaded.section_headers.is_empty());
+    let loaded = elf::CheckSecResults::parse(&loaded, &image);
+    if let Ok(BinResults::Elf(elf_result)) = checksec_core(&buf){
+        assert!(loaded.canary);
+        assert_eq!(loaded.dynlibs, elf_result.dynlibs);
+        assert_eq!(elf_result.differences(&loaded), vec![]);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+    let buf = file_to_buf("./tests/binaries/elf/none".into());
+    if let Ok(BinResults::Elf(elf_result)) = checksec_core(&buf){
+        let differences = elf_result.differences(&loaded);
+        assert!(differences.iter().any(|d| d.check == "canary" && d.file == "false" && d.memory == "true"));
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_image_from_memory_bad_header(){
+    let mut buf = file_to_buf("./tests/binaries/elf/all".into());
+    // e_phentsize, which sizes the program header read
+    buf[0x36..0x38].copy_from_slice(&0xffff_u16.to_le_bytes());
+    // memory of any size, remembering the largest read
+    let largest = std::cell::Cell::new(0);
+    let result = elf::image_from_memory(0, |address, out| {
+        largest.set(largest.get().max(out.len()));
+        let start = (address as usize).min(buf.len());
+        let available = &buf[start..(start + out.len()).min(buf.len())];
+        out[..available.len()].copy_from_slice(available);
+        Ok(())
+    });
+    assert!(result.is_err());
+    assert!(largest.get() <= 0x1000);
+}
+
+#[test]
+fn test_kernel_module() {
+    let buf = file_to_buf("./tests/binaries/elf/kmod.ko".into());
+    if let Ok(BinResults::Elf(elf_result)) = checksec_core(&buf){
+        let module = elf_result.kernel_module.expect("kernel module");
+        assert_eq!(module.name.as_deref(), Some("kmod"));
+        assert_eq!(module.vermagic.as_deref(), Some("6.1.0-18-amd64 SMP preempt mod_unload modversions"));
+        assert_eq!(module.license.as_deref(), Some("GPL"));
+        assert!(module.retpoline);
+        assert!(module.modversions);
+        assert!(module.cfi);
+        assert!(module.ibt);
+        let signature = module.signature.expect("signature");
+        assert_eq!(signature.format, "PKCS#7");
+        assert_eq!(signature.algorithm.as_deref(), Some("RSA"));
+        assert_eq!(signature.hash.as_deref(), Some("SHA-256"));
+        assert!(module.taints.is_empty());
+    }
+    else {
+        panic!("Checksec failed");
+    }
+    let buf = file_to_buf("./tests/binaries/elf/kmod_proprietary.ko".into());
+    if let Ok(BinResults::Elf(elf_result)) = checksec_core(&buf){
+        let module = elf_result.kernel_module.expect("kernel module");
+        assert!(!module.retpoline);
+        assert!(!module.cfi);
+        assert!(!module.ibt);
+        assert_eq!(module.signature, None);
+        assert_eq!(module.taints, vec![Taint::Proprietary, Taint::OutOfTree, Taint::Unsigned]);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+    // plain relocatable objects are no modules
+    let buf = file_to_buf("./tests/binaries/elf/rel.o".into());
+    if let Ok(BinResults::Elf(elf_result)) = checksec_core(&buf){
+        assert_eq!(elf_result.kernel_module, None);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
 //TODO: Add further testing for stack clash?
 
 
@@ -0,0 +1,62 @@
+use checksec::kernel::{CheckSecResults, KernelConfig, Sysctls};
+use checksec::provenance::StackProtector;
+use std::path::Path;
+
+#[test]
+fn test_config() {
+    let path = Path::new("./tests/binaries/kernel/config-6.18");
+    let results = CheckSecResults::from_config(path).expect("config failed");
+    assert_eq!(results.version.as_deref(), Some("6.18.44"));
+    assert_eq!(results.config.as_deref(), Some(path));
+    assert_eq!(results.kaslr, Some(true));
+    assert_eq!(results.stack_protector, Some(StackProtector::Strong));
+    assert_eq!(results.strict_kernel_rwx, Some(true));
+    assert_eq!(results.strict_module_rwx, Some(false));
+    assert_eq!(results.hardened_usercopy, Some(true));
+    assert_eq!(results.fortify_source, Some(true));
+    assert_eq!(results.vmap_stack, Some(true));
+    assert_eq!(results.slab_freelist_random, Some(true));
+    assert_eq!(results.slab_freelist_hardened, Some(true));
+    assert_eq!(results.strict_devmem, Some(false));
+    assert_eq!(results.pti, Some(true));
+    // runtime only
+    assert_eq!(results.smap, None);
+    assert_eq!(results.smep, None);
+    assert_eq!(results.sysctls, Sysctls::default());
+}
+
+#[test]
+fn test_config_legacy_gz() {
+    let path = Path::new("./tests/binaries/kernel/config-4.9.gz");
+    let config = KernelConfig::from_file(path).expect("config failed");
+    assert_eq!(config.get("CC_STACKPROTECTOR_REGULAR"), Some("y"));
+    assert_eq!(config.get("RANDOMIZE_BASE"), None);
+    let results = CheckSecResults::from_config(path).expect("config failed");
+    assert_eq!(results.version.as_deref(), Some("4.9.0"));
+    assert_eq!(results.kaslr, Some(false));
+    assert_eq!(results.stack_protector, Some(StackProtector::Basic));
+    assert_eq!(results.strict_kernel_rwx, Some(true));
+    assert_eq!(results.strict_module_rwx, Some(true));
+    assert_eq!(results.hardened_usercopy, Some(false));
+    assert_eq!(results.pti, Some(true));
+    assert_eq!(results.module_sig_force, Some(true));
+}
+
+#[test]
+fn test_sysctls() {
+    let sysctls = Sysctls::read(Path::new("./tests/binaries/kernel/sys"));
+    assert_eq!(sysctls.kptr_restrict, Some(2));
+    assert_eq!(sysctls.dmesg_restrict, Some(1));
+    assert_eq!(sysctls.ptrace_scope, Some(0));
+    assert_eq!(sysctls.unprivileged_bpf_disabled, Some(2));
+    assert_eq!(sysctls.kexec_load_disabled, Some(1));
+    assert_eq!(sysctls.perf_event_paranoid, Some(-1));
+    assert_eq!(sysctls.randomize_va_space, Some(2));
+    assert_eq!(sysctls.protected_symlinks, Some(1));
+    assert_eq!(sysctls.protected_hardlinks, Some(1));
+    assert_eq!(sysctls.mmap_min_addr, Some(65536));
+    assert_eq!(
+        Sysctls::read(Path::new("./tests/binaries/kernel/missing")),
+        Sysctls::default()
+    );
+}
@@ -1,4 +1,4 @@
-use checksec::{elf, shared, checksec_core, shared::{BinResults, compress_results, decompress_results}};
+use checksec::{checksec_core, shared::{BinResults, compress_results, decompress_results}};
 mod utils;
 use utils::file_to_buf;
 
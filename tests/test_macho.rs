@@ -1,4 +1,9 @@
-use checksec::{macho, shared, checksec_core, BinResults};
+#![allow(clippy::bool_assert_comparison)]
+#![allow(non_snake_case)]
+use checksec::{shared, checksec_core, BinResults};
+use checksec::debuginfo::Stripped;
+use checksec::functions::{Function, Functions};
+use checksec::sanitizer::{Sanitizer, VecSanitizer};
 mod utils;
 use utils::file_to_buf;
 
@@ -224,15 +229,52 @@ fn test_rpath(){
     }
 }
 
+#[test]
+fn test_no_sanitizers(){
+    let buf = file_to_buf("./tests/binaries/Mach-O/basic".into());
+    if let Ok(BinResults::Macho(macho_result)) = checksec_core(&buf){
+        assert!(macho_result.sanitizers.is_empty());
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
 
+#[test]
+fn test_sanitizer_symbols(){
+    // Mach-O symbols carry an extra leading underscore
+    let sanitizers = VecSanitizer::detect(
+        ["___asan_init", "___ubsan_handle_add_overflow", "_LLVMFuzzerTestOneInput", "_main"],
+        ["@rpath/libclang_rt.tsan_osx_dynamic.dylib"],
+    );
+    assert_eq!(
+        *sanitizers,
+        vec![Sanitizer::ASan, Sanitizer::TSan, Sanitizer::UBSan, Sanitizer::LibFuzzer]
+    );
+}
 
+#[test]
+fn test_debug_info(){
+    let buf = file_to_buf("./tests/binaries/Mach-O/basic".into());
+    if let Ok(BinResults::Macho(macho_result)) = checksec_core(&buf){
+        let debug_info = macho_result.debug_info;
+        assert_eq!(debug_info.stripped, Stripped::Full);
+        assert!(!debug_info.stabs);
+        assert!(debug_info.debug_sections.is_empty());
+        assert_eq!(debug_info.debug_id.as_deref(), Some("590D2C0D-68E4-38E4-9FCC-AD71F8EAD734"));
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
 
-
-
-
-
-
-
-
-
-
+#[test]
+fn test_functions(){
+    let buf = file_to_buf("./tests/binaries/Mach-O/basic".into());
+    let macho = goblin::mach::MachO::parse(&buf, 0).expect("valid Mach-O");
+    let functions = Functions::from_macho(&macho, &buf);
+    // LC_FUNCTION_STARTS only records starts
+    assert_eq!(functions.len(), 2);
+    assert_eq!(functions[0], Function { start: 0x1_0000_0460, end: 0x1_0000_04d8 });
+    assert_eq!(functions[1].start, 0x1_0000_04d8);
+}
@@ -1,4 +1,14 @@
-use checksec::{pe, shared, checksec_core, BinResults};
+#![allow(clippy::bool_assert_comparison)]
+#![allow(non_snake_case)]
+use checksec::{pe, checksec_core, BinResults};
+use checksec::dotnet::StrongName;
+use checksec::resource::{ExecutionLevel, Manifest, Resources};
+use checksec::debuginfo::Stripped;
+use checksec::disassembly::StackProbe;
+use checksec::functions::{Function, Functions};
+use checksec::sanitizer::Sanitizer;
+use checksec::pe::{ImportPolicy, LoadConfig, Properties};
+use goblin::pe::PE;
 mod utils;
 use utils::file_to_buf;
 
@@ -244,13 +254,48 @@ fn test_no_authenticode(){
     }
 }
 
-// TODO: Find a PE that has .NET
+#[test]
+fn test_dotnet_signed(){
+    let buf = file_to_buf("./tests/binaries/pe/dotnet-signed.dll.bin".into());
+    if let Ok(BinResults::Pe(pe_result)) = checksec_core(&buf){
+        assert!(pe_result.dotnet);
+        let clr = pe_result.clr.expect("CLI header missing");
+        assert_eq!(clr.runtime_version, "v4.0.30319");
+        assert_eq!(clr.target_framework.as_deref(), Some(".NETCoreApp,Version=v8.0"));
+        assert!(clr.il_only);
+        assert!(!clr.required_32bit);
+        assert!(!clr.preferred_32bit);
+        assert_eq!(clr.strong_name, StrongName::Present);
+        assert!(!clr.ready_to_run);
+        assert!(clr.native_code_hardened);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_dotnet_mixed_delay_signed(){
+    let buf = file_to_buf("./tests/binaries/pe/dotnet-mixed-delaysigned.dll.bin".into());
+    if let Ok(BinResults::Pe(pe_result)) = checksec_core(&buf){
+        let clr = pe_result.clr.expect("CLI header missing");
+        assert!(!clr.il_only);
+        assert!(clr.required_32bit);
+        assert_eq!(clr.strong_name, StrongName::DelaySigned);
+        assert!(clr.ready_to_run);
+        assert!(!clr.native_code_hardened);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
 
 #[test]
 fn test_no_dotnet(){
     let buf = file_to_buf("./tests/binaries/pe/pegoat.exe".into());
     if let Ok(BinResults::Pe(pe_result)) = checksec_core(&buf){
         assert_eq!(pe_result.dotnet, false);
+        assert!(pe_result.clr.is_none());
     }
     else {
         panic!("Checksec failed");
@@ -282,3 +327,475 @@ fn test_not_cet_compat(){
 
 
 
+#[test]
+fn test_is_cet_strict(){
+    let buf = file_to_buf("./tests/binaries/pe/debug_directories-msvc.exe.bin".into());
+    if let Ok(BinResults::Pe(pe_result)) = checksec_core(&buf){
+        assert!(pe_result.cet);
+        assert!(pe_result.cet_strict);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_not_cet_strict(){
+    let buf = file_to_buf("./tests/binaries/pe/pegoat-cetcompat.exe".into());
+    if let Ok(BinResults::Pe(pe_result)) = checksec_core(&buf){
+        assert!(!pe_result.cet_strict);
+        assert!(!pe_result.cet_ip_validation_relaxed);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_no_eh_continuation(){
+    let buf = file_to_buf("./tests/binaries/pe/pegoat.exe".into());
+    if let Ok(BinResults::Pe(pe_result)) = checksec_core(&buf){
+        assert!(!pe_result.eh_continuation);
+        assert_eq!(pe_result.eh_continuation_count, 0);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_no_dynamic_relocations(){
+    let buf = file_to_buf("./tests/binaries/pe/pegoat-yes-cfg.exe".into());
+    if let Ok(BinResults::Pe(pe_result)) = checksec_core(&buf){
+        assert!(!pe_result.retpoline);
+        assert!(!pe_result.arm64x);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_eh_continuation(){
+    // a PE32+ load config listing three EH continuation targets
+    let mut bytes = vec![0_u8; 0x130];
+    bytes[..4].copy_from_slice(&0x130_u32.to_le_bytes());
+    bytes[0x110..0x118].copy_from_slice(&3_u64.to_le_bytes());
+    let load_config = LoadConfig::parse(&bytes, true).expect("parse failed");
+    assert!(!load_config.has_eh_continuation());
+    // IMAGE_GUARD_EH_CONTINUATION_TABLE_PRESENT
+    bytes[0x90..0x94].copy_from_slice(&0x0040_0000_u32.to_le_bytes());
+    let load_config = LoadConfig::parse(&bytes, true).expect("parse failed");
+    assert!(load_config.has_eh_continuation());
+    assert_eq!(load_config.eh_continuation_count(), 3);
+}
+
+/// file offset of `rva` in a PE
+fn rva_to_offset(pe: &PE, rva: u32) -> usize {
+    let section = pe.sections.iter()
+        .find(|section| {
+            (section.virtual_address..section.virtual_address + section.virtual_size).contains(&rva)
+        })
+        .expect("rva outside of sections");
+    (rva - section.virtual_address + section.pointer_to_raw_data) as usize
+}
+
+/// append a version 1 `IMAGE_DYNAMIC_RELOCATION_TABLE` with one entry per
+/// symbol to a PE32+ and point its load config at it
+fn with_dynamic_relocations(path: &str, symbols: &[u64]) -> Vec<u8> {
+    let mut buf = file_to_buf(path.into());
+    let pe = PE::parse(&buf).expect("PE parse failed");
+    let optional_header = pe.header.optional_header.expect("optional header");
+    let load_config = optional_header.data_directories.get_load_config_table()
+        .expect("load config missing");
+    let load_config = rva_to_offset(&pe, load_config.virtual_address);
+    let table_offset = buf.len() as u32 - pe.sections[0].pointer_to_raw_data;
+    drop(pe);
+    // DynamicValueRelocTableOffset and DynamicValueRelocTableSection
+    buf[load_config + 0xe0..load_config + 0xe4].copy_from_slice(&table_offset.to_le_bytes());
+    buf[load_config + 0xe4..load_config + 0xe6].copy_from_slice(&1_u16.to_le_bytes());
+    buf.extend_from_slice(&1_u32.to_le_bytes());
+    buf.extend_from_slice(&(symbols.len() as u32 * 12).to_le_bytes());
+    for symbol in symbols {
+        // Symbol and an empty BaseRelocSize
+        buf.extend_from_slice(&symbol.to_le_bytes());
+        buf.extend_from_slice(&0_u32.to_le_bytes());
+    }
+    buf
+}
+
+#[test]
+fn test_retpoline_relocations(){
+    // IMAGE_DYNAMIC_RELOCATION_GUARD_RF_PROLOGUE, ..._INDIR_CONTROL_TRANSFER
+    let buf = with_dynamic_relocations("./tests/binaries/pe/pegoat-yes-cfg.exe", &[1, 4]);
+    if let Ok(BinResults::Pe(pe_result)) = checksec_core(&buf){
+        assert!(pe_result.retpoline);
+        assert!(!pe_result.arm64x);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_arm64x_relocations(){
+    // IMAGE_DYNAMIC_RELOCATION_ARM64X
+    let buf = with_dynamic_relocations("./tests/binaries/pe/pegoat-yes-cfg.exe", &[6]);
+    if let Ok(BinResults::Pe(pe_result)) = checksec_core(&buf){
+        assert!(!pe_result.retpoline);
+        assert!(pe_result.arm64x);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_forward_cfi(){
+    let mut buf = file_to_buf("./tests/binaries/pe/debug_directories-msvc.exe.bin".into());
+    let pe = PE::parse(&buf).expect("PE parse failed");
+    let optional_header = pe.header.optional_header.expect("optional header");
+    let debug = optional_header.data_directories.get_debug_table()
+        .expect("debug directory missing");
+    let debug_offset = rva_to_offset(&pe, debug.virtual_address);
+    drop(pe);
+    // IMAGE_DEBUG_DIRECTORY entries, find IMAGE_DEBUG_TYPE_EX_DLLCHARACTERISTICS
+    let entry = (debug_offset..debug_offset + debug.size as usize)
+        .step_by(28)
+        .find(|entry| buf[entry + 12..entry + 16] == 20_u32.to_le_bytes())
+        .expect("no extended DLL characteristics");
+    let data = u32::from_le_bytes(buf[entry + 24..entry + 28].try_into().unwrap()) as usize;
+    // IMAGE_DLLCHARACTERISTICS_EX_FORWARD_CFI_COMPAT
+    buf[data] |= 0x40;
+    if let Ok(BinResults::Pe(pe_result)) = checksec_core(&buf){
+        assert!(pe_result.forward_cfi);
+        assert!(pe_result.cet_strict);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_load_config_fields(){
+    let buf = file_to_buf("./tests/binaries/pe/pegoat-yes-cfg.exe".into());
+    let pe = PE::parse(&buf).expect("PE parse failed");
+    let load_config = pe.load_config(&buf).expect("load config missing");
+    assert!(load_config.security_cookie.is_some());
+    assert!(load_config.guard_flags.is_some());
+    assert_ne!(load_config.guard_cf_function_count, Some(0));
+}
+
+#[test]
+fn test_load_config_honours_size(){
+    // a PE32+ load config that ends right after `security_cookie`
+    let size: u32 = 0x60;
+    let mut bytes = vec![0xff_u8; 0x200];
+    bytes[..4].copy_from_slice(&size.to_le_bytes());
+    let load_config = LoadConfig::parse(&bytes, true).expect("parse failed");
+    assert_eq!(load_config.size, size);
+    assert_eq!(load_config.security_cookie, Some(u64::MAX));
+    assert_eq!(load_config.se_handler_table, None);
+    assert_eq!(load_config.guard_flags, None);
+    assert_eq!(load_config.guard_eh_continuation_count, None);
+    assert!(!load_config.has_rfg());
+}
+
+#[test]
+fn test_dangerous_imports(){
+    let buf = file_to_buf("./tests/binaries/pe/imports.exe.bin".into());
+    if let Ok(BinResults::Pe(pe_result)) = checksec_core(&buf){
+        assert_eq!(pe_result.dangerous_imports, ["StrCpyW", "VirtualAlloc", "strcpy"]);
+        assert_eq!(pe_result.ordinal_imports, 2);
+        assert_eq!(pe_result.secure_crt_imports, 2);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_secure_crt_imports(){
+    // `_s` imports from ucrtbase.dll and an api-ms-win-crt-* delay import
+    // count, those of vendor.dll and vendor_delay.dll do not
+    let buf = file_to_buf("./tests/binaries/pe/secure_crt.exe.bin".into());
+    if let Ok(BinResults::Pe(pe_result)) = checksec_core(&buf){
+        assert_eq!(pe_result.secure_crt_imports, 2);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_no_dangerous_imports(){
+    let buf = file_to_buf("./tests/binaries/pe/pegoat.exe".into());
+    if let Ok(BinResults::Pe(pe_result)) = checksec_core(&buf){
+        assert!(pe_result.dangerous_imports.is_empty());
+        assert_eq!(pe_result.ordinal_imports, 0);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_debug_info_mingw(){
+    let buf = file_to_buf("./tests/binaries/pe/debuginfo-mingw.exe.bin".into());
+    if let Ok(BinResults::Pe(pe_result)) = checksec_core(&buf){
+        let debug_info = pe_result.debug_info;
+        assert_eq!(debug_info.stripped, Stripped::None);
+        assert!(debug_info.symbols);
+        assert_eq!(debug_info.debug_sections, vec![".debug_info", ".debug_abbrev"]);
+        assert_eq!(debug_info.dwarf_version, Some(4));
+        assert!(!debug_info.has_separate_debug());
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_debug_info_pdb(){
+    let buf = file_to_buf("./tests/binaries/pe/debug_directories-msvc.exe.bin".into());
+    if let Ok(BinResults::Pe(pe_result)) = checksec_core(&buf){
+        let debug_info = pe_result.debug_info;
+        assert_eq!(debug_info.stripped, Stripped::Full);
+        assert_eq!(debug_info.debug_file.as_deref(), Some("THIS-IS-BINARY-FOR-GOBLIN-TESTS"));
+        assert_eq!(debug_info.debug_id.as_deref(), Some("9C584F1FEA3C83003F5700CC36A784DF3"));
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_functions(){
+    let buf = file_to_buf("./tests/binaries/pe/pegoat.exe".into());
+    let pe = PE::parse(&buf).expect("valid PE");
+    let functions = Functions::from_pe(&pe);
+    assert_eq!(functions.len(), 30);
+    assert_eq!(functions[0], Function { start: 0x1_4000_1000, end: 0x1_4000_11c9 });
+    assert!(functions.windows(2).all(|w| w[0].end <= w[1].start));
+}
+
+#[test]
+fn test_functions_oversized_exception_directory(){
+    // an exception directory claiming far more entries than the file holds
+    let mut buf = file_to_buf("./tests/binaries/pe/chkstk.exe.bin".into());
+    let pe = PE::parse(&buf).expect("valid PE");
+    let functions = Functions::from_pe(&pe).len();
+    assert!(functions > 0);
+    drop(pe);
+    // IMAGE_DIRECTORY_ENTRY_EXCEPTION of the PE32+ optional header
+    let pe_header = u32::from_le_bytes(buf[0x3c..0x40].try_into().unwrap()) as usize;
+    let size = pe_header + 24 + 112 + 3 * 8 + 4;
+    buf[size..size + 4].copy_from_slice(&(12_u32 << 24).to_le_bytes());
+    let pe = PE::parse(&buf).expect("valid PE");
+    assert!(Functions::from_pe(&pe).len() >= functions);
+    assert!(checksec_core(&buf).is_ok());
+}
+
+#[test]
+fn test_speculation_mitigations(){
+    let buf = file_to_buf("./tests/binaries/pe/pegoat.exe".into());
+    if let Ok(BinResults::Pe(pe_result)) = checksec_core(&buf){
+        let speculation = pe_result.speculation.expect("x86 code");
+        assert_eq!(speculation.retpoline_thunks, 0);
+        assert_eq!(speculation.lfences, 0);
+        assert!(speculation.indirect_branches > 0);
+        assert!(speculation.sls_candidates >= speculation.sls_hardened);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_sanitizers(){
+    let buf = file_to_buf("./tests/binaries/pe/sanitizers.exe.bin".into());
+    if let Ok(BinResults::Pe(pe_result)) = checksec_core(&buf){
+        assert_eq!(*pe_result.sanitizers, vec![Sanitizer::ASan, Sanitizer::Coverage]);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_no_sanitizers(){
+    let buf = file_to_buf("./tests/binaries/pe/pegoat.exe".into());
+    if let Ok(BinResults::Pe(pe_result)) = checksec_core(&buf){
+        assert!(pe_result.sanitizers.is_empty());
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_dangerous_imports_policy(){
+    let buf = file_to_buf("./tests/binaries/pe/imports.exe.bin".into());
+    let pe = PE::parse(&buf).expect("PE parse failed");
+    let policy = ImportPolicy::from_list("# user list\nmemcpy_s\n\nGetLastError\n");
+    let pe_result = pe::CheckSecResults::parse_with_policy(&pe, &buf, &policy);
+    assert_eq!(pe_result.dangerous_imports, ["GetLastError", "memcpy_s"]);
+}
+
+#[test]
+fn test_manifest(){
+    let buf = file_to_buf("./tests/binaries/pe/resources.exe.bin".into());
+    if let Ok(BinResults::Pe(pe_result)) = checksec_core(&buf){
+        assert_eq!(pe_result.execution_level, ExecutionLevel::RequireAdministrator);
+        assert!(pe_result.ui_access);
+        assert!(pe_result.auto_elevate);
+        assert_eq!(pe_result.sxs_dependencies, ["Microsoft.Windows.Common-Controls"]);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_manifest_as_invoker(){
+    let buf = file_to_buf("./tests/binaries/pe/pegoat.exe".into());
+    if let Ok(BinResults::Pe(pe_result)) = checksec_core(&buf){
+        assert_eq!(pe_result.execution_level, ExecutionLevel::AsInvoker);
+        assert!(!pe_result.ui_access);
+        assert!(!pe_result.auto_elevate);
+        assert!(pe_result.sxs_dependencies.is_empty());
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_manifest_namespace_prefix(){
+    let manifest = Manifest::parse(
+        "<ms_asmv2:requestedExecutionLevel level='highestAvailable' \
+        uiAccess='false'/><autoElevate> false </autoElevate>",
+    );
+    assert_eq!(manifest.execution_level, ExecutionLevel::HighestAvailable);
+    assert!(!manifest.ui_access);
+    assert!(!manifest.auto_elevate);
+}
+
+#[test]
+fn test_version_info(){
+    let buf = file_to_buf("./tests/binaries/pe/resources.exe.bin".into());
+    let pe = PE::parse(&buf).expect("PE parse failed");
+    let version_info = Resources::parse(&pe, &buf)
+        .and_then(|resources| resources.version_info())
+        .expect("version info missing");
+    assert_eq!(version_info.company.as_deref(), Some("Checksec Test"));
+    assert_eq!(version_info.product.as_deref(), Some("checksec resources"));
+    // VS_FIXEDFILEINFO takes precedence over the FileVersion string
+    assert_eq!(version_info.file_version.as_deref(), Some("1.2.3.4"));
+}
+
+#[test]
+fn test_debug_directories_msvc(){
+    let buf = file_to_buf("./tests/binaries/pe/debug_directories-msvc.exe.bin".into());
+    if let Ok(BinResults::Pe(pe_result)) = checksec_core(&buf){
+        let pdb = pe_result.pdb.expect("CodeView missing");
+        assert_eq!(pdb.path, "THIS-IS-BINARY-FOR-GOBLIN-TESTS");
+        assert_eq!(pdb.age, 3);
+        assert_eq!(pdb.guid, "9C584F1F-EA3C-8300-3F57-00CC36A784DF");
+        assert_eq!(pdb.symbol_server_key(), "9C584F1FEA3C83003F5700CC36A784DF3");
+        assert!(pe_result.repro);
+        assert_eq!(pe_result.pogo_entries, 4);
+        let vc_features = pe_result.vc_features.expect("VC features missing");
+        assert_eq!(vc_features.c_and_cplusplus, 1);
+        assert_eq!(vc_features.gs, 0);
+        assert_eq!(vc_features.sdl, 0);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_debug_directories_clang_lld(){
+    let buf = file_to_buf("./tests/binaries/pe/debug_directories-clang_lld.exe.bin".into());
+    if let Ok(BinResults::Pe(pe_result)) = checksec_core(&buf){
+        assert!(pe_result.pdb.is_some());
+        assert!(pe_result.repro);
+        assert_eq!(pe_result.pogo_entries, 0);
+        assert!(pe_result.vc_features.is_none());
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_no_debug_directories(){
+    let buf = file_to_buf("./tests/binaries/pe/no_debug_directories.exe.bin".into());
+    if let Ok(BinResults::Pe(pe_result)) = checksec_core(&buf){
+        assert!(pe_result.pdb.is_none());
+        assert!(!pe_result.repro);
+        assert_ne!(pe_result.timestamp, 0);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_rich_header_toolset(){
+    let buf = file_to_buf("./tests/binaries/pe/pegoat.exe".into());
+    if let Ok(BinResults::Pe(pe_result)) = checksec_core(&buf){
+        let rich = pe_result.rich.expect("Rich header missing");
+        assert_eq!(rich.entries.len(), 10);
+        // linker (0x0102) from VS2019 16.5, before /CETCOMPAT
+        assert!(rich.entries.iter().any(|entry| entry.product == 0x0102 && entry.build == 28610));
+        assert_eq!(rich.toolset.as_deref(), Some("VS2019 16.5 (14.25)"));
+        assert_eq!(rich.unsupported, ["/CETCOMPAT"]);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_rich_header_cetcompat_toolset(){
+    let buf = file_to_buf("./tests/binaries/pe/pegoat-cetcompat.exe".into());
+    if let Ok(BinResults::Pe(pe_result)) = checksec_core(&buf){
+        let rich = pe_result.rich.expect("Rich header missing");
+        assert!(rich.unsupported.is_empty());
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_no_rich_header(){
+    let buf = file_to_buf("./tests/binaries/pe/debug_directories-clang_lld.exe.bin".into());
+    if let Ok(BinResults::Pe(pe_result)) = checksec_core(&buf){
+        assert!(pe_result.rich.is_none());
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
+
+#[test]
+fn test_stack_probe(){
+    let buf = file_to_buf("./tests/binaries/pe/chkstk.exe.bin".into());
+    if let Ok(BinResults::Pe(pe_result)) = checksec_core(&buf){
+        // __chkstk has no symbol, it is recognized by its code
+        assert_eq!(pe_result.stack_probe, StackProbe::Call);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+    let buf = file_to_buf("./tests/binaries/pe/pegoat.exe".into());
+    if let Ok(BinResults::Pe(pe_result)) = checksec_core(&buf){
+        assert_eq!(pe_result.stack_probe, StackProbe::None);
+    }
+    else {
+        panic!("Checksec failed");
+    }
+}
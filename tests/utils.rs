@@ -7,7 +7,7 @@ use std::process;
 pub fn file_to_buf(filename: String) -> Vec<u8>{
     let path = Path::new(&filename);
     if let Ok(buf) = fs::read(path){
-        return buf;
+        buf
     }
     else{
         println!("reading of provided file path failed, test suite is misconfigured");